@@ -0,0 +1,125 @@
+// 用于测试 `todolist-cli caldav` 的本地 CalDAV 替身服务器，数据只保存在内存中
+// 用法: cargo run --example caldav_server -- [--no-etag] [地址] [用户名:密码]
+// 默认地址 127.0.0.1:5232，任务集合可以是任意路径，例如 http://127.0.0.1:5232/tasks/
+// --no-etag 模拟不提供 ETag 的服务器；tests/caldav.rs 也用它做集成测试
+use std::collections::BTreeMap;
+use base64::Engine;
+use tiny_http::{Header, Method, Request, Response, Server};
+
+struct Resource {
+    body: String,
+    etag: String,
+}
+
+fn main() {
+    let (flags, mut args): (Vec<String>, Vec<String>) = std::env::args().skip(1).partition(|arg| arg.starts_with("--"));
+    let etags = !flags.iter().any(|flag| flag == "--no-etag");
+    args.reverse();
+    let address = args.pop().unwrap_or_else(|| "127.0.0.1:5232".to_string());
+    let authorization = args.pop()
+        .map(|credentials| format!("Basic {}", base64::engine::general_purpose::STANDARD.encode(credentials)));
+
+    let server = Server::http(&address).expect("无法监听地址");
+    println!("📅 CalDAV 替身服务器运行在 http://{}/", address);
+    serve(server, authorization, etags);
+}
+
+// 处理请求直到服务器关闭；etags 为 false 时不返回 ETag，也忽略 If-Match
+pub fn serve(server: Server, authorization: Option<String>, etags: bool) {
+    let mut resources: BTreeMap<String, Resource> = BTreeMap::new();
+    let mut version = 0u64;
+    for mut request in server.incoming_requests() {
+        if let Some(expected) = &authorization
+            && header(&request, "Authorization").as_deref() != Some(expected.as_str())
+        {
+            respond(request, Response::from_string("unauthorized").with_status_code(401));
+            continue;
+        }
+
+        let path = request.url().split('?').next().unwrap_or("/").to_string();
+        let mut body = String::new();
+        request.as_reader().read_to_string(&mut body).ok();
+        let if_match = header(&request, "If-Match");
+        let if_none_match = header(&request, "If-None-Match");
+        let current = resources.get(&path).map(|r| r.etag.clone());
+        println!("{} {}", request.method(), path);
+
+        let precondition_failed = match (&if_match, &if_none_match) {
+            (Some(expected), _) if etags => current.as_ref() != Some(expected),
+            (None, Some(_)) => current.is_some(),
+            _ => false,
+        };
+
+        let response = match request.method() {
+            Method::NonStandard(method) if method.as_str() == "PROPFIND" => {
+                let collection = if path.ends_with('/') { path.clone() } else { format!("{}/", path) };
+                Response::from_string(multistatus(&collection, &resources, etags))
+                    .with_status_code(207)
+                    .with_header(content_type("application/xml; charset=utf-8"))
+            }
+            Method::Get => match resources.get(&path) {
+                Some(resource) => with_etag(
+                    Response::from_string(resource.body.clone()).with_header(content_type("text/calendar; charset=utf-8")),
+                    etags.then_some(&resource.etag),
+                ),
+                None => Response::from_string("not found").with_status_code(404),
+            },
+            Method::Put if precondition_failed => Response::from_string("precondition failed").with_status_code(412),
+            Method::Put => {
+                version += 1;
+                let etag = format!("\"{}\"", version);
+                let status = if current.is_some() { 204 } else { 201 };
+                resources.insert(path, Resource { body, etag: etag.clone() });
+                with_etag(Response::from_string("").with_status_code(status), etags.then_some(&etag))
+            }
+            Method::Delete if precondition_failed => Response::from_string("precondition failed").with_status_code(412),
+            Method::Delete => match resources.remove(&path) {
+                Some(_) => Response::from_string("").with_status_code(204),
+                None => Response::from_string("not found").with_status_code(404),
+            },
+            _ => Response::from_string("method not allowed").with_status_code(405),
+        };
+        respond(request, response);
+    }
+}
+
+fn multistatus(collection: &str, resources: &BTreeMap<String, Resource>, etags: bool) -> String {
+    let mut xml = String::from(r#"<?xml version="1.0" encoding="utf-8"?><d:multistatus xmlns:d="DAV:">"#);
+    xml.push_str(&format!("<d:response><d:href>{}</d:href><d:propstat><d:prop><d:resourcetype><d:collection/></d:resourcetype></d:prop><d:status>HTTP/1.1 200 OK</d:status></d:propstat></d:response>", collection));
+    for (path, resource) in resources.range(collection.to_string()..).take_while(|(path, _)| path.starts_with(collection)) {
+        let etag = match etags {
+            true => format!("<d:getetag>{}</d:getetag>", resource.etag.replace('"', "&quot;")),
+            false => String::new(),
+        };
+        xml.push_str(&format!(
+            "<d:response><d:href>{}</d:href><d:propstat><d:prop>{}</d:prop><d:status>HTTP/1.1 200 OK</d:status></d:propstat></d:response>",
+            path,
+            etag,
+        ));
+    }
+    xml.push_str("</d:multistatus>");
+    xml
+}
+
+fn header(request: &Request, name: &str) -> Option<String> {
+    request.headers().iter()
+        .find(|h| h.field.as_str().as_str().eq_ignore_ascii_case(name))
+        .map(|h| h.value.as_str().to_string())
+}
+
+fn content_type(value: &str) -> Header {
+    Header::from_bytes("Content-Type", value).unwrap()
+}
+
+fn with_etag<R: std::io::Read>(response: Response<R>, etag: Option<&String>) -> Response<R> {
+    match etag {
+        Some(etag) => response.with_header(Header::from_bytes("ETag", etag.as_str()).unwrap()),
+        None => response,
+    }
+}
+
+fn respond<R: std::io::Read>(request: Request, response: Response<R>) {
+    if let Err(err) = request.respond(response) {
+        eprintln!("响应失败: {}", err);
+    }
+}
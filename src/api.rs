@@ -0,0 +1,362 @@
+use std::str::FromStr;
+use serde::{Deserialize, Deserializer, Serialize};
+use serde_json::{json, Value};
+use chrono::Utc;
+use crate::error::{Result, TodoError};
+use crate::export;
+use crate::stats::Statistics;
+use crate::task::{self, Task, TaskStatus, TaskUpdate};
+use crate::task_list::TaskList;
+
+// 对外提供的任务结构 (REST API、JSON 输出等)，字段保持稳定
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct TaskRecord {
+    pub id: usize,
+    pub uuid: String,
+    pub description: String,
+    // pending / suspended / completed
+    pub status: String,
+    pub created_at: String,
+    pub completed_at: Option<String>,
+    pub tags: Vec<String>,
+    pub position: usize,
+    // 如 "30m"、"2h"、"3pt"
+    pub estimate: Option<String>,
+    pub pomodoros: u32,
+    pub tracked_seconds: i64,
+    pub timer_running: bool,
+    // YYYY-MM-DD
+    pub due: Option<String>,
+    // high / medium / low
+    pub priority: Option<String>,
+    // 重复周期，如 "1d"、"2w"、"1m"
+    pub repeat: Option<String>,
+    // 备注，可以有多行，没有时为空字符串
+    pub notes: String,
+}
+
+// 错误信息，code 来自 TodoError::code
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ErrorRecord {
+    pub code: String,
+    pub message: String,
+}
+
+// 新建任务的参数 (REST 请求体、RPC 参数)
+#[derive(Debug, Clone, Deserialize)]
+pub struct NewTask {
+    pub description: String,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    pub status: Option<String>,
+    pub estimate: Option<String>,
+    pub due: Option<String>,
+    pub priority: Option<String>,
+    pub repeat: Option<String>,
+    pub notes: Option<String>,
+}
+
+// 修改任务的参数，未出现的字段保持不变，estimate、due、priority、repeat 为 null 时清除，notes 为 null 或空字符串时清除
+#[derive(Debug, Clone, Deserialize)]
+pub struct TaskPatch {
+    pub description: Option<String>,
+    pub status: Option<String>,
+    pub tags: Option<Vec<String>>,
+    #[serde(default, deserialize_with = "nullable")]
+    pub estimate: Option<Option<String>>,
+    #[serde(default, deserialize_with = "nullable")]
+    pub due: Option<Option<String>>,
+    #[serde(default, deserialize_with = "nullable")]
+    pub priority: Option<Option<String>>,
+    #[serde(default, deserialize_with = "nullable")]
+    pub repeat: Option<Option<String>>,
+    #[serde(default, deserialize_with = "nullable")]
+    pub notes: Option<Option<String>>,
+}
+
+// 区分字段缺失 (None) 和显式的 null (Some(None))
+fn nullable<'de, D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Option<Option<String>>, D::Error> {
+    Option::deserialize(deserializer).map(Some)
+}
+
+impl NewTask {
+    // 添加到任务列表，返回新任务的ID，不负责保存
+    pub fn add_to(self, task_list: &mut TaskList) -> Result<usize> {
+        let description = self.description.trim().to_string();
+        if description.is_empty() {
+            return Err(TodoError::InvalidInput("任务描述不能为空".to_string()));
+        }
+        let update = TaskUpdate {
+            description: None,
+            status: self.status.map(|s| s.parse()).transpose()?,
+            tags: Some(self.tags),
+            estimate: self.estimate.map(|e| e.parse().map(Some)).transpose()?,
+            due: self.due.map(|d| task::parse_due(&d).map(Some)).transpose()?,
+            priority: self.priority.map(|p| p.parse().map(Some)).transpose()?,
+            recurrence: self.repeat.map(|r| r.parse().map(Some)).transpose()?,
+            notes: self.notes,
+        };
+        let id = task_list.add_task(description);
+        task_list.update_task(id, update)?;
+        Ok(id)
+    }
+
+    // PUT 整体替换已有任务：未给出的字段清除，状态默认为 pending；
+    // 创建时间、计时记录和番茄数由本工具维护，不受影响
+    pub fn into_replacement(self) -> Result<TaskUpdate> {
+        Ok(TaskUpdate {
+            description: Some(self.description),
+            status: Some(self.status.map_or(Ok(TaskStatus::Pending), |s| s.parse())?),
+            tags: Some(self.tags),
+            estimate: Some(self.estimate.map(|e| e.parse()).transpose()?),
+            due: Some(self.due.map(|d| task::parse_due(&d)).transpose()?),
+            priority: Some(self.priority.map(|p| p.parse()).transpose()?),
+            recurrence: Some(self.repeat.map(|r| r.parse()).transpose()?),
+            notes: Some(self.notes.unwrap_or_default()),
+        })
+    }
+}
+
+impl TaskPatch {
+    pub fn into_update(self) -> Result<TaskUpdate> {
+        Ok(TaskUpdate {
+            description: self.description,
+            status: self.status.map(|s| s.parse()).transpose()?,
+            tags: self.tags,
+            estimate: parse_nullable(self.estimate, str::parse)?,
+            due: parse_nullable(self.due, task::parse_due)?,
+            priority: parse_nullable(self.priority, str::parse)?,
+            recurrence: parse_nullable(self.repeat, str::parse)?,
+            notes: self.notes.map(Option::unwrap_or_default),
+        })
+    }
+}
+
+// null 保持为 Some(None)，即清除该字段
+fn parse_nullable<T>(value: Option<Option<String>>, parse: impl Fn(&str) -> Result<T>) -> Result<Option<Option<T>>> {
+    value.map(|value| value.as_deref().map(parse).transpose()).transpose()
+}
+
+// 任务可以用数字ID、uuid:<前缀> 或 UUID (前缀) 指定
+pub fn resolve_task(task_list: &TaskList, selector: &str) -> Result<usize> {
+    let selector = selector.trim();
+    match selector.parse() {
+        Ok(id) => task_list.get_task(id).map(|task| task.id()).ok_or(TodoError::TaskNotFound(id)),
+        Err(_) => task_list.resolve_uuid(selector.strip_prefix("uuid:").unwrap_or(selector)),
+    }
+}
+
+pub fn task_records(task_list: &TaskList) -> Vec<TaskRecord> {
+    task_list.get_tasks().iter().map(TaskRecord::from).collect()
+}
+
+impl From<&Task> for TaskRecord {
+    fn from(task: &Task) -> Self {
+        TaskRecord {
+            id: task.id(),
+            uuid: task.uuid().to_string(),
+            description: task.description().to_string(),
+            status: export::status_name(task.status()).to_string(),
+            created_at: task.created_at().to_string(),
+            completed_at: task.completed_at().map(str::to_string),
+            tags: task.tags().to_vec(),
+            position: task.position(),
+            estimate: task.estimate().map(|e| e.to_string()),
+            pomodoros: task.pomodoros(),
+            tracked_seconds: task.tracked_seconds(Utc::now()),
+            timer_running: task.is_timer_running(),
+            due: task.due().map(|d| d.to_string()),
+            priority: task.priority().map(|p| p.name().to_string()),
+            repeat: task.recurrence().map(|r| r.to_string()),
+            notes: task.notes().to_string(),
+        }
+    }
+}
+
+impl From<&TodoError> for ErrorRecord {
+    fn from(err: &TodoError) -> Self {
+        ErrorRecord {
+            code: err.code().to_string(),
+            message: err.to_string(),
+        }
+    }
+}
+
+// 统计结果，字段含义同 stats::Statistics，时长以秒为单位
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct StatsRecord {
+    pub total: usize,
+    pub pending: usize,
+    pub suspended: usize,
+    pub completed: usize,
+    pub overdue: usize,
+    pub completion_rate: f64,
+    pub average_lead_time_seconds: Option<i64>,
+    pub current_streak: usize,
+    pub longest_streak: usize,
+    pub pomodoros: u32,
+    pub tracked_seconds: i64,
+}
+
+impl From<&Statistics> for StatsRecord {
+    fn from(stats: &Statistics) -> Self {
+        StatsRecord {
+            total: stats.total,
+            pending: stats.pending,
+            suspended: stats.suspended,
+            completed: stats.completed,
+            overdue: stats.overdue,
+            completion_rate: stats.completion_rate,
+            average_lead_time_seconds: stats.average_lead_time,
+            current_streak: stats.current_streak,
+            longest_streak: stats.longest_streak,
+            pomodoros: stats.pomodoros,
+            tracked_seconds: stats.tracked_seconds,
+        }
+    }
+}
+
+// 命令行输出格式: text 为默认的提示文字，其余用于脚本
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum OutputFormat {
+    #[default]
+    Text,
+    // 整个结果为一个 JSON 值
+    Json,
+    // 每行一个 JSON 对象
+    Jsonl,
+    // 第一行为列名，字段中的制表符、换行和反斜杠转义为 \t、\n、\\
+    Tsv,
+}
+
+// TSV 的列，与 TaskRecord 的字段一致，标签以逗号分隔，空值为空字符串
+pub const TSV_COLUMNS: [&str; 16] = [
+    "id",
+    "uuid",
+    "description",
+    "status",
+    "created_at",
+    "completed_at",
+    "tags",
+    "position",
+    "estimate",
+    "pomodoros",
+    "tracked_seconds",
+    "timer_running",
+    "due",
+    "priority",
+    "repeat",
+    "notes",
+];
+
+impl OutputFormat {
+    pub fn is_text(&self) -> bool {
+        *self == OutputFormat::Text
+    }
+
+    // 任务列表; text 格式由调用方自行输出，这里按 json 处理
+    pub fn render_tasks(&self, tasks: &[TaskRecord]) -> String {
+        let rows: Vec<Value> = tasks.iter().map(|task| json!(task)).collect();
+        self.render_rows(&TSV_COLUMNS, &rows)
+    }
+
+    // 单个对象，例如统计结果；tsv 输出列名行和一行数据
+    pub fn render_object<T: Serialize>(&self, value: &T) -> String {
+        let value = json!(value);
+        if *self == OutputFormat::Json || *self == OutputFormat::Text {
+            return serde_json::to_string_pretty(&value).unwrap_or_default();
+        }
+        let columns: Vec<&str> = value.as_object().map(|o| o.keys().map(String::as_str).collect()).unwrap_or_default();
+        let rows = [value.clone()];
+        self.render_rows(&columns, &rows)
+    }
+
+    // 错误对象 {"code", "message"}，tsv 为一行 code<TAB>message
+    pub fn render_error(&self, err: &TodoError) -> String {
+        let record = ErrorRecord::from(err);
+        match self {
+            OutputFormat::Tsv => format!("{}\t{}", record.code, tsv_field(&json!(record.message))),
+            _ => json!(record).to_string(),
+        }
+    }
+
+    fn render_rows(&self, columns: &[&str], rows: &[Value]) -> String {
+        match self {
+            OutputFormat::Text | OutputFormat::Json => serde_json::to_string_pretty(rows).unwrap_or_default(),
+            OutputFormat::Jsonl => rows.iter().map(|row| format!("{}\n", row)).collect::<String>().trim_end().to_string(),
+            OutputFormat::Tsv => {
+                let mut lines = vec![columns.join("\t")];
+                for row in rows {
+                    let fields: Vec<String> = columns.iter().map(|column| tsv_field(&row[*column])).collect();
+                    lines.push(fields.join("\t"));
+                }
+                lines.join("\n")
+            }
+        }
+    }
+}
+
+fn tsv_field(value: &Value) -> String {
+    let text = match value {
+        Value::Null => String::new(),
+        Value::String(text) => text.clone(),
+        Value::Array(items) => items.iter().map(|item| item.as_str().map_or_else(|| item.to_string(), str::to_string)).collect::<Vec<_>>().join(","),
+        other => other.to_string(),
+    };
+    text.replace('\\', "\\\\").replace('\t', "\\t").replace('\n', "\\n").replace('\r', "\\r")
+}
+
+impl FromStr for OutputFormat {
+    type Err = TodoError;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "text" => Ok(OutputFormat::Text),
+            "json" => Ok(OutputFormat::Json),
+            "jsonl" | "ndjson" => Ok(OutputFormat::Jsonl),
+            "tsv" => Ok(OutputFormat::Tsv),
+            other => Err(TodoError::InvalidInput(format!("未知的输出格式: {}，可选 text、json、jsonl、tsv", other))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::task::Priority;
+
+    #[test]
+    fn patch_distinguishes_missing_and_null_fields() {
+        let patch: TaskPatch = serde_json::from_str(r#"{"description":"写周报","due":null,"priority":"high"}"#).unwrap();
+        let update = patch.into_update().unwrap();
+        assert_eq!(update.description.as_deref(), Some("写周报"));
+        assert_eq!(update.due, Some(None));
+        assert_eq!(update.priority, Some(Some(Priority::High)));
+        assert!(update.estimate.is_none() && update.tags.is_none() && update.status.is_none() && update.notes.is_none());
+        let cleared: TaskPatch = serde_json::from_str(r#"{"notes":null}"#).unwrap();
+        assert_eq!(cleared.into_update().unwrap().notes.as_deref(), Some(""));
+        let invalid: TaskPatch = serde_json::from_str(r#"{"due":"someday"}"#).unwrap();
+        assert!(invalid.into_update().is_err());
+    }
+
+    #[test]
+    fn replacement_clears_missing_fields() {
+        let replacement: NewTask = serde_json::from_str(r#"{"description":"写月报"}"#).unwrap();
+        let update = replacement.into_replacement().unwrap();
+        assert_eq!(update.status, Some(TaskStatus::Pending));
+        assert_eq!(update.tags, Some(Vec::new()));
+        assert_eq!((update.estimate, update.due, update.priority, update.recurrence), (Some(None), Some(None), Some(None), Some(None)));
+        assert_eq!(update.notes.as_deref(), Some(""));
+    }
+
+    #[test]
+    fn resolves_ids_and_uuid_prefixes() {
+        let mut task_list = TaskList::new();
+        let id = task_list.add_task("写报告".to_string());
+        let uuid = task_list.get_task(id).unwrap().uuid().to_string();
+        assert_eq!(resolve_task(&task_list, &id.to_string()).unwrap(), id);
+        assert_eq!(resolve_task(&task_list, &format!("uuid:{}", &uuid[..8])).unwrap(), id);
+        assert_eq!(resolve_task(&task_list, &uuid).unwrap(), id);
+        assert!(matches!(resolve_task(&task_list, "42"), Err(TodoError::TaskNotFound(42))));
+    }
+}
@@ -0,0 +1,547 @@
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::Path;
+use std::str::FromStr;
+use std::time::Duration;
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+use crate::error::{Result, TodoError};
+use crate::ical;
+use crate::task::{Task, TaskUpdate};
+use crate::task_list::TaskList;
+
+const CONFIG_FILE: &str = "caldav.json";
+const STATE_FILE: &str = "caldav_state.json";
+// 密码不写入配置文件
+pub const PASSWORD_ENV: &str = "TODOLIST_CALDAV_PASSWORD";
+
+// 任务集合的地址，例如 http://127.0.0.1:5232/user/tasks/
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CalDavConfig {
+    pub url: String,
+    pub username: Option<String>,
+    #[serde(skip)]
+    pub password: Option<String>,
+}
+
+// 上次同步时每个任务的状态：本地ID与远端资源的对应关系、ETag 以及任务内容
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SyncState {
+    pub url: String,
+    pub entries: Vec<SyncEntry>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SyncEntry {
+    // 任务按 UUID 对应，task_id 仅用于显示；旧的状态文件没有 UUID，同步时按ID补上
+    #[serde(default)]
+    pub task_uuid: String,
+    pub task_id: usize,
+    pub uid: String,
+    pub href: String,
+    pub etag: Option<String>,
+    pub fingerprint: String,
+    // 上次同步时远端的内容 (不含 DTSTAMP)，服务器不提供 ETag 时用来判断远端是否被修改
+    #[serde(default)]
+    pub remote_content: Option<String>,
+}
+
+// 两边都修改过同一任务时的处理方式
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum ConflictPolicy {
+    // 只报告冲突，两边都不动，下次同步仍会检测到
+    #[default]
+    Skip,
+    PreferLocal,
+    PreferRemote,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Conflict {
+    pub task_id: Option<usize>,
+    pub uid: String,
+    pub reason: &'static str,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct SyncReport {
+    pub pushed: usize,
+    pub pulled: usize,
+    pub created_remote: usize,
+    pub created_local: usize,
+    pub deleted_remote: usize,
+    pub deleted_local: usize,
+    // 没有同步记录时按 UID 对应上的任务
+    pub matched: usize,
+    pub conflicts: Vec<Conflict>,
+}
+
+impl CalDavConfig {
+    pub fn load() -> Result<Option<Self>> {
+        if !Path::new(CONFIG_FILE).exists() {
+            return Ok(None);
+        }
+        Ok(Some(serde_json::from_str(&fs::read_to_string(CONFIG_FILE)?)?))
+    }
+
+    pub fn save(&self) -> Result<()> {
+        fs::write(CONFIG_FILE, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+}
+
+impl SyncState {
+    // 服务器地址变了之前的对应关系就没有意义了
+    pub fn load(url: &str) -> Result<Self> {
+        let state: SyncState = if Path::new(STATE_FILE).exists() {
+            serde_json::from_str(&fs::read_to_string(STATE_FILE)?)?
+        } else {
+            SyncState::default()
+        };
+        if state.url == url {
+            Ok(state)
+        } else {
+            Ok(SyncState { url: url.to_string(), entries: Vec::new() })
+        }
+    }
+
+    pub fn save(&self) -> Result<()> {
+        fs::write(STATE_FILE, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+}
+
+// PUT / DELETE 的结果，Conflict 表示 ETag 不匹配 (412)
+enum WriteResult {
+    Done(Option<String>),
+    Conflict,
+}
+
+// PUT 的前提条件
+#[derive(Clone, Copy)]
+enum Precondition<'a> {
+    // 只允许新建
+    Absent,
+    // ETag 匹配时才覆盖；不知道 ETag (服务器不提供) 时直接覆盖
+    Matches(Option<&'a str>),
+}
+
+pub struct CalDavClient {
+    agent: ureq::Agent,
+    collection: String,
+    origin: String,
+    authorization: Option<String>,
+}
+
+impl CalDavClient {
+    pub fn new(config: &CalDavConfig) -> Result<Self> {
+        let collection = if config.url.ends_with('/') { config.url.clone() } else { format!("{}/", config.url) };
+        let origin = collection.split_once("://")
+            .map(|(scheme, rest)| format!("{}://{}", scheme, rest.split('/').next().unwrap_or(rest)))
+            .ok_or_else(|| TodoError::InvalidInput(format!("无效的 CalDAV 地址: {}", config.url)))?;
+        let authorization = config.username.as_ref().map(|user| {
+            let credentials = format!("{}:{}", user, config.password.as_deref().unwrap_or(""));
+            format!("Basic {}", base64::engine::general_purpose::STANDARD.encode(credentials))
+        });
+        Ok(CalDavClient {
+            agent: ureq::AgentBuilder::new().timeout(Duration::from_secs(30)).build(),
+            collection,
+            origin,
+            authorization,
+        })
+    }
+
+    fn request(&self, method: &str, href: &str) -> ureq::Request {
+        let url = if href.starts_with('/') { format!("{}{}", self.origin, href) } else { href.to_string() };
+        let request = self.agent.request(method, &url);
+        match &self.authorization {
+            Some(auth) => request.set("Authorization", auth),
+            None => request,
+        }
+    }
+
+    // 集合中所有 .ics 资源的 href -> ETag
+    fn list(&self) -> Result<HashMap<String, Option<String>>> {
+        let body = r#"<?xml version="1.0" encoding="utf-8"?><d:propfind xmlns:d="DAV:"><d:prop><d:getetag/></d:prop></d:propfind>"#;
+        let response = self.request("PROPFIND", &self.collection)
+            .set("Depth", "1")
+            .set("Content-Type", "application/xml; charset=utf-8")
+            .send_string(body)?;
+        let xml = response.into_string()?;
+        Ok(xml_elements(&xml, "response").into_iter()
+            .filter_map(|response| {
+                let href = xml_elements(response, "href").into_iter().next()?.trim().to_string();
+                let etag = xml_elements(response, "getetag").into_iter().next().map(|e| unescape_xml(e.trim()));
+                href.ends_with(".ics").then_some((href, etag))
+            })
+            .collect())
+    }
+
+    fn get(&self, href: &str) -> Result<String> {
+        Ok(self.request("GET", href).call()?.into_string()?)
+    }
+
+    fn put(&self, href: &str, body: &str, precondition: Precondition) -> Result<WriteResult> {
+        let request = self.request("PUT", href).set("Content-Type", "text/calendar; charset=utf-8");
+        let request = match precondition {
+            Precondition::Absent => request.set("If-None-Match", "*"),
+            Precondition::Matches(Some(etag)) => request.set("If-Match", etag),
+            Precondition::Matches(None) => request,
+        };
+        match request.send_string(body) {
+            Ok(response) => Ok(WriteResult::Done(response.header("ETag").map(str::to_string))),
+            Err(ureq::Error::Status(412, _)) => Ok(WriteResult::Conflict),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    fn delete(&self, href: &str, etag: Option<&str>) -> Result<WriteResult> {
+        let request = self.request("DELETE", href);
+        let request = match etag {
+            Some(etag) => request.set("If-Match", etag),
+            None => request,
+        };
+        match request.call() {
+            Ok(_) | Err(ureq::Error::Status(404, _)) => Ok(WriteResult::Done(None)),
+            Err(ureq::Error::Status(412, _)) => Ok(WriteResult::Conflict),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    fn href_for(&self, uid: &str) -> String {
+        let name: String = uid.chars().map(|c| if c.is_ascii_alphanumeric() || "-_.".contains(c) { c } else { '_' }).collect();
+        // 与 PROPFIND 返回的 href 一致，只保留路径部分
+        format!("{}{}.ics", &self.collection[self.origin.len()..], name)
+    }
+}
+
+// 双向同步：推送本地修改、拉取远端修改、两边新建和删除的任务互相同步
+pub fn sync(task_list: &mut TaskList, client: &CalDavClient, state: &mut SyncState, policy: ConflictPolicy) -> Result<SyncReport> {
+    let remote = client.list()?;
+    let mut report = SyncReport::default();
+    let mut entries = Vec::new();
+    let mut deleted_local = Vec::new();
+    for entry in state.entries.iter_mut().filter(|e| e.task_uuid.is_empty()) {
+        if let Some(task) = task_list.get_task(entry.task_id) {
+            entry.task_uuid = task.uuid().to_string();
+        }
+    }
+    let known_uuids: HashSet<String> = state.entries.iter().map(|e| e.task_uuid.clone()).collect();
+    let known_hrefs: HashSet<String> = state.entries.iter().map(|e| e.href.clone()).collect();
+
+    for mut entry in state.entries.drain(..) {
+        let local = task_list.find_by_uuid(&entry.task_uuid).cloned();
+        if let Some(task) = &local {
+            entry.task_id = task.id();
+        }
+        let local_changed = local.as_ref().is_some_and(|t| ical::fingerprint(t) != entry.fingerprint);
+        let remote_etag = remote.get(&entry.href).cloned();
+        // 服务器不提供 ETag 时只能取回内容比较
+        let mut content = None;
+        let remote_changed = match &remote_etag {
+            None => false,
+            Some(Some(etag)) => entry.etag.as_ref() != Some(etag),
+            Some(None) => {
+                let body = client.get(&entry.href)?;
+                let changed = entry.remote_content.as_deref() != Some(ical::content_key(&body).as_str());
+                content = Some(body);
+                changed
+            }
+        };
+        let mut fetch = |href: &str| content.take().map_or_else(|| client.get(href), Ok);
+        let (task_id, uid) = (entry.task_id, entry.uid.clone());
+        let conflict = |reason| Conflict { task_id: Some(task_id), uid: uid.clone(), reason };
+
+        match (local, remote_etag) {
+            // 两边都删除了
+            (None, None) => {}
+            (None, Some(etag)) if remote_changed => match policy {
+                ConflictPolicy::PreferRemote => {
+                    let body = fetch(&entry.href)?;
+                    let id = pull_new(task_list, &entry.href, &body)?;
+                    entry.task_id = id;
+                    if let Some(task) = task_list.get_task(id) {
+                        entry.task_uuid = task.uuid().to_string();
+                        entry.fingerprint = ical::fingerprint(task);
+                    }
+                    entry.etag = etag;
+                    entry.remote_content = Some(ical::content_key(&body));
+                    report.created_local += 1;
+                    entries.push(entry);
+                }
+                ConflictPolicy::PreferLocal => {
+                    client.delete(&entry.href, None)?;
+                    report.deleted_remote += 1;
+                }
+                ConflictPolicy::Skip => {
+                    report.conflicts.push(conflict("本地已删除，远端已修改"));
+                    entries.push(entry);
+                }
+            },
+            (None, Some(_)) => match client.delete(&entry.href, entry.etag.as_deref())? {
+                WriteResult::Done(_) => report.deleted_remote += 1,
+                WriteResult::Conflict => {
+                    report.conflicts.push(conflict("本地已删除，远端已修改"));
+                    entries.push(entry);
+                }
+            },
+            (Some(task), None) if local_changed => match policy {
+                ConflictPolicy::PreferLocal => {
+                    if push(client, &mut entry, &task, Precondition::Absent)? {
+                        report.created_remote += 1;
+                        entries.push(entry);
+                    }
+                }
+                ConflictPolicy::PreferRemote => {
+                    deleted_local.push(task.id());
+                    report.deleted_local += 1;
+                }
+                ConflictPolicy::Skip => {
+                    report.conflicts.push(conflict("远端已删除，本地已修改"));
+                    entries.push(entry);
+                }
+            },
+            (Some(task), None) => {
+                deleted_local.push(task.id());
+                report.deleted_local += 1;
+            }
+            (Some(task), Some(etag)) => {
+                let pull = match (local_changed, remote_changed) {
+                    (false, false) => false,
+                    (true, false) => {
+                        let expected = entry.etag.clone();
+                        if push(client, &mut entry, &task, Precondition::Matches(expected.as_deref()))? {
+                            report.pushed += 1;
+                        } else {
+                            report.conflicts.push(conflict("推送时远端已被修改"));
+                        }
+                        false
+                    }
+                    (false, true) => true,
+                    (true, true) => match policy {
+                        ConflictPolicy::PreferLocal => {
+                            if push(client, &mut entry, &task, Precondition::Matches(etag.as_deref()))? {
+                                report.pushed += 1;
+                            } else {
+                                report.conflicts.push(conflict("推送时远端已被修改"));
+                            }
+                            false
+                        }
+                        ConflictPolicy::PreferRemote => true,
+                        ConflictPolicy::Skip => {
+                            report.conflicts.push(conflict("本地和远端都修改了"));
+                            false
+                        }
+                    },
+                };
+                if pull {
+                    let body = fetch(&entry.href)?;
+                    pull_into(task_list, &mut entry, &body, etag)?;
+                    report.pulled += 1;
+                }
+                entries.push(entry);
+            }
+        }
+    }
+
+    // 没有同步记录的远端资源先取回内容，以便按 UID 对应本地任务，
+    // 例如删除了 caldav_state.json 或在另一台设备上已经同步过的任务
+    let mut unknown_remote = Vec::new();
+    for (href, etag) in remote.iter().filter(|(href, _)| !known_hrefs.contains(*href)) {
+        let body = client.get(href)?;
+        if let Some(todo) = ical::parse_calendar(&body).into_iter().next() {
+            unknown_remote.push((href.clone(), etag.clone(), todo, body));
+        }
+    }
+
+    let new_local: Vec<Task> = task_list.get_tasks().iter()
+        .filter(|t| !known_uuids.contains(t.uuid()))
+        .cloned()
+        .collect();
+    for task in new_local {
+        let uid = ical::task_uid(&task);
+        let mut entry = SyncEntry {
+            task_uuid: task.uuid().to_string(),
+            task_id: task.id(),
+            uid: uid.clone(),
+            href: client.href_for(&uid),
+            etag: None,
+            fingerprint: ical::fingerprint(&task),
+            remote_content: None,
+        };
+        let conflict = |reason| Conflict { task_id: Some(task.id()), uid: uid.clone(), reason };
+
+        let Some(index) = unknown_remote.iter().position(|(_, _, todo, _)| todo.uid.as_deref() == Some(uid.as_str())) else {
+            // 本地新建的任务
+            if push(client, &mut entry, &task, Precondition::Absent)? {
+                report.created_remote += 1;
+                entries.push(entry);
+            } else {
+                report.conflicts.push(conflict("远端已存在同名资源"));
+            }
+            continue;
+        };
+
+        // 远端已有同一 UID 的任务：没有上次同步的记录，无从判断哪边较新，
+        // 内容相同时直接对应，不同时按冲突处理
+        let (href, etag, _, body) = unknown_remote.remove(index);
+        entry.href = href;
+        entry.etag = etag.clone();
+        entry.remote_content = Some(ical::content_key(&body));
+        report.matched += 1;
+        if ical::content_key(&ical::to_resource(&task, &uid)) == ical::content_key(&body) {
+            entries.push(entry);
+            continue;
+        }
+        match policy {
+            ConflictPolicy::PreferLocal => {
+                if push(client, &mut entry, &task, Precondition::Matches(etag.as_deref()))? {
+                    report.pushed += 1;
+                    entries.push(entry);
+                } else {
+                    report.conflicts.push(conflict("推送时远端已被修改"));
+                }
+            }
+            ConflictPolicy::PreferRemote => {
+                pull_into(task_list, &mut entry, &body, etag)?;
+                report.pulled += 1;
+                entries.push(entry);
+            }
+            // 不记录对应关系，下次同步时仍会检测到
+            ConflictPolicy::Skip => report.conflicts.push(conflict("本地和远端的同一任务内容不同")),
+        }
+    }
+
+    // 远端新建的任务
+    for (href, etag, todo, body) in unknown_remote {
+        let uid = todo.uid.clone().unwrap_or_else(|| href.clone());
+        let id = task_list.import_task(todo.task);
+        if let Some(task) = task_list.get_task(id) {
+            entries.push(SyncEntry {
+                task_uuid: task.uuid().to_string(),
+                task_id: id,
+                uid,
+                href,
+                etag,
+                fingerprint: ical::fingerprint(task),
+                remote_content: Some(ical::content_key(&body)),
+            });
+        }
+        report.created_local += 1;
+    }
+
+    for id in deleted_local {
+        task_list.delete_task(id);
+    }
+
+    // PUT 的响应没有 ETag 时重新查询一次
+    if entries.iter().any(|e| e.etag.is_none()) {
+        let remote = client.list()?;
+        for entry in entries.iter_mut().filter(|e| e.etag.is_none()) {
+            entry.etag = remote.get(&entry.href).cloned().flatten();
+        }
+    }
+    state.entries = entries;
+    Ok(report)
+}
+
+// 推送本地任务并记录新的 ETag 和内容，远端已被修改 (412) 时返回 false
+fn push(client: &CalDavClient, entry: &mut SyncEntry, task: &Task, precondition: Precondition) -> Result<bool> {
+    let body = ical::to_resource(task, &entry.uid);
+    match client.put(&entry.href, &body, precondition)? {
+        WriteResult::Done(etag) => {
+            entry.etag = etag;
+            entry.fingerprint = ical::fingerprint(task);
+            entry.remote_content = Some(ical::content_key(&body));
+            Ok(true)
+        }
+        WriteResult::Conflict => Ok(false),
+    }
+}
+
+// 用远端内容更新已有任务，保留远端记录的完成时间
+fn pull_into(task_list: &mut TaskList, entry: &mut SyncEntry, body: &str, etag: Option<String>) -> Result<()> {
+    let todo = ical::parse_calendar(body).into_iter().next()
+        .ok_or_else(|| TodoError::SyncError(format!("{} 中没有 VTODO", entry.href)))?;
+    let update = TaskUpdate {
+        description: Some(todo.task.description),
+        status: Some(todo.task.status),
+        tags: Some(todo.task.tags),
+        estimate: Some(todo.task.estimate),
+        due: Some(todo.task.due),
+        priority: Some(todo.task.priority),
+        recurrence: Some(todo.task.recurrence),
+        notes: Some(todo.task.notes),
+    };
+    task_list.update_task(entry.task_id, update)?;
+    if let Some(completed_at) = todo.task.completed_at
+        && let Some(task) = task_list.tasks.iter_mut().find(|t| t.id() == entry.task_id)
+    {
+        task.restore_completed_at(completed_at);
+    }
+    if let Some(task) = task_list.get_task(entry.task_id) {
+        entry.fingerprint = ical::fingerprint(task);
+    }
+    entry.etag = etag;
+    entry.remote_content = Some(ical::content_key(body));
+    Ok(())
+}
+
+fn pull_new(task_list: &mut TaskList, href: &str, body: &str) -> Result<usize> {
+    let todo = ical::parse_calendar(body).into_iter().next()
+        .ok_or_else(|| TodoError::SyncError(format!("{} 中没有 VTODO", href)))?;
+    Ok(task_list.import_task(todo.task))
+}
+
+// 取出所有 <x:name>...</x:name> 的内容，忽略命名空间前缀
+fn xml_elements<'a>(xml: &'a str, name: &str) -> Vec<&'a str> {
+    let mut found = Vec::new();
+    let mut rest = xml;
+    while let Some(start) = rest.find('<') {
+        rest = &rest[start + 1..];
+        let Some(end) = rest.find('>') else { break };
+        let tag = &rest[..end];
+        rest = &rest[end + 1..];
+        let local = tag.split_whitespace().next().unwrap_or("");
+        let local = local.rsplit(':').next().unwrap_or(local);
+        if local != name || tag.ends_with('/') {
+            continue;
+        }
+        let Some(close) = find_closing(rest, name) else { break };
+        found.push(&rest[..close]);
+        rest = &rest[close..];
+    }
+    found
+}
+
+fn find_closing(xml: &str, name: &str) -> Option<usize> {
+    let mut offset = 0;
+    while let Some(start) = xml[offset..].find("</") {
+        let position = offset + start;
+        let tag = &xml[position + 2..];
+        let end = tag.find('>')?;
+        let local = tag[..end].trim();
+        if local.rsplit(':').next() == Some(name) {
+            return Some(position);
+        }
+        offset = position + 2;
+    }
+    None
+}
+
+fn unescape_xml(text: &str) -> String {
+    text.replace("&quot;", "\"").replace("&apos;", "'").replace("&lt;", "<").replace("&gt;", ">").replace("&amp;", "&")
+}
+
+impl FromStr for ConflictPolicy {
+    type Err = TodoError;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "local" | "本地" => Ok(ConflictPolicy::PreferLocal),
+            "remote" | "远端" => Ok(ConflictPolicy::PreferRemote),
+            "skip" => Ok(ConflictPolicy::Skip),
+            other => Err(TodoError::InvalidInput(format!("未知的冲突处理方式: {}", other))),
+        }
+    }
+}
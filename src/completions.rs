@@ -0,0 +1,224 @@
+use std::fmt::Write;
+use std::str::FromStr;
+use crate::error::TodoError;
+use crate::shell::{COMMANDS, FLAGS, FLAG_VALUES, SHELL_COMMANDS};
+use crate::task_list::TaskList;
+
+// shell 补全脚本和手册页，由命令表生成；任务ID和标签在补全时调用
+// `todolist-cli completions ids|tags` 从当前的 tasks.json 读取
+pub const BIN: &str = "todolist-cli";
+
+// 参数为任务ID的命令
+const ID_COMMANDS: [&str; 10] = ["done", "complete", "suspend", "delete", "rm", "edit", "estimate", "move", "start", "time"];
+// 参数为文件的命令
+const FILE_COMMANDS: [&str; 1] = ["import"];
+const SHELLS: [&str; 3] = ["bash", "zsh", "fish"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Shell {
+    Bash,
+    Zsh,
+    Fish,
+}
+
+impl FromStr for Shell {
+    type Err = TodoError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "bash" => Ok(Shell::Bash),
+            "zsh" => Ok(Shell::Zsh),
+            "fish" => Ok(Shell::Fish),
+            other => Err(TodoError::InvalidInput(format!("不支持的 shell: {} (可选 bash|zsh|fish)", other))),
+        }
+    }
+}
+
+impl Shell {
+    pub fn script(self) -> String {
+        match self {
+            Shell::Bash => bash_script(),
+            Shell::Zsh => zsh_script(),
+            Shell::Fish => fish_script(),
+        }
+    }
+}
+
+// 命令行可用的命令 (不含只在交互模式中使用的命令)
+fn commands() -> impl Iterator<Item = &'static (&'static str, &'static str, &'static str)> {
+    COMMANDS.iter().filter(|(name, _, _)| !SHELL_COMMANDS.contains(name))
+}
+
+fn flags_of(command: &str) -> &'static [&'static str] {
+    FLAGS.iter().find(|(name, _)| *name == command).map_or(&[], |(_, flags)| flags)
+}
+
+// 补全时输出的任务ID，每行 "ID<TAB>描述"
+pub fn task_ids(task_list: &TaskList) -> String {
+    task_list.get_tasks().iter()
+        .map(|task| format!("{}\t{}", task.id(), task.description().replace(['\t', '\n', '\r'], " ")))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+// 补全时输出的标签，每行一个
+pub fn tags(task_list: &TaskList) -> String {
+    let mut tags: Vec<&str> = task_list.get_tasks().iter().flat_map(|t| t.tags()).map(String::as_str).collect();
+    tags.sort();
+    tags.dedup();
+    tags.join("\n")
+}
+
+fn bash_script() -> String {
+    let names: Vec<&str> = commands().map(|(name, _, _)| *name).collect();
+    let mut script = String::new();
+    writeln!(script, "# {} 的 bash 补全，由 `{} completions bash` 生成", BIN, BIN).ok();
+    script.push_str("_todolist_cli() {\n");
+    script.push_str("    local cur=\"${COMP_WORDS[COMP_CWORD]}\" prev=\"${COMP_WORDS[COMP_CWORD-1]}\"\n");
+    script.push_str("    if [ \"$COMP_CWORD\" -eq 1 ]; then\n");
+    writeln!(script, "        COMPREPLY=($(compgen -W \"{} --format\" -- \"$cur\"))", names.join(" ")).ok();
+    script.push_str("        return\n    fi\n");
+    script.push_str("    case \"$prev\" in\n");
+    for (flag, values) in FLAG_VALUES {
+        writeln!(script, "        {}) COMPREPLY=($(compgen -W \"{}\" -- \"$cur\")); return ;;", flag, values.join(" ")).ok();
+    }
+    writeln!(script, "        --tags) COMPREPLY=($(compgen -W \"$({} completions tags 2>/dev/null)\" -- \"$cur\")); return ;;", BIN).ok();
+    script.push_str("        -o|--output|--replica) COMPREPLY=($(compgen -f -- \"$cur\")); return ;;\n");
+    script.push_str("    esac\n");
+    script.push_str("    local command=\"${COMP_WORDS[1]}\"\n");
+    script.push_str("    if [[ \"$cur\" == -* ]]; then\n        case \"$command\" in\n");
+    for (command, flags) in FLAGS {
+        writeln!(script, "            {}) COMPREPLY=($(compgen -W \"{}\" -- \"$cur\")) ;;", command, flags.join(" ")).ok();
+    }
+    script.push_str("            *) COMPREPLY=($(compgen -W \"--format\" -- \"$cur\")) ;;\n");
+    script.push_str("        esac\n        return\n    fi\n");
+    script.push_str("    case \"$command\" in\n");
+    writeln!(script, "        {})", ID_COMMANDS.join("|")).ok();
+    writeln!(script, "            COMPREPLY=($(compgen -W \"$({} completions ids 2>/dev/null | cut -f1)\" -- \"$cur\")) ;;", BIN).ok();
+    script.push_str("        tag|untag)\n");
+    script.push_str("            if [ \"$COMP_CWORD\" -eq 2 ]; then\n");
+    writeln!(script, "                COMPREPLY=($(compgen -W \"$({} completions tags 2>/dev/null)\" -- \"$cur\"))", BIN).ok();
+    script.push_str("            else\n");
+    writeln!(script, "                COMPREPLY=($(compgen -W \"$({} completions ids 2>/dev/null | cut -f1)\" -- \"$cur\"))", BIN).ok();
+    script.push_str("            fi ;;\n");
+    writeln!(script, "        {}) COMPREPLY=($(compgen -f -- \"$cur\")) ;;", FILE_COMMANDS.join("|")).ok();
+    writeln!(script, "        completions) COMPREPLY=($(compgen -W \"{}\" -- \"$cur\")) ;;", SHELLS.join(" ")).ok();
+    script.push_str("    esac\n}\n");
+    writeln!(script, "complete -F _todolist_cli {}", BIN).ok();
+    script
+}
+
+fn zsh_script() -> String {
+    let mut script = String::new();
+    writeln!(script, "#compdef {}", BIN).ok();
+    writeln!(script, "# {} 的 zsh 补全，由 `{} completions zsh` 生成", BIN, BIN).ok();
+    script.push_str("_todolist_cli_ids() {\n");
+    writeln!(script, "    local -a ids=(\"${{(@f)$({} completions ids 2>/dev/null)}}\")", BIN).ok();
+    script.push_str("    ids=(\"${ids[@]/$'\\t'/:}\")\n");
+    script.push_str("    _describe '任务' ids\n}\n");
+    script.push_str("_todolist_cli_tags() {\n");
+    writeln!(script, "    local -a tags=(\"${{(@f)$({} completions tags 2>/dev/null)}}\")", BIN).ok();
+    script.push_str("    compadd -a tags\n}\n");
+    script.push_str("_todolist_cli() {\n");
+    script.push_str("    local -a commands=(\n");
+    for (name, _, summary) in commands() {
+        writeln!(script, "        '{}:{}'", name, summary.replace('\'', "'\\''")).ok();
+    }
+    script.push_str("    )\n");
+    script.push_str("    if (( CURRENT == 2 )); then\n        _describe '命令' commands\n        return\n    fi\n");
+    script.push_str("    case ${words[CURRENT-1]} in\n");
+    for (flag, values) in FLAG_VALUES {
+        writeln!(script, "        {}) compadd -- {}; return ;;", flag, values.join(" ")).ok();
+    }
+    script.push_str("        --tags) _todolist_cli_tags; return ;;\n");
+    script.push_str("        -o|--output|--replica) _files; return ;;\n");
+    script.push_str("    esac\n");
+    script.push_str("    if [[ ${words[CURRENT]} == -* ]]; then\n        case ${words[2]} in\n");
+    for (command, flags) in FLAGS {
+        writeln!(script, "            {}) compadd -- {} ;;", command, flags.join(" ")).ok();
+    }
+    script.push_str("            *) compadd -- --format ;;\n");
+    script.push_str("        esac\n        return\n    fi\n");
+    script.push_str("    case ${words[2]} in\n");
+    writeln!(script, "        {}) _todolist_cli_ids ;;", ID_COMMANDS.join("|")).ok();
+    script.push_str("        tag|untag) if (( CURRENT == 3 )); then _todolist_cli_tags; else _todolist_cli_ids; fi ;;\n");
+    writeln!(script, "        {}) _files ;;", FILE_COMMANDS.join("|")).ok();
+    writeln!(script, "        completions) compadd -- {} ;;", SHELLS.join(" ")).ok();
+    script.push_str("    esac\n}\n");
+    // 放在 fpath 中自动加载时直接补全，用 source 加载时注册
+    script.push_str("if [ \"$funcstack[1]\" = \"_todolist_cli\" ]; then\n    _todolist_cli \"$@\"\nelse\n");
+    writeln!(script, "    compdef _todolist_cli {}\nfi", BIN).ok();
+    script
+}
+
+fn fish_script() -> String {
+    let mut script = String::new();
+    writeln!(script, "# {} 的 fish 补全，由 `{} completions fish` 生成", BIN, BIN).ok();
+    writeln!(script, "complete -c {} -f", BIN).ok();
+    writeln!(script, "complete -c {} -l format -x -a 'text json jsonl tsv' -d '输出格式'", BIN).ok();
+    for (name, _, summary) in commands() {
+        writeln!(script, "complete -c {} -n '__fish_use_subcommand' -a {} -d '{}'", BIN, name, summary.replace('\'', "\\'")).ok();
+    }
+    for (command, flags) in FLAGS {
+        for flag in flags.iter() {
+            let values = FLAG_VALUES.iter().find(|(name, _)| name == flag).map(|(_, values)| values.join(" "));
+            let option = match flag.strip_prefix("--") {
+                Some(long) => format!("-l {}", long),
+                None => format!("-s {}", flag.trim_start_matches('-')),
+            };
+            let argument = match (values, *flag) {
+                (Some(values), _) => format!(" -x -a '{}'", values),
+                (None, "--tags") => format!(" -x -a '({} completions tags 2>/dev/null)'", BIN),
+                (None, "--output" | "--replica") => " -r -F".to_string(),
+                _ => String::new(),
+            };
+            writeln!(script, "complete -c {} -n '__fish_seen_subcommand_from {}' {}{}", BIN, command, option, argument).ok();
+        }
+    }
+    writeln!(script, "complete -c {} -n '__fish_seen_subcommand_from {}' -a '({} completions ids 2>/dev/null)'", BIN, ID_COMMANDS.join(" "), BIN).ok();
+    writeln!(script, "complete -c {} -n '__fish_seen_subcommand_from tag untag; and test (count (commandline -opc)) -eq 2' -a '({} completions tags 2>/dev/null)'", BIN, BIN).ok();
+    writeln!(script, "complete -c {} -n '__fish_seen_subcommand_from tag untag; and test (count (commandline -opc)) -gt 2' -a '({} completions ids 2>/dev/null)'", BIN, BIN).ok();
+    writeln!(script, "complete -c {} -n '__fish_seen_subcommand_from {}' -F", BIN, FILE_COMMANDS.join(" ")).ok();
+    writeln!(script, "complete -c {} -n '__fish_seen_subcommand_from completions' -a '{}'", BIN, SHELLS.join(" ")).ok();
+    script
+}
+
+// roff 中 - 和 \ 需要转义
+fn roff(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('-', "\\-")
+}
+
+// man 1 格式的手册页
+pub fn man_page() -> String {
+    let mut page = String::new();
+    writeln!(page, ".TH TODOLIST\\-CLI 1 \"\" \"{} {}\" \"用户命令\"", BIN, env!("CARGO_PKG_VERSION")).ok();
+    page.push_str(".SH 名称\n");
+    writeln!(page, "{} \\- 一个简单的命令行任务管理工具", roff(BIN)).ok();
+    page.push_str(".SH 概要\n");
+    writeln!(page, ".B {}", roff(BIN)).ok();
+    page.push_str("[\\fB\\-\\-format\\fR \\fItext|json|jsonl|tsv\\fR] [\\fI命令\\fR [\\fI参数\\fR...]]\n");
+    page.push_str(".SH 描述\n");
+    page.push_str("不带参数运行时进入交互模式，支持行编辑、历史记录和 Tab 补全，可用命令与下面相同，另有 help、clear 和 exit。\n");
+    page.push_str("带参数时执行一条命令后退出。任务可以用数字ID、\\fBuuid:\\fR前缀或完整 UUID 指定，批量命令还接受ID范围 (如 7\\-10) 和 \\fBtag:\\fR、\\fBstatus:\\fR、\\fBtext:\\fR 查询条件。\n");
+    page.push_str(".SH 命令\n");
+    for (name, args, summary) in commands() {
+        writeln!(page, ".TP\n\\fB{}\\fR \\fI{}\\fR\n{}", roff(name), roff(args), roff(summary)).ok();
+        let flags = flags_of(name);
+        if !flags.is_empty() {
+            let flags: Vec<String> = flags.iter().map(|flag| format!("\\fB{}\\fR", roff(flag))).collect();
+            writeln!(page, ".br\n选项: {}", flags.join(", ")).ok();
+        }
+    }
+    page.push_str(".SH 选项\n.TP\n.B \\-\\-format \\fItext|json|jsonl|tsv\\fR\n");
+    page.push_str("以机器可读格式输出 list、stats 和修改任务的命令的结果；出错时向标准错误输出 {\"code\", \"message\"}。\n");
+    page.push_str(".SH 补全\n");
+    writeln!(page, "bash: \\fBsource <({} completions bash)\\fR", roff(BIN)).ok();
+    page.push_str(".br\n");
+    writeln!(page, "zsh: \\fB{} completions zsh > \"${{fpath[1]}}/_{}\"\\fR", roff(BIN), roff(BIN)).ok();
+    page.push_str(".br\n");
+    writeln!(page, "fish: \\fB{} completions fish > ~/.config/fish/completions/{}.fish\\fR", roff(BIN), roff(BIN)).ok();
+    page.push_str(".SH 文件\n.TP\n.B tasks.json\n当前目录中的任务数据。\n.TP\n.B .todolist_history\n交互模式的历史记录。\n");
+    page.push_str(".SH 环境变量\n.TP\n.B TODOLIST_API_TOKEN\nserve 的访问令牌。\n.TP\n.B TODOLIST_CALDAV_PASSWORD\ncaldav 的密码。\n");
+    page.push_str(".SH 退出状态\n成功时为 0，出错时为 1。\n");
+    page
+}
@@ -5,8 +5,11 @@ use std::fmt;
 pub enum TodoError {
     IoError(std::io::Error),
     JsonError(serde_json::Error),
+    CsvError(csv::Error),
     TaskNotFound(usize),
     InvalidInput(String),
+    SyncError(String),
+    GitError(String),
 }
 
 // 实现Display trait用于错误显示
@@ -15,8 +18,36 @@ impl fmt::Display for TodoError {
         match self {
             TodoError::IoError(err) => write!(f, "IO错误: {}", err),
             TodoError::JsonError(err) => write!(f, "JSON解析错误: {}", err),
+            TodoError::CsvError(err) => write!(f, "CSV错误: {}", err),
             TodoError::TaskNotFound(id) => write!(f, "未找到ID为{}的任务", id),
             TodoError::InvalidInput(msg) => write!(f, "输入无效: {}", msg),
+            TodoError::SyncError(msg) => write!(f, "同步失败: {}", msg),
+            TodoError::GitError(msg) => write!(f, "Git错误: {}", msg),
+        }
+    }
+}
+
+impl TodoError {
+    // 机器可读的错误码，供 API 和脚本使用
+    pub fn code(&self) -> &'static str {
+        match self {
+            TodoError::IoError(_) => "io_error",
+            TodoError::JsonError(_) => "invalid_json",
+            TodoError::CsvError(_) => "invalid_csv",
+            TodoError::TaskNotFound(_) => "task_not_found",
+            TodoError::InvalidInput(_) => "invalid_input",
+            TodoError::SyncError(_) => "sync_failed",
+            TodoError::GitError(_) => "git_error",
+        }
+    }
+
+    // 对应的 HTTP 状态码
+    pub fn http_status(&self) -> u16 {
+        match self {
+            TodoError::JsonError(_) | TodoError::CsvError(_) | TodoError::InvalidInput(_) => 400,
+            TodoError::TaskNotFound(_) => 404,
+            TodoError::SyncError(_) => 502,
+            TodoError::IoError(_) | TodoError::GitError(_) => 500,
         }
     }
 }
@@ -38,4 +69,28 @@ impl From<serde_json::Error> for TodoError {
     }
 }
 
+// 从csv::Error转换
+impl From<csv::Error> for TodoError {
+    fn from(err: csv::Error) -> Self {
+        TodoError::CsvError(err)
+    }
+}
+
+// 从ureq::Error转换
+impl From<ureq::Error> for TodoError {
+    fn from(err: ureq::Error) -> Self {
+        TodoError::SyncError(err.to_string())
+    }
+}
+
+// 从rustyline的ReadlineError转换
+impl From<rustyline::error::ReadlineError> for TodoError {
+    fn from(err: rustyline::error::ReadlineError) -> Self {
+        match err {
+            rustyline::error::ReadlineError::Io(err) => TodoError::IoError(err),
+            other => TodoError::IoError(std::io::Error::other(other.to_string())),
+        }
+    }
+}
+
 pub type Result<T> = std::result::Result<T, TodoError>;
\ No newline at end of file
@@ -0,0 +1,213 @@
+use std::fmt;
+use std::str::FromStr;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use crate::error::TodoError;
+use crate::task::Task;
+
+// 工作量预估：时间（分钟）或故事点
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum Estimate {
+    Minutes(u32),
+    Points(f64),
+}
+
+// 剩余（未完成任务的）预估工作量，时间和故事点分开累计
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct EstimateTotals {
+    pub minutes: u32,
+    pub points: f64,
+    pub tasks: usize,
+}
+
+// 单个任务的预估与实际用时
+#[derive(Debug, Clone)]
+pub struct EstimateComparison<'a> {
+    pub task: &'a Task,
+    pub estimate: Estimate,
+    pub actual_seconds: i64,
+}
+
+impl EstimateComparison<'_> {
+    // 实际 / 预估，仅时间预估可比
+    pub fn ratio(&self) -> Option<f64> {
+        match self.estimate {
+            Estimate::Minutes(minutes) if minutes > 0 => {
+                Some(self.actual_seconds as f64 / (f64::from(minutes) * 60.0))
+            }
+            _ => None,
+        }
+    }
+}
+
+impl EstimateTotals {
+    pub fn add(&mut self, estimate: Estimate) {
+        match estimate {
+            // 预估值可能很大，累计时不溢出
+            Estimate::Minutes(minutes) => self.minutes = self.minutes.saturating_add(minutes),
+            Estimate::Points(points) => self.points += points,
+        }
+        self.tasks += 1;
+    }
+}
+
+impl fmt::Display for EstimateTotals {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let (minutes, points) = (Estimate::Minutes(self.minutes), Estimate::Points(self.points));
+        match (self.minutes > 0, self.points > 0.0) {
+            (false, false) => write!(f, "-"),
+            (true, false) => write!(f, "{}", minutes),
+            (false, true) => write!(f, "{}", points),
+            (true, true) => write!(f, "{} + {}", minutes, points),
+        }
+    }
+}
+
+// 未完成任务的剩余预估
+pub fn remaining_totals<'a>(tasks: impl IntoIterator<Item = &'a Task>) -> EstimateTotals {
+    let mut totals = EstimateTotals::default();
+    for task in tasks.into_iter().filter(|t| !t.is_completed()) {
+        if let Some(estimate) = task.estimate() {
+            totals.add(estimate);
+        }
+    }
+    totals
+}
+
+// 按标签汇总剩余预估，按标签名排序
+pub fn remaining_by_tag(tasks: &[Task]) -> Vec<(String, EstimateTotals)> {
+    let mut tags: Vec<&String> = tasks.iter().flat_map(|t| t.tags()).collect();
+    tags.sort();
+    tags.dedup();
+    tags.into_iter()
+        .map(|tag| (tag.clone(), remaining_totals(tasks.iter().filter(|t| t.has_tag(tag)))))
+        .filter(|(_, totals)| totals.tasks > 0)
+        .collect()
+}
+
+// 有预估且有计时记录的任务
+pub fn compare(tasks: &[Task], now: DateTime<Utc>) -> Vec<EstimateComparison<'_>> {
+    tasks.iter()
+        .filter_map(|task| {
+            let estimate = task.estimate()?;
+            let actual_seconds = task.tracked_seconds(now);
+            (actual_seconds > 0).then_some(EstimateComparison { task, estimate, actual_seconds })
+        })
+        .collect()
+}
+
+impl fmt::Display for Estimate {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            // 与解析格式一致: 2h、30m、1h30m
+            Estimate::Minutes(minutes) => match (minutes / 60, minutes % 60) {
+                (0, m) => write!(f, "{}m", m),
+                (h, 0) => write!(f, "{}h", h),
+                (h, m) => write!(f, "{}h{}m", h, m),
+            },
+            Estimate::Points(points) => write!(f, "{}pt", points),
+        }
+    }
+}
+
+// 支持 30m、2h、1h30m、1.5h、3pt / 3sp
+impl FromStr for Estimate {
+    type Err = TodoError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || TodoError::InvalidInput(format!("无法识别的预估: {}，示例: 30m、2h、1h30m、3pt", s));
+        let value = s.trim().to_lowercase();
+
+        for suffix in ["pt", "sp", "p"] {
+            if let Some(points) = value.strip_suffix(suffix) {
+                let points: f64 = points.trim().parse().map_err(|_| invalid())?;
+                // inf、NaN 无法写入 JSON
+                return if points.is_finite() && points > 0.0 { Ok(Estimate::Points(points)) } else { Err(invalid()) };
+            }
+        }
+
+        let mut minutes = 0.0;
+        let mut number = String::new();
+        for c in value.chars() {
+            match c {
+                '0'..='9' | '.' => number.push(c),
+                'h' | 'm' => {
+                    let n: f64 = number.parse().map_err(|_| invalid())?;
+                    if !n.is_finite() {
+                        return Err(invalid());
+                    }
+                    minutes += if c == 'h' { n * 60.0 } else { n };
+                    number.clear();
+                }
+                c if c.is_whitespace() => {}
+                _ => return Err(invalid()),
+            }
+        }
+        // 没有单位时按分钟计
+        if !number.is_empty() {
+            minutes += number.parse::<f64>().map_err(|_| invalid())?;
+        }
+        let minutes = minutes.round();
+        if !minutes.is_finite() || minutes < 1.0 || minutes > f64::from(u32::MAX) {
+            return Err(invalid());
+        }
+        Ok(Estimate::Minutes(minutes as u32))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_time_and_points() {
+        assert_eq!("30m".parse::<Estimate>().unwrap(), Estimate::Minutes(30));
+        assert_eq!("2h".parse::<Estimate>().unwrap(), Estimate::Minutes(120));
+        assert_eq!("1h30m".parse::<Estimate>().unwrap(), Estimate::Minutes(90));
+        assert_eq!("1.5h".parse::<Estimate>().unwrap(), Estimate::Minutes(90));
+        assert_eq!("45".parse::<Estimate>().unwrap(), Estimate::Minutes(45));
+        assert_eq!("3pt".parse::<Estimate>().unwrap(), Estimate::Points(3.0));
+        assert_eq!("0.5SP".parse::<Estimate>().unwrap(), Estimate::Points(0.5));
+    }
+
+    #[test]
+    fn display_round_trips() {
+        for text in ["30m", "2h", "1h30m", "3pt", "0.5pt"] {
+            assert_eq!(text.parse::<Estimate>().unwrap().to_string(), text);
+        }
+    }
+
+    #[test]
+    fn rejects_invalid_values() {
+        for text in ["", "abc", "0m", "0pt", "-3pt", "infpt", "NaNpt", "1e400pt", "30x", "h"] {
+            assert!(text.parse::<Estimate>().is_err(), "{} 应被拒绝", text);
+        }
+    }
+
+    #[test]
+    fn rejects_minutes_beyond_u32() {
+        assert!("99999999999h".parse::<Estimate>().is_err());
+        assert!(format!("{}m", u64::from(u32::MAX) + 1).parse::<Estimate>().is_err());
+        assert_eq!(format!("{}m", u32::MAX).parse::<Estimate>().unwrap(), Estimate::Minutes(u32::MAX));
+    }
+
+    #[test]
+    fn totals_add_separately_and_saturate() {
+        let mut totals = EstimateTotals::default();
+        totals.add(Estimate::Minutes(30));
+        totals.add(Estimate::Points(2.0));
+        totals.add(Estimate::Minutes(90));
+        assert_eq!(totals, EstimateTotals { minutes: 120, points: 2.0, tasks: 3 });
+        assert_eq!(totals.to_string(), "2h + 2pt");
+
+        totals.add(Estimate::Minutes(u32::MAX));
+        totals.add(Estimate::Minutes(u32::MAX));
+        assert_eq!(totals.minutes, u32::MAX);
+        assert_eq!(totals.tasks, 5);
+    }
+
+    #[test]
+    fn empty_totals_display_dash() {
+        assert_eq!(EstimateTotals::default().to_string(), "-");
+    }
+}
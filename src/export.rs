@@ -0,0 +1,227 @@
+use std::fmt::Write;
+use std::str::FromStr;
+use crate::error::{Result, TodoError};
+use crate::ical;
+use crate::task::{Priority, Task, TaskStatus};
+use crate::time_tracking;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ExportFormat {
+    Csv,
+    Markdown,
+    TodoTxt,
+    ICalendar,
+}
+
+// CSV 列，顺序固定，导入时按列名识别
+pub const CSV_COLUMNS: [&str; 15] = [
+    "id",
+    "uuid",
+    "description",
+    "status",
+    "created_at",
+    "completed_at",
+    "tags",
+    "position",
+    "estimate",
+    "pomodoros",
+    // 每段计时为 开始~结束，多段以 ; 分隔，进行中的计时结束时间为空
+    "sessions",
+    "due",
+    "priority",
+    "repeat",
+    "notes",
+];
+
+impl ExportFormat {
+    pub const ALL: [ExportFormat; 4] = [ExportFormat::Csv, ExportFormat::Markdown, ExportFormat::TodoTxt, ExportFormat::ICalendar];
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "csv",
+            ExportFormat::Markdown => "md",
+            ExportFormat::TodoTxt => "txt",
+            ExportFormat::ICalendar => "ics",
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "CSV",
+            ExportFormat::Markdown => "Markdown",
+            ExportFormat::TodoTxt => "todo.txt",
+            ExportFormat::ICalendar => "iCalendar",
+        }
+    }
+
+    // 根据文件扩展名推断格式
+    pub fn from_path(path: &std::path::Path) -> Option<Self> {
+        let extension = path.extension()?.to_str()?.to_lowercase();
+        Self::ALL.into_iter().find(|format| format.extension() == extension)
+    }
+}
+
+pub fn export<'a>(tasks: impl IntoIterator<Item = &'a Task>, format: ExportFormat) -> Result<String> {
+    let tasks = tasks.into_iter();
+    match format {
+        ExportFormat::Csv => to_csv(tasks),
+        ExportFormat::Markdown => Ok(tasks.map(to_markdown_line).collect()),
+        ExportFormat::TodoTxt => Ok(tasks.map(to_todo_txt_line).collect()),
+        ExportFormat::ICalendar => Ok(ical::to_calendar(tasks)),
+    }
+}
+
+fn to_csv<'a>(tasks: impl Iterator<Item = &'a Task>) -> Result<String> {
+    let mut writer = csv::Writer::from_writer(Vec::new());
+    writer.write_record(CSV_COLUMNS)?;
+    for task in tasks {
+        let sessions = task.sessions().iter()
+            .map(|s| format!("{}~{}", s.start, s.end.as_deref().unwrap_or("")))
+            .collect::<Vec<_>>()
+            .join(";");
+        writer.write_record([
+            task.id().to_string(),
+            task.uuid().to_string(),
+            task.description().to_string(),
+            status_name(task.status()).to_string(),
+            task.created_at().to_string(),
+            task.completed_at().unwrap_or("").to_string(),
+            task.tags().join(","),
+            task.position().to_string(),
+            task.estimate().map(|e| e.to_string()).unwrap_or_default(),
+            task.pomodoros().to_string(),
+            sessions,
+            task.due().map(|d| d.to_string()).unwrap_or_default(),
+            task.priority().map(|p| p.name()).unwrap_or_default().to_string(),
+            task.recurrence().map(|r| r.to_string()).unwrap_or_default(),
+            task.notes().to_string(),
+        ])?;
+    }
+    let bytes = writer.into_inner().map_err(|e| e.into_error())?;
+    String::from_utf8(bytes).map_err(|e| TodoError::InvalidInput(format!("CSV 编码错误: {}", e)))
+}
+
+// - [x] 描述 #标签 <!-- id:1 uuid:... status:completed created:... -->
+// 注释中保留其余字段，便于再次导入；备注写在下面缩进两格的行中
+fn to_markdown_line(task: &Task) -> String {
+    let mut line = format!("- [{}] {}", if task.is_completed() { "x" } else { " " }, task.description());
+    for tag in task.tags() {
+        write!(line, " #{}", tag.replace(' ', "_")).ok();
+    }
+    write!(line, " <!-- id:{} uuid:{} status:{} created:{}", task.id(), task.uuid(), status_name(task.status()), task.created_at().replace(' ', "T")).ok();
+    if let Some(completed) = task.completed_at() {
+        write!(line, " completed:{}", completed.replace(' ', "T")).ok();
+    }
+    if let Some(estimate) = task.estimate() {
+        write!(line, " estimate:{}", estimate).ok();
+    }
+    if let Some(due) = task.due() {
+        write!(line, " due:{}", due).ok();
+    }
+    if let Some(priority) = task.priority() {
+        write!(line, " priority:{}", priority.name()).ok();
+    }
+    if let Some(recurrence) = task.recurrence() {
+        write!(line, " repeat:{}", recurrence).ok();
+    }
+    if task.pomodoros() > 0 {
+        write!(line, " pomodoros:{}", task.pomodoros()).ok();
+    }
+    line.push_str(" -->\n");
+    for note in task.notes().lines() {
+        writeln!(line, "  {}", note).ok();
+    }
+    line
+}
+
+// x 完成日期 (A) 创建日期 描述 +标签 id:1 uuid:... est:30m due:2025-07-02 rec:1w pomo:2 status:suspended note:...
+// 备注中的空白和 % 按 URL 编码，保持一行一个任务
+// 已完成的任务按惯例不带 (A) 前缀，优先级写为 pri:A
+fn to_todo_txt_line(task: &Task) -> String {
+    let created = time_tracking::local_date(task.created_at());
+    let mut line = String::new();
+    if task.is_completed() {
+        line.push_str("x ");
+        if let Some(completed) = task.completed_at().and_then(time_tracking::local_date) {
+            write!(line, "{} ", completed).ok();
+        }
+    }
+    let priority = task.priority().map(priority_letter);
+    if let Some(letter) = priority.filter(|_| !task.is_completed()) {
+        write!(line, "({}) ", letter).ok();
+    }
+    if let Some(created) = created {
+        write!(line, "{} ", created).ok();
+    }
+    line.push_str(task.description());
+    for tag in task.tags() {
+        write!(line, " +{}", tag.replace(' ', "_")).ok();
+    }
+    write!(line, " id:{} uuid:{}", task.id(), task.uuid()).ok();
+    if let Some(estimate) = task.estimate() {
+        write!(line, " est:{}", estimate).ok();
+    }
+    if let Some(due) = task.due() {
+        write!(line, " due:{}", due).ok();
+    }
+    if let Some(letter) = priority.filter(|_| task.is_completed()) {
+        write!(line, " pri:{}", letter).ok();
+    }
+    if let Some(recurrence) = task.recurrence() {
+        write!(line, " rec:{}", recurrence).ok();
+    }
+    if task.pomodoros() > 0 {
+        write!(line, " pomo:{}", task.pomodoros()).ok();
+    }
+    if *task.status() == TaskStatus::Suspended {
+        line.push_str(" status:suspended");
+    }
+    if !task.notes().is_empty() {
+        write!(line, " note:{}", encode_note(task.notes())).ok();
+    }
+    line.push('\n');
+    line
+}
+
+fn encode_note(note: &str) -> String {
+    let mut encoded = String::with_capacity(note.len());
+    for c in note.chars() {
+        match c {
+            '%' | ' ' | '\t' | '\r' | '\n' => {
+                write!(encoded, "%{:02X}", c as u8).ok();
+            }
+            c => encoded.push(c),
+        }
+    }
+    encoded
+}
+
+fn priority_letter(priority: Priority) -> char {
+    match priority {
+        Priority::High => 'A',
+        Priority::Medium => 'B',
+        Priority::Low => 'C',
+    }
+}
+
+pub fn status_name(status: &TaskStatus) -> &'static str {
+    match status {
+        TaskStatus::Pending => "pending",
+        TaskStatus::Suspended => "suspended",
+        TaskStatus::Completed => "completed",
+    }
+}
+
+impl FromStr for ExportFormat {
+    type Err = TodoError;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "csv" => Ok(ExportFormat::Csv),
+            "md" | "markdown" => Ok(ExportFormat::Markdown),
+            "todo.txt" | "todotxt" | "txt" => Ok(ExportFormat::TodoTxt),
+            "ics" | "ical" | "icalendar" => Ok(ExportFormat::ICalendar),
+            other => Err(TodoError::InvalidInput(format!("未知的导出格式: {}", other))),
+        }
+    }
+}
@@ -0,0 +1,278 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::process::Command;
+use std::sync::Mutex;
+use crate::error::{Result, TodoError};
+use crate::task::{Task, TaskStatus};
+use crate::task_list::TaskList;
+
+// 数据目录是 git 仓库并开启了 todolist.autocommit 时，每次保存都提交一次 tasks.json
+const AUTOCOMMIT_KEY: &str = "todolist.autocommit";
+const DEFAULT_REMOTE: &str = "origin";
+
+// is_enabled 的结果，每次保存都会检查，只在进程内查询一次；git-init 后更新
+static ENABLED: Mutex<Option<bool>> = Mutex::new(None);
+
+// 一条提交记录
+#[derive(Debug, Clone)]
+pub struct LogEntry {
+    pub hash: String,
+    pub date: String,
+    pub message: String,
+}
+
+// 执行 git 命令，返回标准输出
+fn git(args: &[&str]) -> Result<String> {
+    let output = Command::new("git")
+        .args(args)
+        .output()
+        .map_err(|e| TodoError::GitError(format!("无法执行 git: {}", e)))?;
+    if output.status.success() {
+        Ok(String::from_utf8_lossy(&output.stdout).trim_end().to_string())
+    } else {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        Err(TodoError::GitError(format!("git {}: {}", args.join(" "), stderr.trim())))
+    }
+}
+
+pub fn is_enabled() -> bool {
+    let mut enabled = ENABLED.lock().unwrap_or_else(|e| e.into_inner());
+    *enabled.get_or_insert_with(|| {
+        rooted_here().unwrap_or(false)
+            && git(&["config", "--bool", "--get", AUTOCOMMIT_KEY]).is_ok_and(|value| value == "true")
+    })
+}
+
+// 仓库的根目录就是数据目录；数据目录位于其他仓库中时不使用外层仓库
+fn rooted_here() -> Result<bool> {
+    let Ok(top) = git(&["rev-parse", "--show-toplevel"]) else {
+        return Ok(false);
+    };
+    let canonical = |path: PathBuf| path.canonicalize().unwrap_or(path);
+    Ok(canonical(PathBuf::from(top)) == canonical(std::env::current_dir()?))
+}
+
+fn ensure_enabled() -> Result<()> {
+    if is_enabled() {
+        Ok(())
+    } else {
+        Err(TodoError::GitError("尚未开启 git 存储，请先运行 git-init".to_string()))
+    }
+}
+
+// 在数据目录中初始化仓库并开启自动提交，可同时设置远程仓库
+pub fn init(file: &str, remote: Option<&str>) -> Result<()> {
+    match git(&["rev-parse", "--show-toplevel"]) {
+        Err(_) => git(&["init", "--quiet"]).map(drop)?,
+        Ok(top) if !rooted_here()? => {
+            return Err(TodoError::GitError(format!("数据目录位于仓库 {} 中，请在仓库根目录运行或改用单独的数据目录", top)));
+        }
+        Ok(_) => {}
+    }
+    git(&["config", AUTOCOMMIT_KEY, "true"])?;
+    *ENABLED.lock().unwrap_or_else(|e| e.into_inner()) = Some(true);
+    if let Some(url) = remote {
+        match git(&["remote", "get-url", DEFAULT_REMOTE]) {
+            Ok(_) => git(&["remote", "set-url", DEFAULT_REMOTE, url])?,
+            Err(_) => git(&["remote", "add", DEFAULT_REMOTE, url])?,
+        };
+    }
+    commit(file, "init task list")
+}
+
+// 只提交任务文件，没有变化时不提交
+pub fn commit(file: &str, message: &str) -> Result<()> {
+    if std::path::Path::new(file).exists() {
+        git(&["add", "--", file])?;
+    }
+    if git(&["diff", "--cached", "--quiet", "--", file]).is_ok() {
+        return Ok(());
+    }
+    let mut args = identity_args();
+    args.extend(["commit", "--quiet", "-m", message, "--", file]);
+    git(&args)?;
+    Ok(())
+}
+
+// 没有配置提交者时使用默认身份，避免保存失败
+fn identity_args() -> Vec<&'static str> {
+    if git(&["config", "user.email"]).is_ok() {
+        Vec::new()
+    } else {
+        vec!["-c", "user.name=todolist-cli", "-c", "user.email=todolist-cli@localhost"]
+    }
+}
+
+pub fn log(file: &str, limit: usize) -> Result<Vec<LogEntry>> {
+    ensure_enabled()?;
+    let limit = format!("-{}", limit);
+    let output = git(&["log", &limit, "--format=%h%x1f%ad%x1f%B%x1e", "--date=format-local:%Y-%m-%d %H:%M", "--", file])?;
+    Ok(output.split('\x1e')
+        .filter_map(|record| {
+            let mut fields = record.trim_matches('\n').splitn(3, '\x1f');
+            Some(LogEntry {
+                hash: fields.next()?.to_string(),
+                date: fields.next()?.to_string(),
+                message: fields.next()?.trim().to_string(),
+            })
+        })
+        .collect())
+}
+
+// 拉取、合并并推送；合并冲突时放弃本次合并，保留本地提交
+pub fn sync(file: &str, remote: Option<&str>) -> Result<String> {
+    ensure_enabled()?;
+    let remote = remote.unwrap_or(DEFAULT_REMOTE);
+    commit(file, "save before sync")?;
+    let branch = git(&["rev-parse", "--abbrev-ref", "HEAD"])?;
+
+    let mut summary = Vec::new();
+    // 远程仓库还没有这个分支时直接推送
+    if !git(&["ls-remote", "--heads", remote, &branch])?.is_empty() {
+        git(&["fetch", "--quiet", remote, &branch])?;
+        let before = git(&["rev-parse", "HEAD"])?;
+        let mut args = identity_args();
+        args.extend(["merge", "--no-edit", "--quiet", "FETCH_HEAD"]);
+        if let Err(err) = git(&args) {
+            let conflicted = git(&["diff", "--name-only", "--diff-filter=U"]).unwrap_or_default();
+            if conflicted.is_empty() {
+                return Err(err);
+            }
+            // 只有任务文件冲突时按任务合并，其他文件冲突交给用户处理
+            if conflicted != file {
+                git(&["merge", "--abort"]).ok();
+                return Err(TodoError::GitError(format!("合并 {}/{} 时发生冲突，已放弃合并，请手动解决: {}", remote, branch, conflicted)));
+            }
+            if let Err(err) = resolve_conflict(file, &format!("merge tasks from {}/{}", remote, branch)) {
+                git(&["merge", "--abort"]).ok();
+                return Err(err);
+            }
+            summary.push("已按任务合并冲突".to_string());
+        }
+        if git(&["rev-parse", "HEAD"])? != before {
+            summary.push(format!("已合并 {}/{}", remote, branch));
+        }
+    }
+    git(&["push", "--quiet", remote, &format!("HEAD:refs/heads/{}", branch)])?;
+    summary.push(format!("已推送到 {}/{}", remote, branch));
+    Ok(summary.join("，"))
+}
+
+// 用双方版本的任务列表合并结果解决 tasks.json 的冲突
+fn resolve_conflict(file: &str, message: &str) -> Result<()> {
+    let ours: TaskList = serde_json::from_str(&git(&["show", &format!(":2:{}", file)])?)?;
+    let mut theirs: TaskList = serde_json::from_str(&git(&["show", &format!(":3:{}", file)])?)?;
+    // 旧版本文件没有 UUID，双方都要补上才能对应同一任务
+    theirs.ensure_uuids();
+    let mut merged = ours;
+    merged.ensure_uuids();
+    merged.merge(&theirs);
+    std::fs::write(file, serde_json::to_string_pretty(&merged)?)?;
+    git(&["add", "--", file])?;
+    let mut args = identity_args();
+    args.extend(["commit", "--quiet", "-m", message]);
+    git(&args)?;
+    Ok(())
+}
+
+// 根据保存前后的任务列表生成提交说明，例如 "complete #12: 写报告"
+pub fn describe_changes(old: &TaskList, new: &TaskList) -> Option<String> {
+    let before: HashMap<usize, &Task> = old.get_tasks().iter().map(|t| (t.id(), t)).collect();
+    let mut changes = Vec::new();
+    let mut moves = Vec::new();
+    for task in new.get_tasks() {
+        let action = match before.get(&task.id()) {
+            None => Some("add"),
+            Some(previous) => describe_task_change(previous, task),
+        };
+        match action {
+            Some("move") => moves.push(format!("move #{}: {}", task.id(), task.description())),
+            Some(action) => changes.push(format!("{} #{}: {}", action, task.id(), task.description())),
+            None => {}
+        }
+    }
+    for task in old.get_tasks().iter().filter(|t| new.get_task(t.id()).is_none()) {
+        changes.push(format!("delete #{}: {}", task.id(), task.description()));
+    }
+
+    // 删除和移动会让其他任务的位置跟着变化，有其他修改时不单独列出
+    if changes.is_empty() {
+        return match moves.len() {
+            // 例如只有删除记录变化
+            0 if serialized(old) != serialized(new) => Some("update task list".to_string()),
+            0 => None,
+            1 => moves.pop(),
+            _ => Some("reorder tasks".to_string()),
+        };
+    }
+    match changes.len() {
+        1 => changes.pop(),
+        n => Some(format!("update {} tasks\n\n{}", n, changes.join("\n"))),
+    }
+}
+
+fn describe_task_change(old: &Task, new: &Task) -> Option<&'static str> {
+    if old.status() != new.status() {
+        return Some(match new.status() {
+            TaskStatus::Completed => "complete",
+            TaskStatus::Suspended => "suspend",
+            TaskStatus::Pending => "reopen",
+        });
+    }
+    if old.description() != new.description() {
+        Some("edit")
+    } else if old.tags() != new.tags() {
+        Some("tag")
+    } else if old.is_timer_running() != new.is_timer_running() {
+        Some(if new.is_timer_running() { "start" } else { "stop" })
+    } else if old.estimate() != new.estimate() {
+        Some("estimate")
+    } else if old.pomodoros() != new.pomodoros() {
+        Some("pomodoro")
+    } else if old.due() != new.due() {
+        Some("due")
+    } else if old.priority() != new.priority() {
+        Some("priority")
+    } else if old.recurrence() != new.recurrence() {
+        Some("repeat")
+    } else if old.notes() != new.notes() {
+        Some("note")
+    } else if old.position() != new.position() {
+        Some("move")
+    } else if serialized(old) != serialized(new) {
+        // 以上没有列出的字段，保证每次修改都有提交
+        Some("edit")
+    } else {
+        None
+    }
+}
+
+fn serialized<T: serde::Serialize>(value: &T) -> String {
+    serde_json::to_string(value).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::task::{Priority, TaskUpdate};
+    use chrono::NaiveDate;
+
+    fn edited(update: TaskUpdate) -> (TaskList, TaskList) {
+        let mut old = TaskList::new();
+        old.add_task("写报告".to_string());
+        let mut new = old.clone();
+        new.update_task(1, update).unwrap();
+        (old, new)
+    }
+
+    #[test]
+    fn describes_due_priority_and_note_changes() {
+        let (old, new) = edited(TaskUpdate { due: Some(NaiveDate::from_ymd_opt(2030, 1, 1)), ..Default::default() });
+        assert_eq!(describe_changes(&old, &new).as_deref(), Some("due #1: 写报告"));
+        let (old, new) = edited(TaskUpdate { priority: Some(Some(Priority::High)), ..Default::default() });
+        assert_eq!(describe_changes(&old, &new).as_deref(), Some("priority #1: 写报告"));
+        let (old, new) = edited(TaskUpdate { notes: Some("补充数据".to_string()), ..Default::default() });
+        assert_eq!(describe_changes(&old, &new).as_deref(), Some("note #1: 写报告"));
+        assert_eq!(describe_changes(&old, &old), None);
+    }
+}
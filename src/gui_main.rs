@@ -1,12 +1,22 @@
-mod task;
-mod task_list;
-mod storage;
-mod error;
-
+use std::collections::BTreeSet;
 use eframe::egui;
-use storage::Storage;
-use task::Task;
-use task_list::TaskList;
+use todolist_cli::error::Result;
+use todolist_cli::storage::Storage;
+use todolist_cli::task::{self, Priority, Task, TaskStatus, TaskUpdate};
+use todolist_cli::task_list::{Placement, TaskList};
+use todolist_cli::view::{GroupBy, SortKey, ViewOptions};
+use todolist_cli::time_tracking;
+use todolist_cli::estimate::{self, Estimate};
+use todolist_cli::recurrence::Recurrence;
+use todolist_cli::stats::{self, Statistics};
+use todolist_cli::report::{self, ReportFormat, ReportRange};
+use todolist_cli::export::{self, ExportFormat};
+use egui_plot::{Bar, BarChart, Legend, Line, Plot, PlotPoints};
+use todolist_cli::pomodoro::{Pomodoro, PomodoroEvent, PomodoroSettings};
+
+// eframe 持久化存储中视图设置的键
+const VIEW_KEY: &str = "view_options";
+const POMODORO_KEY: &str = "pomodoro_settings";
 
 fn main() -> eframe::Result<()> {
     let options = eframe::NativeOptions {
@@ -21,7 +31,7 @@ fn main() -> eframe::Result<()> {
         Box::new(|cc| {
             // 配置中文字体支持
             setup_custom_fonts(&cc.egui_ctx);
-            Box::new(TodoApp::new())
+            Box::new(TodoApp::new(cc.storage))
         }),
     )
 }
@@ -135,16 +145,59 @@ fn setup_custom_fonts(ctx: &egui::Context) {
     ctx.set_fonts(fonts);
 }
 
+// 主界面的标签页
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Tab {
+    Tasks,
+    Stats,
+    Analytics,
+    Report,
+}
+
 struct TodoApp {
     task_list: TaskList,
     new_task_description: String,
     selected_task_id: Option<usize>,
     status_message: String,
     show_completed: bool,
+    tab: Tab,
+    // 分析图表覆盖的天数
+    analytics_days: i64,
+    // 报告的日期范围 (YYYY-MM-DD) 和格式
+    report_from: String,
+    report_to: String,
+    report_format: ReportFormat,
+    // 排序与分组，退出时由 eframe 保存
+    view: ViewOptions,
+    // 正在行内编辑的任务及其编辑缓冲
+    editing_task_id: Option<usize>,
+    edit_buffer: String,
+    // 详情面板中尚未保存的字段
+    detail_description: String,
+    detail_status: TaskStatus,
+    detail_tags: String,
+    detail_estimate: String,
+    detail_due: String,
+    detail_priority: Option<Priority>,
+    detail_repeat: String,
+    detail_notes: String,
+    // Ctrl/Shift 多选的任务，selected_task_id 为最近点击的一项
+    selected_ids: BTreeSet<usize>,
+    bulk_tag: String,
+    // 最近一次批量操作前的快照，用于撤销
+    undo_snapshot: Option<TaskList>,
+    // 番茄钟及其窗口是否打开
+    pomodoro: Pomodoro,
+    show_pomodoro: bool,
+    // 正在拖动排序的任务
+    dragging_task_id: Option<usize>,
+    // 键盘切换选中任务后滚动到该任务
+    scroll_to_selected: bool,
 }
 
 impl TodoApp {
-    fn new() -> Self {
+    fn new(storage: Option<&dyn eframe::Storage>) -> Self {
+        let mut status_message = "就绪".to_string();
         let task_list = match Storage::load_tasks() {
             Ok(tasks) => {
                 if !Storage::storage_exists() {
@@ -153,10 +206,17 @@ impl TodoApp {
                 }
                 tasks
             }
-            Err(_) => {
-                let new_list = TaskList::new();
-                let _ = Storage::save_tasks(&new_list);
-                new_list
+            // 无法读取时先备份原文件再使用空列表，之后的保存不会丢失原有数据
+            Err(e) => {
+                match Storage::back_up_unreadable() {
+                    Ok(backup) => status_message = format!("❌ 读取 tasks.json 失败: {}，原文件已备份为 {}", e, backup),
+                    // 无法备份时不能继续，否则第一次保存就会覆盖原文件
+                    Err(backup_error) => {
+                        eprintln!("❌ 读取 tasks.json 失败: {}，备份也失败了: {}", e, backup_error);
+                        std::process::exit(1);
+                    }
+                }
+                TaskList::new()
             }
         };
 
@@ -164,12 +224,43 @@ impl TodoApp {
             task_list,
             new_task_description: String::new(),
             selected_task_id: None,
-            status_message: "就绪".to_string(),
+            status_message,
             show_completed: true,
+            tab: Tab::Tasks,
+            analytics_days: 30,
+            report_from: ReportRange::this_week().from.to_string(),
+            report_to: ReportRange::this_week().to.to_string(),
+            report_format: ReportFormat::Markdown,
+            view: storage.and_then(|s| eframe::get_value(s, VIEW_KEY)).unwrap_or_default(),
+            editing_task_id: None,
+            edit_buffer: String::new(),
+            detail_description: String::new(),
+            detail_status: TaskStatus::Pending,
+            detail_tags: String::new(),
+            detail_estimate: String::new(),
+            detail_due: String::new(),
+            detail_priority: None,
+            detail_repeat: String::new(),
+            detail_notes: String::new(),
+            selected_ids: BTreeSet::new(),
+            bulk_tag: String::new(),
+            undo_snapshot: None,
+            pomodoro: Pomodoro::new(
+                storage.and_then(|s| eframe::get_value::<PomodoroSettings>(s, POMODORO_KEY)).unwrap_or_default(),
+            ),
+            show_pomodoro: false,
+            dragging_task_id: None,
+            scroll_to_selected: false,
         }
     }
 
+    // 修改任务后保存；批量操作的撤销快照只对紧接着的撤销有效，其他修改之后作废
     fn save_tasks(&mut self) {
+        self.undo_snapshot = None;
+        self.write_tasks();
+    }
+
+    fn write_tasks(&mut self) {
         match Storage::save_tasks(&self.task_list) {
             Ok(_) => self.status_message = "✅ 已保存".to_string(),
             Err(e) => self.status_message = format!("❌ 保存失败: {:?}", e),
@@ -190,6 +281,7 @@ impl TodoApp {
     fn complete_task(&mut self, id: usize) {
         if self.task_list.complete_task(id) {
             self.status_message = format!("✅ 任务 #{} 已完成", id);
+            self.refresh_detail();
             self.save_tasks();
         } else {
             self.status_message = format!("❌ 未找到任务 #{}", id);
@@ -199,6 +291,7 @@ impl TodoApp {
     fn suspend_task(&mut self, id: usize) {
         if self.task_list.suspend_task(id) {
             self.status_message = format!("⏸️ 任务 #{} 已挂起", id);
+            self.refresh_detail();
             self.save_tasks();
         } else {
             self.status_message = format!("❌ 未找到任务 #{}", id);
@@ -208,140 +301,1048 @@ impl TodoApp {
     fn delete_task(&mut self, id: usize) {
         if self.task_list.delete_task(id) {
             self.status_message = format!("🗑️ 任务 #{} 已删除", id);
+            if self.selected_task_id == Some(id) {
+                self.selected_task_id = None;
+            }
+            self.selected_ids.remove(&id);
             self.save_tasks();
         } else {
             self.status_message = format!("❌ 未找到任务 #{}", id);
         }
     }
 
-    fn get_status_color(&self, task: &Task) -> egui::Color32 {
-        match task.is_completed() {
-            true => egui::Color32::from_rgb(100, 200, 100), // 绿色
-            false => egui::Color32::from_rgb(200, 200, 100), // 黄色
+    fn move_task(&mut self, id: usize, placement: Placement) {
+        match self.task_list.move_task(id, placement) {
+            Ok(()) => {
+                self.status_message = format!("↕️ 任务 #{} 已移动", id);
+                self.save_tasks();
+            }
+            Err(e) => self.status_message = format!("❌ {}", e),
         }
     }
 
-    fn get_status_text(&self, task: &Task) -> &str {
-        if task.is_completed() {
-            "✅ 已完成"
-        } else {
-            "⏳ 进行中"
+    fn start_timer(&mut self, id: usize) {
+        match self.task_list.start_timer(id) {
+            Ok(paused) => {
+                self.save_tasks();
+                self.status_message = match paused {
+                    Some(paused) => format!("⏱ 任务 #{} 开始计时，#{} 已暂停", id, paused),
+                    None => format!("⏱ 任务 #{} 开始计时", id),
+                };
+            }
+            Err(e) => self.status_message = format!("❌ {}", e),
         }
     }
-}
 
-impl eframe::App for TodoApp {
-    fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
-        egui::CentralPanel::default().show(ctx, |ui| {
-            // 标题
-            ui.heading("📋 Todo List GUI");
-            ui.separator();
+    fn stop_timer(&mut self) {
+        if let Some(id) = self.task_list.stop_timer() {
+            self.save_tasks();
+            self.status_message = format!("⏹ 任务 #{} 已停止计时", id);
+        }
+    }
 
-            // 添加任务区域
-            ui.horizontal(|ui| {
-                ui.label("新任务:");
-                let response = ui.text_edit_singleline(&mut self.new_task_description);
-                
-                // 支持回车键添加任务
-                if response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter)) {
-                    self.add_task();
+    // 标题栏中的计时指示：正在计时的任务、已用时间和今日合计，计时中每秒刷新
+    fn show_timer_indicator(&mut self, ui: &mut egui::Ui) {
+        let now = chrono::Utc::now();
+        ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+            let today = self.task_list.tracked_seconds_on(time_tracking::today(), now);
+            ui.label(format!("今日: {}", time_tracking::format_duration(today)));
+
+            let running = self.task_list.active_timer()
+                .and_then(|task| Some((task.id(), task.description().to_string(), task.running_seconds(now)?)));
+            if let Some((id, description, seconds)) = running {
+                if ui.button("⏹").on_hover_text("停止计时").clicked() {
+                    self.stop_timer();
                 }
-                
-                if ui.button("➕ 添加").clicked() {
-                    self.add_task();
+                ui.colored_label(
+                    egui::Color32::from_rgb(220, 120, 80),
+                    format!("⏱ #{} {} {}", id, description, time_tracking::format_clock(seconds)),
+                );
+                ui.ctx().request_repaint_after(std::time::Duration::from_secs(1));
+            }
+        });
+    }
+
+    // 推进番茄钟，专注阶段结束时给绑定的任务记一个番茄
+    fn tick_pomodoro(&mut self, ctx: &egui::Context) {
+        let now = std::time::Instant::now();
+        match self.pomodoro.tick(now) {
+            Some(PomodoroEvent::WorkFinished(task_id)) => {
+                // 绑定的任务可能已被删除
+                let recorded = task_id.filter(|&id| self.task_list.record_pomodoro(id).is_ok());
+                if let Some(id) = recorded {
+                    self.save_tasks();
+                    self.status_message = format!("🍅 任务 #{} 完成一个番茄，休息一下吧", id);
+                } else {
+                    self.status_message = "🍅 完成一个番茄，休息一下吧".to_string();
                 }
-            });
+                self.show_pomodoro = true;
+            }
+            Some(PomodoroEvent::BreakFinished) => {
+                self.status_message = "🍅 休息结束，开始下一个番茄吧".to_string();
+                self.show_pomodoro = true;
+            }
+            None => {}
+        }
+        if self.pomodoro.is_running() {
+            // 倒计时按秒显示，刷新到下一整秒即可
+            let remaining = self.pomodoro.remaining(now);
+            let until_next_second = std::time::Duration::from_nanos(u64::from(remaining.subsec_nanos()));
+            ctx.request_repaint_after(until_next_second.max(std::time::Duration::from_millis(50)));
+        }
+    }
 
-            ui.separator();
+    fn show_pomodoro_window(&mut self, ctx: &egui::Context) {
+        let mut open = self.show_pomodoro;
+        let now = std::time::Instant::now();
+        egui::Window::new("🍅 番茄钟")
+            .open(&mut open)
+            .resizable(false)
+            .show(ctx, |ui| {
+                let task = self.pomodoro.task_id.and_then(|id| self.task_list.get_task(id));
+                ui.horizontal(|ui| {
+                    ui.label("任务:");
+                    match task {
+                        Some(task) => ui.label(format!("#{} {} (🍅 {})", task.id(), task.description(), task.pomodoros())),
+                        None => ui.label("(未绑定)"),
+                    };
+                });
+                if let Some(id) = self.selected_task_id.filter(|id| Some(*id) != self.pomodoro.task_id)
+                    && ui.button(format!("绑定选中任务 #{}", id)).clicked()
+                {
+                    self.pomodoro.task_id = Some(id);
+                }
 
-            // 过滤选项
-            ui.horizontal(|ui| {
-                ui.checkbox(&mut self.show_completed, "显示已完成任务");
-                
-                let tasks = self.task_list.get_tasks();
-                let total_count = tasks.len();
-                let pending_count = tasks.iter().filter(|t| !t.is_completed()).count();
-                let completed_count = total_count - pending_count;
-                
-                ui.label(format!("📊 总计: {} | 待处理: {} | 已完成: {}", 
-                    total_count, pending_count, completed_count));
-            });
+                ui.separator();
+                let remaining = self.pomodoro.remaining(now).as_secs();
+                ui.label(self.pomodoro.phase().label());
+                ui.heading(egui::RichText::new(format!("{:02}:{:02}", remaining / 60, remaining % 60)).size(40.0));
 
-            ui.separator();
+                ui.horizontal(|ui| {
+                    if self.pomodoro.is_running() {
+                        if ui.button("⏸ 暂停").clicked() {
+                            self.pomodoro.pause(now);
+                        }
+                    } else if ui.button("▶ 开始").clicked() {
+                        self.pomodoro.start(now);
+                    }
+                    if ui.button("↺ 重置").clicked() {
+                        self.pomodoro.reset();
+                    }
+                    if ui.button("⏭ 跳过").clicked() {
+                        self.pomodoro.skip();
+                    }
+                });
 
-            // 任务列表 - 修复借用问题
-            egui::ScrollArea::vertical().show(ui, |ui| {
-                // 先收集需要执行的操作，避免在循环中修改
-                let mut actions = Vec::new();
-                
-                let tasks = self.task_list.get_tasks().clone(); // 克隆任务列表
-                
-                if tasks.is_empty() {
-                    ui.centered_and_justified(|ui| {
-                        ui.label("🎉 暂无任务，添加一个开始吧！");
+                ui.separator();
+                ui.collapsing("设置", |ui| {
+                    let settings = &mut self.pomodoro.settings;
+                    let before = *settings;
+                    egui::Grid::new("pomodoro_settings").num_columns(2).show(ui, |ui| {
+                        ui.label("专注 (分钟):");
+                        ui.add(egui::DragValue::new(&mut settings.work_minutes).clamp_range(1..=180));
+                        ui.end_row();
+                        ui.label("短休息 (分钟):");
+                        ui.add(egui::DragValue::new(&mut settings.short_break_minutes).clamp_range(1..=60));
+                        ui.end_row();
+                        ui.label("长休息 (分钟):");
+                        ui.add(egui::DragValue::new(&mut settings.long_break_minutes).clamp_range(1..=120));
+                        ui.end_row();
+                        ui.label("长休息间隔 (个):");
+                        ui.add(egui::DragValue::new(&mut settings.long_break_every).clamp_range(1..=12));
+                        ui.end_row();
                     });
-                } else {
-                    for task in &tasks {
-                        // 根据过滤条件决定是否显示
-                        if !self.show_completed && task.is_completed() {
-                            continue;
-                        }
+                    // 未开始计时时，修改立即作用于当前阶段
+                    if *settings != before && !self.pomodoro.is_running() {
+                        self.pomodoro.reset();
+                    }
+                });
+            });
+        self.show_pomodoro = open;
+    }
+
+    // 选择文件并导出当前视图中可见的任务，格式由扩展名决定
+    fn export_tasks(&mut self) {
+        let mut dialog = rfd::FileDialog::new().set_file_name("tasks.csv");
+        for format in ExportFormat::ALL {
+            dialog = dialog.add_filter(format.label(), &[format.extension()]);
+        }
+        let Some(path) = dialog.save_file() else {
+            return;
+        };
+        let format = ExportFormat::from_path(&path).unwrap_or(ExportFormat::Csv);
+
+        let tasks = self.tasks_in_view();
+        let result = export::export(tasks.iter().copied(), format)
+            .and_then(|content| Ok(std::fs::write(&path, content)?));
+        self.status_message = match result {
+            Ok(()) => format!("📤 已导出 {} 个任务到 {}", tasks.len(), path.display()),
+            Err(e) => format!("❌ 导出失败: {}", e),
+        };
+    }
+
+    fn start_editing(&mut self, task: &Task) {
+        self.editing_task_id = Some(task.id());
+        self.edit_buffer = task.description().to_string();
+    }
+
+    fn finish_editing(&mut self) {
+        let Some(id) = self.editing_task_id.take() else {
+            return;
+        };
+        let update = TaskUpdate {
+            description: Some(std::mem::take(&mut self.edit_buffer)),
+            ..Default::default()
+        };
+        match self.task_list.update_task(id, update) {
+            Ok(()) => {
+                self.status_message = format!("✏️ 任务 #{} 已更新", id);
+                self.refresh_detail();
+                self.save_tasks();
+            }
+            Err(e) => self.status_message = format!("❌ {}", e),
+        }
+    }
 
-                        ui.group(|ui| {
+    fn cancel_editing(&mut self) {
+        self.editing_task_id = None;
+        self.edit_buffer.clear();
+    }
+
+    fn select_task(&mut self, id: Option<usize>) {
+        self.selected_task_id = id;
+        self.selected_ids = id.into_iter().collect();
+        self.refresh_detail();
+    }
+
+    // Ctrl+点击：切换单个任务的选中状态
+    fn toggle_selection(&mut self, id: usize) {
+        if !self.selected_ids.remove(&id) {
+            self.selected_ids.insert(id);
+        }
+        self.selected_task_id = Some(id);
+        self.refresh_detail();
+    }
+
+    // Shift+点击：选中从上次点击的任务到当前任务之间的所有可见任务
+    fn select_range(&mut self, id: usize) {
+        let ids = self.visible_task_ids();
+        let anchor = self.selected_task_id.and_then(|a| ids.iter().position(|&i| i == a));
+        match (anchor, ids.iter().position(|&i| i == id)) {
+            (Some(a), Some(b)) => {
+                let (from, to) = if a <= b { (a, b) } else { (b, a) };
+                self.selected_ids.extend(&ids[from..=to]);
+            }
+            _ => self.select_task(Some(id)),
+        }
+    }
+
+    // 对所有选中任务执行一次批量操作，作为一个可撤销的整体并只保存一次
+    fn apply_bulk(&mut self, label: &str, op: impl FnOnce(&mut TaskList, &[usize]) -> Result<()>) {
+        let ids: Vec<usize> = self.selected_ids.iter().copied().collect();
+        let snapshot = self.task_list.clone();
+        match op(&mut self.task_list, &ids) {
+            Ok(()) => {
+                self.undo_snapshot = Some(snapshot);
+                self.selected_ids.retain(|id| self.task_list.get_task(*id).is_some());
+                if self.selected_task_id.is_some_and(|id| !self.selected_ids.contains(&id)) {
+                    self.selected_task_id = None;
+                }
+                self.refresh_detail();
+                self.write_tasks();
+                self.status_message = format!("✅ 已对 {} 个任务{}", ids.len(), label);
+            }
+            Err(e) => self.status_message = format!("❌ {}", e),
+        }
+    }
+
+    fn undo(&mut self) {
+        if let Some(snapshot) = self.undo_snapshot.take() {
+            self.task_list = snapshot;
+            self.refresh_detail();
+            self.write_tasks();
+            self.status_message = "↩️ 已撤销上一次批量操作".to_string();
+        }
+    }
+
+    fn show_bulk_toolbar(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            if self.selected_ids.len() > 1 {
+                ui.label(format!("已选 {} 项:", self.selected_ids.len()));
+                if ui.button("✅ 完成").clicked() {
+                    self.apply_bulk("标记完成", |list, ids| list.complete_tasks(ids));
+                }
+                if ui.button("⏸️ 挂起").clicked() {
+                    self.apply_bulk("挂起", |list, ids| list.suspend_tasks(ids));
+                }
+                if ui.button("🗑️ 删除").clicked() {
+                    self.apply_bulk("删除", |list, ids| list.delete_tasks(ids));
+                }
+                ui.add(egui::TextEdit::singleline(&mut self.bulk_tag).hint_text("标签").desired_width(80.0));
+                if ui.button("🏷 打标签").clicked() {
+                    let tag = self.bulk_tag.trim().to_string();
+                    self.apply_bulk(&format!("添加标签 {}", tag), |list, ids| list.tag_tasks(ids, &tag));
+                }
+                if ui.button("✖ 取消选择").clicked() {
+                    self.select_task(None);
+                }
+            } else {
+                ui.label("💡 Ctrl+点击 或 Shift+点击 可多选任务进行批量操作");
+            }
+
+            if self.undo_snapshot.is_some() {
+                ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                    if ui.button("↩️ 撤销").clicked() {
+                        self.undo();
+                    }
+                });
+            }
+        });
+    }
+
+    // 用选中任务的当前值重置详情面板
+    fn refresh_detail(&mut self) {
+        if let Some(task) = self.selected_task_id.and_then(|id| self.task_list.get_task(id)) {
+            self.detail_description = task.description().to_string();
+            self.detail_status = task.status().clone();
+            self.detail_tags = task.tags().join(", ");
+            self.detail_estimate = task.estimate().map(|e| e.to_string()).unwrap_or_default();
+            self.detail_due = task.due().map(|d| d.to_string()).unwrap_or_default();
+            self.detail_priority = task.priority();
+            self.detail_repeat = task.recurrence().map(|r| r.to_string()).unwrap_or_default();
+            self.detail_notes = task.notes().to_string();
+        }
+    }
+
+    fn save_detail(&mut self, id: usize) {
+        let tags = self.detail_tags.split([',', '，'])
+            .map(|tag| tag.trim().to_string())
+            .filter(|tag| !tag.is_empty())
+            .collect();
+        // 预估留空表示清除
+        let estimate = match self.detail_estimate.trim() {
+            "" => None,
+            value => match value.parse::<Estimate>() {
+                Ok(estimate) => Some(estimate),
+                Err(e) => {
+                    self.status_message = format!("❌ {}", e);
+                    return;
+                }
+            },
+        };
+        // 截止日期和重复周期同样留空表示清除
+        let due = match self.detail_due.trim() {
+            "" => None,
+            value => match task::parse_due(value) {
+                Ok(due) => Some(due),
+                Err(e) => {
+                    self.status_message = format!("❌ {}", e);
+                    return;
+                }
+            },
+        };
+        let recurrence = match self.detail_repeat.trim() {
+            "" => None,
+            value => match value.parse::<Recurrence>() {
+                Ok(recurrence) => Some(recurrence),
+                Err(e) => {
+                    self.status_message = format!("❌ {}", e);
+                    return;
+                }
+            },
+        };
+        let update = TaskUpdate {
+            description: Some(self.detail_description.clone()),
+            status: Some(self.detail_status.clone()),
+            tags: Some(tags),
+            estimate: Some(estimate),
+            due: Some(due),
+            priority: Some(self.detail_priority),
+            recurrence: Some(recurrence),
+            notes: Some(self.detail_notes.clone()),
+        };
+        match self.task_list.update_task(id, update) {
+            Ok(()) => {
+                self.status_message = format!("✏️ 任务 #{} 已更新", id);
+                self.refresh_detail();
+                self.save_tasks();
+            }
+            Err(e) => self.status_message = format!("❌ {}", e),
+        }
+    }
+
+    // 当前过滤条件下可见的任务ID，按显示顺序
+    fn visible_task_ids(&self) -> Vec<usize> {
+        self.tasks_in_view().iter().map(|t| t.id()).collect()
+    }
+
+    // 按当前视图排序的可见任务，分组中重复出现的任务只保留一次
+    fn tasks_in_view(&self) -> Vec<&Task> {
+        let mut tasks: Vec<&Task> = self.view.apply(self.visible_tasks(self.task_list.get_tasks()))
+            .into_iter()
+            .flat_map(|group| group.tasks)
+            .collect();
+        let mut seen = BTreeSet::new();
+        tasks.retain(|task| seen.insert(task.id()));
+        tasks
+    }
+
+    fn visible_tasks<'a>(&self, tasks: &'a [Task]) -> impl Iterator<Item = &'a Task> {
+        let show_completed = self.show_completed;
+        tasks.iter().filter(move |t| show_completed || !t.is_completed())
+    }
+
+    fn show_view_controls(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            ui.label("排序:");
+            egui::ComboBox::from_id_source("view_sort")
+                .selected_text(self.view.sort.label())
+                .show_ui(ui, |ui| {
+                    for key in SortKey::ALL {
+                        ui.selectable_value(&mut self.view.sort, key, key.label());
+                    }
+                });
+            ui.checkbox(&mut self.view.descending, "降序");
+
+            ui.label("分组:");
+            egui::ComboBox::from_id_source("view_group")
+                .selected_text(self.view.group.label())
+                .show_ui(ui, |ui| {
+                    for group in GroupBy::ALL {
+                        ui.selectable_value(&mut self.view.group, group, group.label());
+                    }
+                });
+        });
+    }
+
+    // 上下方向键在可见任务间移动选中项，Esc 取消选中
+    fn handle_keyboard_navigation(&mut self, ctx: &egui::Context) {
+        if ctx.wants_keyboard_input() {
+            return;
+        }
+        let (up, down, escape) = ctx.input(|i| (
+            i.key_pressed(egui::Key::ArrowUp),
+            i.key_pressed(egui::Key::ArrowDown),
+            i.key_pressed(egui::Key::Escape),
+        ));
+        if escape {
+            self.select_task(None);
+            return;
+        }
+        if !up && !down {
+            return;
+        }
+
+        let ids = self.visible_task_ids();
+        if ids.is_empty() {
+            return;
+        }
+        let current = self.selected_task_id.and_then(|id| ids.iter().position(|&i| i == id));
+        let next = match (current, down) {
+            (None, true) => 0,
+            (None, false) => ids.len() - 1,
+            (Some(i), true) => (i + 1).min(ids.len() - 1),
+            (Some(i), false) => i.saturating_sub(1),
+        };
+        self.select_task(Some(ids[next]));
+        self.scroll_to_selected = true;
+    }
+
+    fn show_tasks_tab(&mut self, ui: &mut egui::Ui) {
+        // 添加任务区域
+        ui.horizontal(|ui| {
+            ui.label("新任务:");
+            let response = ui.text_edit_singleline(&mut self.new_task_description);
+            
+            // 支持回车键添加任务
+            if response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter)) {
+                self.add_task();
+            }
+            
+            if ui.button("➕ 添加").clicked() {
+                self.add_task();
+            }
+        });
+
+        ui.separator();
+
+        // 过滤选项
+        ui.horizontal(|ui| {
+            ui.checkbox(&mut self.show_completed, "显示已完成任务");
+            
+            let tasks = self.task_list.get_tasks();
+            let total_count = tasks.len();
+            let pending_count = tasks.iter().filter(|t| !t.is_completed()).count();
+            let completed_count = total_count - pending_count;
+            
+            ui.label(format!("📊 总计: {} | 待处理: {} | 已完成: {} | 🍅 {} | ⌛ 剩余预估: {}", 
+                total_count, pending_count, completed_count, self.task_list.total_pomodoros(),
+                estimate::remaining_totals(tasks)));
+        });
+
+        // 排序与分组
+        self.show_view_controls(ui);
+
+        // 批量操作
+        self.show_bulk_toolbar(ui);
+
+        ui.separator();
+
+        // 任务列表 - 修复借用问题
+        egui::ScrollArea::vertical().show(ui, |ui| {
+            // 先收集需要执行的操作，避免在循环中修改
+            let mut actions = Vec::new();
+            // 每行的位置，用于计算拖放目标
+            let mut row_rects = Vec::new();
+            
+            let tasks = self.task_list.get_tasks().clone(); // 克隆任务列表
+            
+            if tasks.is_empty() {
+                ui.centered_and_justified(|ui| {
+                    ui.label("🎉 暂无任务，添加一个开始吧！");
+                });
+            } else {
+                // 只有按手动顺序且不分组时才能拖动排序
+                let can_drag = self.view == ViewOptions::default();
+                let groups = self.view.apply(self.visible_tasks(&tasks));
+
+                for group in &groups {
+                    if !group.title.is_empty() {
+                        ui.label(egui::RichText::new(format!("{} ({})", group.title, group.tasks.len())).strong());
+                    }
+                    for &task in &group.tasks {
+                        // 选中的任务高亮显示，点击任务行即选中
+                        let selected = self.selected_ids.contains(&task.id());
+                        let mut frame = egui::Frame::group(ui.style());
+                        if selected {
+                            frame = frame.fill(ui.visuals().selection.bg_fill.linear_multiply(0.3));
+                        }
+                        let row = frame.show(ui, |ui| {
                             ui.horizontal(|ui| {
+                                // 拖动手柄
+                                if can_drag {
+                                    let handle = ui.add(egui::Label::new("☰").sense(egui::Sense::drag()))
+                                        .on_hover_cursor(egui::CursorIcon::Grab);
+                                    if handle.drag_started() {
+                                        actions.push(("drag", task.id()));
+                                    }
+                                }
+
                                 // 任务状态指示器
-                                let status_color = if task.is_completed() {
-                                    egui::Color32::from_rgb(100, 200, 100) // 绿色
-                                } else {
-                                    egui::Color32::from_rgb(200, 200, 100) // 黄色
-                                };
-                                
-                                let status_text = if task.is_completed() {
-                                    "✅ 已完成"
-                                } else {
-                                    "⏳ 进行中"
-                                };
-                                
-                                ui.colored_label(status_color, status_text);
-                                
-                                // 任务ID和描述
+                                ui.colored_label(self.get_status_color(task), self.get_status_text(task));
+                            
+                                // 任务ID和描述，双击描述进入行内编辑
                                 ui.label(format!("#{}", task.id()));
-                                ui.label(&format!("{}", task));
-                                
+                                if self.editing_task_id == Some(task.id()) {
+                                    let response = ui.text_edit_singleline(&mut self.edit_buffer);
+                                    response.request_focus();
+                                    if ui.input(|i| i.key_pressed(egui::Key::Escape)) {
+                                        actions.push(("cancel_edit", task.id()));
+                                    } else if response.lost_focus() {
+                                        actions.push(("finish_edit", task.id()));
+                                    }
+                                } else {
+                                    let response = ui.add(egui::Label::new(format!("{}", task)).sense(egui::Sense::click()))
+                                        .on_hover_text("双击编辑");
+                                    if response.double_clicked() {
+                                        actions.push(("edit", task.id()));
+                                    } else if response.clicked() {
+                                        actions.push((click_action(ui), task.id()));
+                                    }
+                                }
+                            
                                 ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
                                     // 删除按钮
                                     if ui.button("🗑️ 删除").clicked() {
                                         actions.push(("delete", task.id()));
                                     }
-                                    
+                                
                                     // 挂起按钮
                                     if !task.is_completed() && ui.button("⏸️ 挂起").clicked() {
                                         actions.push(("suspend", task.id()));
                                     }
-                                    
+                                
                                     // 完成按钮
                                     if !task.is_completed() && ui.button("✅ 完成").clicked() {
                                         actions.push(("complete", task.id()));
                                     }
+
+                                    // 计时按钮
+                                    if task.is_timer_running() {
+                                        if ui.button("⏹ 停止").clicked() {
+                                            actions.push(("stop_timer", task.id()));
+                                        }
+                                    } else if *task.status() == TaskStatus::Pending && ui.button("▶ 计时").clicked() {
+                                        actions.push(("start_timer", task.id()));
+                                    }
                                 });
                             });
-                        });
-                        
+                        }).response.interact(egui::Sense::click());
+
+                        if row.clicked() {
+                            actions.push((click_action(ui), task.id()));
+                        }
+                        row_rects.push((task.id(), row.rect));
+                        if self.selected_task_id == Some(task.id()) && self.scroll_to_selected {
+                            row.scroll_to_me(None);
+                            self.scroll_to_selected = false;
+                        }
+                    
                         ui.add_space(5.0);
                     }
                 }
-                
-                // 在循环外执行收集到的操作
-                for (action, task_id) in actions {
-                    match action {
-                        "delete" => self.delete_task(task_id),
-                        "suspend" => self.suspend_task(task_id),
-                        "complete" => self.complete_task(task_id),
-                        _ => {}
+            }
+            
+            // 拖动中：显示插入位置，松开鼠标时移动任务
+            let mut dropped = None;
+            if let Some(dragged) = self.dragging_task_id {
+                ui.ctx().set_cursor_icon(egui::CursorIcon::Grabbing);
+                let target = ui.input(|i| i.pointer.hover_pos())
+                    .and_then(|pos| drop_target(&row_rects, dragged, pos));
+                if let Some((_, y)) = target {
+                    let stroke = egui::Stroke::new(2.0, ui.visuals().selection.stroke.color);
+                    ui.painter().hline(ui.max_rect().x_range(), y, stroke);
+                }
+                if ui.input(|i| i.pointer.any_released()) {
+                    self.dragging_task_id = None;
+                    dropped = target.map(|(placement, _)| (dragged, placement));
+                }
+            }
+            if let Some((task_id, placement)) = dropped {
+                self.move_task(task_id, placement);
+            }
+
+            // 在循环外执行收集到的操作
+            for (action, task_id) in actions {
+                match action {
+                    "delete" => self.delete_task(task_id),
+                    "suspend" => self.suspend_task(task_id),
+                    "complete" => self.complete_task(task_id),
+                    "start_timer" => self.start_timer(task_id),
+                    "stop_timer" => self.stop_timer(),
+                    "select" => self.select_task(Some(task_id)),
+                    "toggle_select" => self.toggle_selection(task_id),
+                    "range_select" => self.select_range(task_id),
+                    "edit" => {
+                        if let Some(task) = tasks.iter().find(|t| t.id() == task_id) {
+                            self.start_editing(task);
+                        }
                     }
+                    "drag" => self.dragging_task_id = Some(task_id),
+                    "finish_edit" => self.finish_editing(),
+                    "cancel_edit" => self.cancel_editing(),
+                    _ => {}
                 }
+            }
+        });
+    }
+
+    fn show_stats_tab(&mut self, ui: &mut egui::Ui) {
+        let stats = Statistics::compute(self.task_list.get_tasks(), chrono::Utc::now());
+
+        egui::ScrollArea::vertical().show(ui, |ui| {
+            egui::Grid::new("stats_grid")
+                .num_columns(2)
+                .spacing([24.0, 6.0])
+                .show(ui, |ui| {
+                    ui.label("总计 / 待处理 / 已挂起 / 已完成:");
+                    ui.label(format!("{} / {} / {} / {}", stats.total, stats.pending, stats.suspended, stats.completed));
+                    ui.end_row();
+
+                    ui.label("已过期:");
+                    if stats.overdue > 0 {
+                        ui.colored_label(egui::Color32::RED, format!("⚠ {}", stats.overdue));
+                    } else {
+                        ui.label("0");
+                    }
+                    ui.end_row();
+
+                    ui.label("完成率:");
+                    ui.add(egui::ProgressBar::new(stats.completion_rate as f32)
+                        .text(format!("{:.1}%", stats.completion_rate * 100.0)));
+                    ui.end_row();
+
+                    ui.label("平均完成耗时:");
+                    ui.label(stats.average_lead_time.map_or("-".to_string(), time_tracking::format_duration));
+                    ui.end_row();
+
+                    ui.label("连续完成:");
+                    ui.label(format!("当前 {} 天，最长 {} 天", stats.current_streak, stats.longest_streak));
+                    ui.end_row();
+
+                    ui.label("番茄 / 计时:");
+                    ui.label(format!("🍅 {} | ⏱ {}", stats.pomodoros, time_tracking::format_duration(stats.tracked_seconds)));
+                    ui.end_row();
+                });
+
+            ui.separator();
+            ui.label(egui::RichText::new("📅 最近 7 天完成").strong());
+            throughput_bars(ui, &stats.daily_throughput, |day| day.format("%m-%d").to_string());
+
+            ui.separator();
+            ui.label(egui::RichText::new("🗓 最近 4 周完成").strong());
+            throughput_bars(ui, &stats.weekly_throughput, |week| format!("{} 起", week.format("%m-%d")));
+        });
+    }
+
+    fn show_analytics_tab(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            ui.label("范围:");
+            for days in [7, 30, 90] {
+                ui.selectable_value(&mut self.analytics_days, days, format!("{} 天", days));
+            }
+        });
+
+        let activity = stats::daily_activity(self.task_list.get_tasks(), self.analytics_days);
+        let tags = stats::tag_distribution(self.task_list.get_tasks());
+        // 横轴为距起始日的天数，刻度显示为日期
+        let day_labels: Vec<String> = activity.iter().map(|a| a.day.format("%m-%d").to_string()).collect();
+        let day_axis = move |x: f64, _: usize, _: &std::ops::RangeInclusive<f64>| axis_label(&day_labels, x);
+        let tag_labels: Vec<String> = tags.iter().map(|(tag, _)| tag.clone()).collect();
+        let tag_axis = move |y: f64, _: usize, _: &std::ops::RangeInclusive<f64>| axis_label(&tag_labels, y);
+
+        egui::ScrollArea::vertical().show(ui, |ui| {
+            ui.label(egui::RichText::new("📅 每日新建 / 完成").strong());
+            let created = BarChart::new(activity.iter().enumerate()
+                .map(|(i, a)| Bar::new(i as f64 - 0.2, a.created as f64).width(0.4))
+                .collect())
+                .name("新建")
+                .color(egui::Color32::from_rgb(100, 150, 200));
+            let completed = BarChart::new(activity.iter().enumerate()
+                .map(|(i, a)| Bar::new(i as f64 + 0.2, a.completed as f64).width(0.4))
+                .collect())
+                .name("完成")
+                .color(egui::Color32::from_rgb(100, 200, 100));
+            Plot::new("created_vs_completed")
+                .height(180.0)
+                .legend(Legend::default())
+                .include_y(0.0)
+                .allow_scroll(false)
+                .x_axis_formatter(day_axis.clone())
+                .show(ui, |plot_ui| {
+                    plot_ui.bar_chart(created);
+                    plot_ui.bar_chart(completed);
+                });
+
+            ui.separator();
+            ui.label(egui::RichText::new("📉 未完成任务燃尽图").strong())
+                .on_hover_text("根据创建和完成时间推算，重新打开过的任务只按当前状态统计");
+            let open: PlotPoints = activity.iter().enumerate()
+                .map(|(i, a)| [i as f64, a.open as f64])
+                .collect();
+            Plot::new("burndown")
+                .height(180.0)
+                .include_y(0.0)
+                .allow_scroll(false)
+                .x_axis_formatter(day_axis)
+                .show(ui, |plot_ui| {
+                    plot_ui.line(Line::new(open).name("未完成").color(egui::Color32::from_rgb(220, 120, 80)));
+                });
+
+            ui.separator();
+            ui.label(egui::RichText::new("🏷 标签分布").strong());
+            let bars = BarChart::new(tags.iter().enumerate()
+                .map(|(i, (tag, count))| Bar::new(i as f64, *count as f64).name(tag).width(0.6))
+                .collect())
+                .horizontal()
+                .color(egui::Color32::from_rgb(180, 130, 200));
+            Plot::new("tag_distribution")
+                .height((tags.len() as f32 * 28.0).clamp(120.0, 320.0))
+                .include_x(0.0)
+                .allow_scroll(false)
+                .y_axis_formatter(tag_axis)
+                .show(ui, |plot_ui| plot_ui.bar_chart(bars));
+        });
+    }
+
+    fn show_report_tab(&mut self, ui: &mut egui::Ui) {
+        let mut preset = None;
+        ui.horizontal(|ui| {
+            if ui.button("今天").clicked() {
+                preset = Some(ReportRange::today());
+            }
+            if ui.button("本周").clicked() {
+                preset = Some(ReportRange::this_week());
+            }
+            if ui.button("上周").clicked() {
+                preset = Some(ReportRange::last_week());
+            }
+            ui.separator();
+            ui.label("从");
+            ui.add(egui::TextEdit::singleline(&mut self.report_from).desired_width(90.0));
+            ui.label("到");
+            ui.add(egui::TextEdit::singleline(&mut self.report_to).desired_width(90.0));
+            ui.separator();
+            ui.selectable_value(&mut self.report_format, ReportFormat::Markdown, "Markdown");
+            ui.selectable_value(&mut self.report_format, ReportFormat::Text, "纯文本");
+        });
+        if let Some(range) = preset {
+            self.report_from = range.from.to_string();
+            self.report_to = range.to.to_string();
+        }
+
+        let range = report::parse_date(&self.report_from)
+            .and_then(|from| Ok(ReportRange { from, to: report::parse_date(&self.report_to)? }));
+        let mut text = match range {
+            Ok(range) if range.from <= range.to => {
+                report::generate(self.task_list.get_tasks(), range, self.report_format, chrono::Utc::now())
+            }
+            Ok(_) => {
+                ui.colored_label(egui::Color32::from_rgb(220, 80, 80), "❌ 起始日期不能晚于结束日期");
+                return;
+            }
+            Err(e) => {
+                ui.colored_label(egui::Color32::from_rgb(220, 80, 80), format!("❌ {}", e));
+                return;
+            }
+        };
+
+        if ui.button("📋 复制到剪贴板").clicked() {
+            ui.output_mut(|o| o.copied_text = text.clone());
+            self.status_message = "📋 报告已复制".to_string();
+        }
+        ui.separator();
+        egui::ScrollArea::vertical().show(ui, |ui| {
+            // 报告每帧重新生成，编辑不会保留，但可以选择部分文本复制
+            ui.add(egui::TextEdit::multiline(&mut text)
+                .font(egui::TextStyle::Monospace)
+                .desired_width(f32::INFINITY));
+        });
+    }
+
+    fn show_detail_panel(&mut self, ctx: &egui::Context) {
+        let Some(task) = self.selected_task_id.and_then(|id| self.task_list.get_task(id)).cloned() else {
+            return;
+        };
+
+        let mut save = false;
+        let mut close = false;
+        egui::SidePanel::right("task_detail")
+            .resizable(true)
+            .default_width(260.0)
+            .show(ctx, |ui| {
+                ui.heading(format!("📝 任务详情 #{}", task.id()));
+                ui.separator();
+
+                egui::Grid::new("task_detail_grid")
+                    .num_columns(2)
+                    .spacing([8.0, 6.0])
+                    .show(ui, |ui| {
+                        ui.label("描述:");
+                        ui.text_edit_multiline(&mut self.detail_description);
+                        ui.end_row();
+
+                        ui.label("状态:");
+                        egui::ComboBox::from_id_source("task_detail_status")
+                            .selected_text(status_label(&self.detail_status))
+                            .show_ui(ui, |ui| {
+                                for status in [TaskStatus::Pending, TaskStatus::Suspended, TaskStatus::Completed] {
+                                    let label = status_label(&status);
+                                    ui.selectable_value(&mut self.detail_status, status, label);
+                                }
+                            });
+                        ui.end_row();
+
+                        ui.label("标签:");
+                        ui.add(egui::TextEdit::singleline(&mut self.detail_tags).hint_text("逗号分隔"));
+                        ui.end_row();
+
+                        ui.label("预估:");
+                        ui.add(egui::TextEdit::singleline(&mut self.detail_estimate).hint_text("30m、2h、3pt"));
+                        ui.end_row();
+
+                        ui.label("截止日期:");
+                        ui.add(egui::TextEdit::singleline(&mut self.detail_due).hint_text("2025-07-02、today、+3"));
+                        ui.end_row();
+
+                        ui.label("优先级:");
+                        egui::ComboBox::from_id_source("task_detail_priority")
+                            .selected_text(self.detail_priority.map_or("无", |p| p.label()))
+                            .show_ui(ui, |ui| {
+                                ui.selectable_value(&mut self.detail_priority, None, "无");
+                                for priority in Priority::ALL {
+                                    ui.selectable_value(&mut self.detail_priority, Some(priority), priority.label());
+                                }
+                            });
+                        ui.end_row();
+
+                        ui.label("重复:");
+                        ui.add(egui::TextEdit::singleline(&mut self.detail_repeat).hint_text("daily、weekly、2w、1m"));
+                        ui.end_row();
+
+                        ui.label("备注:");
+                        ui.add(egui::TextEdit::multiline(&mut self.detail_notes).desired_rows(3));
+                        ui.end_row();
+
+                        ui.label("创建时间:");
+                        ui.label(task.created_at());
+                        ui.end_row();
+
+                        ui.label("完成时间:");
+                        ui.label(task.completed_at().unwrap_or("-"));
+                        ui.end_row();
+
+                        let now = chrono::Utc::now();
+                        ui.label("番茄数:");
+                        ui.label(format!("🍅 {}", task.pomodoros()));
+                        ui.end_row();
+
+                        ui.label("累计用时:");
+                        let tracked = task.tracked_seconds(now);
+                        match task.estimate() {
+                            Some(Estimate::Minutes(minutes)) if minutes > 0 => ui.label(format!("{} ({:.0}% 预估)",
+                                time_tracking::format_duration(tracked),
+                                tracked as f64 / (f64::from(minutes) * 60.0) * 100.0)),
+                            _ => ui.label(time_tracking::format_duration(tracked)),
+                        };
+                        ui.end_row();
+
+                        ui.label("今日用时:");
+                        ui.label(time_tracking::format_duration(task.tracked_seconds_on(time_tracking::today(), now)));
+                        ui.end_row();
+                    });
+
+                ui.separator();
+                // 只记录了每个字段最后一次修改的时间，不是完整的修改历史
+                ui.collapsing("🕓 各字段最后修改时间 (UTC)", |ui| {
+                    egui::Grid::new("task_detail_modified").num_columns(2).show(ui, |ui| {
+                        for (field, time) in task.last_modified() {
+                            ui.label(field);
+                            ui.label(if time.is_empty() { "-" } else { time.as_str() });
+                            ui.end_row();
+                        }
+                    });
+                });
+
+                ui.separator();
+                ui.horizontal(|ui| {
+                    if ui.button("💾 保存修改").clicked() {
+                        save = true;
+                    }
+                    if ui.button("✖ 关闭").clicked() {
+                        close = true;
+                    }
+                });
+                ui.label("提示: ↑/↓ 切换任务，Esc 关闭");
             });
 
+        if save {
+            self.save_detail(task.id());
+        }
+        if close {
+            self.select_task(None);
+        }
+    }
+
+    fn get_status_color(&self, task: &Task) -> egui::Color32 {
+        match task.is_completed() {
+            true => egui::Color32::from_rgb(100, 200, 100), // 绿色
+            false => egui::Color32::from_rgb(200, 200, 100), // 黄色
+        }
+    }
+
+    fn get_status_text(&self, task: &Task) -> &str {
+        if task.is_completed() {
+            "✅ 已完成"
+        } else {
+            "⏳ 进行中"
+        }
+    }
+}
+
+// 根据指针位置计算拖放目标及插入线的纵坐标，指针在被拖动任务自身上时不移动
+fn drop_target(rows: &[(usize, egui::Rect)], dragged: usize, pos: egui::Pos2) -> Option<(Placement, f32)> {
+    let distance = |rect: &egui::Rect| (rect.center().y - pos.y).abs();
+    let (id, rect) = rows.iter().min_by(|(_, a), (_, b)| distance(a).total_cmp(&distance(b)))?;
+    if *id == dragged {
+        return None;
+    }
+    if pos.y < rect.center().y {
+        Some((Placement::Before(*id), rect.top() - 2.5))
+    } else {
+        Some((Placement::After(*id), rect.bottom() + 2.5))
+    }
+}
+
+// 只在整数刻度上显示对应的标签
+fn axis_label(labels: &[String], value: f64) -> String {
+    let index = value.round();
+    if (value - index).abs() > 1e-6 || index < 0.0 {
+        return String::new();
+    }
+    labels.get(index as usize).cloned().unwrap_or_default()
+}
+
+// 每行一个日期及对应完成数的横向条形
+fn throughput_bars(ui: &mut egui::Ui, rows: &[(chrono::NaiveDate, usize)], label: impl Fn(&chrono::NaiveDate) -> String) {
+    let max = rows.iter().map(|(_, count)| *count).max().unwrap_or(0).max(1);
+    for (day, count) in rows {
+        ui.horizontal(|ui| {
+            ui.add_sized([90.0, 18.0], egui::Label::new(label(day)));
+            ui.add(egui::ProgressBar::new(*count as f32 / max as f32)
+                .desired_width(240.0)
+                .text(count.to_string()));
+        });
+    }
+}
+
+// 根据按住的修改键决定点击任务行的含义
+fn click_action(ui: &egui::Ui) -> &'static str {
+    let modifiers = ui.input(|i| i.modifiers);
+    if modifiers.shift {
+        "range_select"
+    } else if modifiers.command {
+        "toggle_select"
+    } else {
+        "select"
+    }
+}
+
+fn status_label(status: &TaskStatus) -> &'static str {
+    match status {
+        TaskStatus::Pending => "⏳ 待处理",
+        TaskStatus::Suspended => "⏸️ 已挂起",
+        TaskStatus::Completed => "✅ 已完成",
+    }
+}
+
+impl eframe::App for TodoApp {
+    fn save(&mut self, storage: &mut dyn eframe::Storage) {
+        eframe::set_value(storage, VIEW_KEY, &self.view);
+        eframe::set_value(storage, POMODORO_KEY, &self.pomodoro.settings);
+    }
+
+    fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
+        self.handle_keyboard_navigation(ctx);
+        self.tick_pomodoro(ctx);
+        self.show_pomodoro_window(ctx);
+        self.show_detail_panel(ctx);
+
+        egui::CentralPanel::default().show(ctx, |ui| {
+            // 标题
+            ui.horizontal(|ui| {
+                ui.heading("📋 Todo List GUI");
+                ui.toggle_value(&mut self.show_pomodoro, "🍅 番茄钟");
+                self.show_timer_indicator(ui);
+            });
+            ui.separator();
+
+            // 标签页
+            ui.horizontal(|ui| {
+                ui.selectable_value(&mut self.tab, Tab::Tasks, "📋 任务");
+                ui.selectable_value(&mut self.tab, Tab::Stats, "📊 统计");
+                ui.selectable_value(&mut self.tab, Tab::Analytics, "📈 分析");
+                ui.selectable_value(&mut self.tab, Tab::Report, "📝 报告");
+            });
+            ui.separator();
+
+            match self.tab {
+                Tab::Tasks => self.show_tasks_tab(ui),
+                Tab::Stats => self.show_stats_tab(ui),
+                Tab::Analytics => self.show_analytics_tab(ui),
+                Tab::Report => self.show_report_tab(ui),
+            }
+
             // 底部状态栏
             ui.separator();
             ui.horizontal(|ui| {
@@ -350,7 +1351,10 @@ impl eframe::App for TodoApp {
                 
                 ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
                     if ui.button("💾 手动保存").clicked() {
-                        self.save_tasks();
+                        self.write_tasks();
+                    }
+                    if ui.button("📤 导出…").on_hover_text("导出当前视图中的任务").clicked() {
+                        self.export_tasks();
                     }
                 });
             });
@@ -0,0 +1,436 @@
+use chrono::{DateTime, Local, NaiveDate, NaiveDateTime, TimeZone, Utc};
+use chrono_tz::Tz;
+use crate::import::{self, ImportedTask};
+use crate::recurrence::Recurrence;
+use crate::task::{Priority, Task, TaskStatus};
+use crate::time_tracking;
+
+// iCalendar (RFC 5545) 的 VTODO 读写
+// 截止日期写为全天的 DUE，重复周期写为 RRULE (只支持 FREQ 和 INTERVAL)，
+// 优先级写为 PRIORITY: 高 1、中 5、低 9
+const PRODID: &str = "-//todolist-cli//VTODO//ZH";
+const ICAL_TIME_FORMAT: &str = "%Y%m%dT%H%M%SZ";
+const ICAL_DATE_FORMAT: &str = "%Y%m%d";
+
+// 从 .ics 中读出的一个 VTODO
+#[derive(Debug, Clone, PartialEq)]
+pub struct VTodo {
+    pub uid: Option<String>,
+    pub task: ImportedTask,
+}
+
+// 任务在日历中的 UID 即任务的 UUID
+pub fn task_uid(task: &Task) -> String {
+    task.uuid().to_string()
+}
+
+pub fn to_calendar<'a>(tasks: impl IntoIterator<Item = &'a Task>) -> String {
+    let stamp = Utc::now().format(ICAL_TIME_FORMAT).to_string();
+    let mut ics = String::new();
+    push_line(&mut ics, "BEGIN", "VCALENDAR");
+    push_line(&mut ics, "VERSION", "2.0");
+    push_line(&mut ics, "PRODID", PRODID);
+    for task in tasks {
+        write_vtodo(&mut ics, task, &task_uid(task), &stamp);
+    }
+    push_line(&mut ics, "END", "VCALENDAR");
+    ics
+}
+
+// 单个任务的日历资源，uid 可以来自远端
+pub fn to_resource(task: &Task, uid: &str) -> String {
+    let stamp = Utc::now().format(ICAL_TIME_FORMAT).to_string();
+    let mut ics = String::new();
+    push_line(&mut ics, "BEGIN", "VCALENDAR");
+    push_line(&mut ics, "VERSION", "2.0");
+    push_line(&mut ics, "PRODID", PRODID);
+    write_vtodo(&mut ics, task, uid, &stamp);
+    push_line(&mut ics, "END", "VCALENDAR");
+    ics
+}
+
+// 日历资源去掉 DTSTAMP (每次生成都不同) 后的内容，用于比较远端资源是否变化
+pub fn content_key(content: &str) -> String {
+    unfold(content).into_iter()
+        .filter(|line| !line.to_uppercase().starts_with("DTSTAMP"))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+// 不含 DTSTAMP 的任务内容，用于判断任务自上次同步后是否被修改
+pub fn fingerprint(task: &Task) -> String {
+    vtodo_properties(task).into_iter()
+        .map(|(name, value)| format!("{}:{}\n", name, value))
+        .collect()
+}
+
+fn write_vtodo(ics: &mut String, task: &Task, uid: &str, stamp: &str) {
+    push_line(ics, "BEGIN", "VTODO");
+    push_line(ics, "UID", &escape(uid));
+    push_line(ics, "DTSTAMP", stamp);
+    for (name, value) in vtodo_properties(task) {
+        push_line(ics, name, &value);
+    }
+    push_line(ics, "END", "VTODO");
+}
+
+fn vtodo_properties(task: &Task) -> Vec<(&'static str, String)> {
+    let mut properties = Vec::new();
+    if let Some(created) = to_ical_time(task.created_at()) {
+        properties.push(("CREATED", created));
+    }
+    properties.push(("SUMMARY", escape(task.description())));
+    if !task.notes().is_empty() {
+        properties.push(("DESCRIPTION", escape(task.notes())));
+    }
+    properties.push(("STATUS", status_to_ical(task).to_string()));
+    // iCalendar 没有"挂起"状态，用扩展属性区分
+    if *task.status() == TaskStatus::Suspended {
+        properties.push(("X-TODOLIST-STATUS", "SUSPENDED".to_string()));
+    }
+    if let Some(due) = task.due() {
+        properties.push(("DUE;VALUE=DATE", due.format(ICAL_DATE_FORMAT).to_string()));
+    }
+    if let Some(recurrence) = task.recurrence() {
+        properties.push(("RRULE", recurrence.to_rrule()));
+    }
+    if let Some(priority) = task.priority() {
+        properties.push(("PRIORITY", priority_to_ical(priority).to_string()));
+    }
+    if let Some(completed) = task.completed_at().and_then(to_ical_time) {
+        properties.push(("COMPLETED", completed));
+        properties.push(("PERCENT-COMPLETE", "100".to_string()));
+    }
+    if !task.tags().is_empty() {
+        let categories: Vec<String> = task.tags().iter().map(|t| escape(t)).collect();
+        properties.push(("CATEGORIES", categories.join(",")));
+    }
+    if let Some(estimate) = task.estimate() {
+        properties.push(("X-TODOLIST-ESTIMATE", estimate.to_string()));
+    }
+    if task.pomodoros() > 0 {
+        properties.push(("X-TODOLIST-POMODOROS", task.pomodoros().to_string()));
+    }
+    properties
+}
+
+// 计时中的任务对应 IN-PROCESS；挂起的任务仍是 NEEDS-ACTION，另加 X-TODOLIST-STATUS
+fn status_to_ical(task: &Task) -> &'static str {
+    match task.status() {
+        TaskStatus::Pending if task.is_timer_running() => "IN-PROCESS",
+        TaskStatus::Pending | TaskStatus::Suspended => "NEEDS-ACTION",
+        TaskStatus::Completed => "COMPLETED",
+    }
+}
+
+fn priority_to_ical(priority: Priority) -> u8 {
+    match priority {
+        Priority::High => 1,
+        Priority::Medium => 5,
+        Priority::Low => 9,
+    }
+}
+
+// RFC 5545: 0 为未定义，1~4 高，5 中，6~9 低
+fn priority_from_ical(value: &str) -> Option<Priority> {
+    match value.trim().parse::<u8>().ok()? {
+        1..=4 => Some(Priority::High),
+        5 => Some(Priority::Medium),
+        6..=9 => Some(Priority::Low),
+        _ => None,
+    }
+}
+
+// 其他客户端取消的任务导入为挂起
+fn status_from_ical(value: &str) -> TaskStatus {
+    match value.trim().to_uppercase().as_str() {
+        "COMPLETED" => TaskStatus::Completed,
+        "CANCELLED" => TaskStatus::Suspended,
+        _ => TaskStatus::Pending,
+    }
+}
+
+pub fn parse_calendar(content: &str) -> Vec<VTodo> {
+    let mut todos = Vec::new();
+    let mut current: Option<VTodo> = None;
+    for line in unfold(content) {
+        let Some((name, params, value)) = split_property(&line) else {
+            continue;
+        };
+        match (name.as_str(), current.as_mut()) {
+            ("BEGIN", None) if value.eq_ignore_ascii_case("VTODO") => {
+                current = Some(VTodo { uid: None, task: ImportedTask::new(String::new()) });
+            }
+            ("END", Some(_)) if value.eq_ignore_ascii_case("VTODO") => {
+                todos.extend(current.take().filter(|todo| !todo.task.description.is_empty()));
+            }
+            (_, Some(todo)) => apply_property(todo, &name, &params, value),
+            _ => {}
+        }
+    }
+    todos
+}
+
+fn apply_property(todo: &mut VTodo, name: &str, params: &str, value: &str) {
+    let task = &mut todo.task;
+    match name {
+        "UID" => {
+            todo.uid = Some(unescape(value));
+            task.uuid = import::parse_uuid(value);
+        }
+        "SUMMARY" => task.description = unescape(value).trim().to_string(),
+        "DESCRIPTION" => task.notes = unescape(value).trim_end().to_string(),
+        "STATUS" => {
+            // X-TODOLIST-STATUS 可能出现在 STATUS 之前
+            let status = status_from_ical(value);
+            if !(status == TaskStatus::Pending && task.status == TaskStatus::Suspended) {
+                task.status = status;
+            }
+        }
+        "X-TODOLIST-STATUS" if value.trim().eq_ignore_ascii_case("SUSPENDED") && task.status == TaskStatus::Pending => {
+            task.status = TaskStatus::Suspended;
+        }
+        "CREATED" => task.created_at = from_ical_time(value, params),
+        "DUE" => task.due = date_from_ical(value, params),
+        "RRULE" => task.recurrence = Recurrence::from_rrule(value),
+        "PRIORITY" => task.priority = priority_from_ical(value),
+        "COMPLETED" => {
+            task.completed_at = from_ical_time(value, params);
+            task.status = TaskStatus::Completed;
+        }
+        "CATEGORIES" => {
+            for tag in split_list(value) {
+                task.add_tag(&tag);
+            }
+        }
+        "X-TODOLIST-ESTIMATE" => task.estimate = value.parse().ok(),
+        "X-TODOLIST-POMODOROS" => task.pomodoros = value.parse().unwrap_or(0),
+        _ => {}
+    }
+}
+
+// 内容行超过 75 字节时折行，续行以空格开头
+fn push_line(ics: &mut String, name: &str, value: &str) {
+    let line = format!("{}:{}", name, value);
+    let mut width = 0;
+    for c in line.chars() {
+        if width + c.len_utf8() > 75 {
+            ics.push_str("\r\n ");
+            width = 1;
+        }
+        ics.push(c);
+        width += c.len_utf8();
+    }
+    ics.push_str("\r\n");
+}
+
+fn unfold(content: &str) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+    for line in content.lines() {
+        match (line.strip_prefix([' ', '\t']), lines.last_mut()) {
+            (Some(rest), Some(last)) => last.push_str(rest),
+            _ => lines.push(line.to_string()),
+        }
+    }
+    lines
+}
+
+// NAME;PARAM=x:VALUE -> (NAME, PARAM=x, VALUE)；参数保留原样，TZID 区分大小写
+fn split_property(line: &str) -> Option<(String, String, &str)> {
+    let (head, value) = line.split_once(':')?;
+    let (name, params) = head.split_once(';').unwrap_or((head, ""));
+    Some((name.trim().to_uppercase(), params.to_string(), value))
+}
+
+// 参数值，参数名不区分大小写: TZID=Europe/Berlin;VALUE=DATE
+fn param<'a>(params: &'a str, name: &str) -> Option<&'a str> {
+    params.split(';')
+        .filter_map(|param| param.split_once('='))
+        .find(|(key, _)| key.trim().eq_ignore_ascii_case(name))
+        .map(|(_, value)| value.trim().trim_matches('"'))
+}
+
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '\\' | ';' | ',' => {
+                escaped.push('\\');
+                escaped.push(c);
+            }
+            '\n' => escaped.push_str("\\n"),
+            '\r' => {}
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+fn unescape(text: &str) -> String {
+    let mut unescaped = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            unescaped.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') | Some('N') => unescaped.push('\n'),
+            Some(other) => unescaped.push(other),
+            None => {}
+        }
+    }
+    unescaped
+}
+
+// 按未转义的逗号拆分 CATEGORIES
+fn split_list(value: &str) -> Vec<String> {
+    let mut items = vec![String::new()];
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => items.last_mut().unwrap().extend(chars.next()),
+            ',' => items.push(String::new()),
+            c => items.last_mut().unwrap().push(c),
+        }
+    }
+    items.into_iter().map(|item| item.trim().to_string()).filter(|item| !item.is_empty()).collect()
+}
+
+fn to_ical_time(timestamp: &str) -> Option<String> {
+    time_tracking::parse_time(timestamp).map(|t| t.format(ICAL_TIME_FORMAT).to_string())
+}
+
+fn is_date_value(value: &str, params: &str) -> bool {
+    param(params, "VALUE").is_some_and(|v| v.eq_ignore_ascii_case("DATE")) || value.len() == 8
+}
+
+// TZID 一般为 IANA 时区名；有些客户端会加上路径前缀，如 /mozilla.org/20050126_1/America/New_York
+fn parse_tzid(tzid: &str) -> Option<Tz> {
+    std::iter::once(0)
+        .chain(tzid.match_indices('/').map(|(i, _)| i + 1))
+        .find_map(|start| tzid[start..].parse().ok())
+}
+
+// 日期时间转为 UTC：带 Z 的为 UTC，带 TZID 的按该时区换算，其余为本地(浮动)时间；
+// 无法识别的 TZID (如 Windows 时区名) 返回 None，不按本地时间猜测
+fn utc_from_ical(value: &str, params: &str) -> Option<DateTime<Utc>> {
+    if let Some(utc) = value.strip_suffix('Z') {
+        return NaiveDateTime::parse_from_str(utc, "%Y%m%dT%H%M%S").ok().map(|time| time.and_utc());
+    }
+    let time = NaiveDateTime::parse_from_str(value, "%Y%m%dT%H%M%S").ok()?;
+    match param(params, "TZID") {
+        Some(tzid) => parse_tzid(tzid)?.from_local_datetime(&time).earliest().map(|t| t.with_timezone(&Utc)),
+        None => Local.from_local_datetime(&time).earliest().map(|t| t.with_timezone(&Utc)),
+    }
+}
+
+// 支持 UTC 时间、本地(浮动/带 TZID)时间和 VALUE=DATE 的日期
+fn from_ical_time(value: &str, params: &str) -> Option<String> {
+    let value = value.trim();
+    if is_date_value(value, params) {
+        let date = NaiveDate::parse_from_str(value, ICAL_DATE_FORMAT).ok()?;
+        return Some(time_tracking::local_midnight_string(date));
+    }
+    utc_from_ical(value, params).map(|time| time.format(time_tracking::TIME_FORMAT).to_string())
+}
+
+// DUE 只取日期：日期时间换算为本地日期，时区无法识别时取写明的日期
+fn date_from_ical(value: &str, params: &str) -> Option<NaiveDate> {
+    let value = value.trim();
+    if !is_date_value(value, params) && let Some(time) = utc_from_ical(value, params) {
+        return Some(time.with_timezone(&Local).date_naive());
+    }
+    NaiveDate::parse_from_str(value.get(..8)?, ICAL_DATE_FORMAT).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::estimate::Estimate;
+    use crate::task::Priority;
+    use crate::task_list::TaskList;
+
+    fn calendar(vtodo: &str) -> String {
+        format!("BEGIN:VCALENDAR\r\nVERSION:2.0\r\nBEGIN:VTODO\r\n{}\r\nEND:VTODO\r\nEND:VCALENDAR\r\n", vtodo)
+    }
+
+    #[test]
+    fn export_then_import_round_trips() {
+        let mut task_list = TaskList::new();
+        for description in ["写报告; 第一版, 含附录\n第二行", "买牛奶", "暂停的任务", "一个很长很长的任务描述，用来检查超过七十五个字节的内容行会被正确折行并在导入时还原"] {
+            task_list.add_task(description.to_string());
+        }
+        let report = &mut task_list.tasks[0];
+        report.set_tags(vec!["work".to_string(), "a,b".to_string()]);
+        report.set_due(NaiveDate::from_ymd_opt(2025, 7, 2));
+        report.set_recurrence("2w".parse().ok());
+        report.set_estimate(Some(Estimate::Minutes(90)));
+        report.set_priority(Some(Priority::High));
+        report.set_notes("先列提纲; 再写正文\n附录见共享盘".to_string());
+        task_list.tasks[1].complete();
+        task_list.tasks[2].suspend();
+
+        let ics = to_calendar(task_list.get_tasks());
+        assert!(ics.lines().all(|line| line.len() <= 75), "{}", ics);
+        assert!(ics.contains("DUE;VALUE=DATE:20250702") && ics.contains("RRULE:FREQ=WEEKLY;INTERVAL=2"), "{}", ics);
+        assert!(!ics.contains("CANCELLED"));
+
+        let todos = parse_calendar(&ics);
+        assert_eq!(todos.len(), 4);
+        for (todo, task) in todos.iter().zip(task_list.get_tasks()) {
+            assert_eq!(todo.uid.as_deref(), Some(task.uuid()));
+            assert_eq!(todo.task.description, task.description());
+            assert_eq!(&todo.task.status, task.status());
+            assert_eq!(todo.task.tags, task.tags());
+            assert_eq!(todo.task.due, task.due());
+            assert_eq!(todo.task.recurrence, task.recurrence());
+            assert_eq!(todo.task.estimate, task.estimate());
+            assert_eq!(todo.task.priority, task.priority());
+            assert_eq!(todo.task.notes, task.notes());
+            assert_eq!(todo.task.created_at.as_deref(), Some(task.created_at()));
+            assert_eq!(todo.task.completed_at.as_deref(), task.completed_at());
+        }
+    }
+
+    #[test]
+    fn converts_tzid_times_through_the_named_zone() {
+        let todos = parse_calendar(&calendar("SUMMARY:会议\r\nCREATED;TZID=Asia/Shanghai:20250702T080000\r\nDUE;TZID=/mozilla.org/20050126_1/America/New_York:20250702T090000"));
+        assert_eq!(todos[0].task.created_at.as_deref(), Some("2025-07-02 00:00:00"));
+        let due = chrono::NaiveDate::from_ymd_opt(2025, 7, 2).unwrap().and_hms_opt(13, 0, 0).unwrap().and_utc();
+        assert_eq!(todos[0].task.due, Some(due.with_timezone(&Local).date_naive()));
+    }
+
+    #[test]
+    fn unknown_tzid_is_not_guessed_as_local() {
+        let todos = parse_calendar(&calendar("SUMMARY:会议\r\nCREATED;TZID=W. Europe Standard Time:20250702T080000\r\nDUE;TZID=W. Europe Standard Time:20250703T230000"));
+        assert_eq!(todos[0].task.created_at, None);
+        assert_eq!(todos[0].task.due, NaiveDate::from_ymd_opt(2025, 7, 3));
+    }
+
+    #[test]
+    fn maps_statuses_from_other_clients() {
+        let status = |vtodo: &str| parse_calendar(&calendar(vtodo))[0].task.status.clone();
+        assert_eq!(status("SUMMARY:a\r\nSTATUS:CANCELLED"), TaskStatus::Suspended);
+        assert_eq!(status("SUMMARY:a\r\nSTATUS:IN-PROCESS"), TaskStatus::Pending);
+        assert_eq!(status("SUMMARY:a\r\nX-TODOLIST-STATUS:SUSPENDED\r\nSTATUS:NEEDS-ACTION"), TaskStatus::Suspended);
+        assert_eq!(status("SUMMARY:a\r\nCOMPLETED:20250702T080000Z"), TaskStatus::Completed);
+    }
+
+    #[test]
+    fn maps_priority_ranges() {
+        let priority = |value: &str| parse_calendar(&calendar(&format!("SUMMARY:a\r\nPRIORITY:{}", value)))[0].task.priority;
+        assert_eq!(priority("2"), Some(Priority::High));
+        assert_eq!(priority("5"), Some(Priority::Medium));
+        assert_eq!(priority("7"), Some(Priority::Low));
+        assert_eq!(priority("0"), None);
+    }
+
+    #[test]
+    fn ignores_rrules_that_cannot_be_represented() {
+        let todos = parse_calendar(&calendar("SUMMARY:周会\r\nRRULE:FREQ=WEEKLY;BYDAY=MO,WE"));
+        assert_eq!(todos[0].task.recurrence, None);
+        let todos = parse_calendar(&calendar("SUMMARY:月报\r\nRRULE:FREQ=MONTHLY"));
+        assert_eq!(todos[0].task.recurrence, "1m".parse().ok());
+    }
+}
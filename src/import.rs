@@ -0,0 +1,572 @@
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+use std::str::FromStr;
+use chrono::NaiveDate;
+use serde_json::Value;
+use crate::error::{Result, TodoError};
+use crate::estimate::Estimate;
+use crate::recurrence::Recurrence;
+use crate::ical;
+use crate::task::{Priority, Task, TaskStatus};
+use crate::task_list::TaskList;
+use crate::time_tracking::{self, WorkSession};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ImportFormat {
+    TodoTxt,
+    Csv,
+    Markdown,
+    Json,
+    ICalendar,
+}
+
+// 从外部格式解析出的任务，导入时再分配新的ID
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImportedTask {
+    // 来自本工具导出的文件时保留原 UUID
+    pub uuid: Option<String>,
+    pub description: String,
+    pub status: TaskStatus,
+    pub created_at: Option<String>,
+    pub completed_at: Option<String>,
+    pub tags: Vec<String>,
+    pub estimate: Option<Estimate>,
+    pub due: Option<NaiveDate>,
+    pub priority: Option<Priority>,
+    pub recurrence: Option<Recurrence>,
+    pub notes: String,
+    pub pomodoros: u32,
+    pub sessions: Vec<WorkSession>,
+}
+
+// 导入计划：先预览再执行；重复指与已有任务的 UUID 或描述相同，或与本次导入中前面的任务描述相同
+#[derive(Debug, Clone, Default)]
+pub struct ImportPlan {
+    pub tasks: Vec<ImportedTask>,
+    // (任务, 已有任务的ID，None 表示与本次导入中的任务重复)
+    pub duplicates: Vec<(ImportedTask, Option<usize>)>,
+}
+
+// CSV 列映射：任务字段名 -> 文件中的列名
+pub type ColumnMapping = HashMap<String, String>;
+
+impl ImportedTask {
+    pub(crate) fn new(description: String) -> Self {
+        ImportedTask {
+            uuid: None,
+            description,
+            status: TaskStatus::Pending,
+            created_at: None,
+            completed_at: None,
+            tags: Vec::new(),
+            estimate: None,
+            due: None,
+            priority: None,
+            recurrence: None,
+            notes: String::new(),
+            pomodoros: 0,
+            sessions: Vec::new(),
+        }
+    }
+
+    pub(crate) fn add_tag(&mut self, tag: &str) {
+        let tag = tag.trim();
+        if !tag.is_empty() && !self.tags.iter().any(|t| t == tag) {
+            self.tags.push(tag.to_string());
+        }
+    }
+}
+
+impl ImportFormat {
+    // 根据文件扩展名推断格式
+    pub fn from_path(path: &Path) -> Option<Self> {
+        match path.extension()?.to_str()?.to_lowercase().as_str() {
+            "txt" => Some(ImportFormat::TodoTxt),
+            "csv" => Some(ImportFormat::Csv),
+            "md" | "markdown" => Some(ImportFormat::Markdown),
+            "json" => Some(ImportFormat::Json),
+            "ics" | "ical" => Some(ImportFormat::ICalendar),
+            _ => None,
+        }
+    }
+}
+
+pub fn parse(content: &str, format: ImportFormat, mapping: &ColumnMapping) -> Result<Vec<ImportedTask>> {
+    match format {
+        ImportFormat::TodoTxt => Ok(content.lines().filter_map(parse_todo_txt_line).collect()),
+        ImportFormat::Csv => parse_csv(content, mapping),
+        ImportFormat::Markdown => Ok(parse_markdown(content)),
+        ImportFormat::Json => parse_json(content),
+        ImportFormat::ICalendar => Ok(ical::parse_calendar(content).into_iter().map(|todo| todo.task).collect()),
+    }
+}
+
+impl ImportPlan {
+    pub fn new(task_list: &TaskList, imported: Vec<ImportedTask>, allow_duplicates: bool) -> Self {
+        let mut plan = ImportPlan::default();
+        let mut seen: HashSet<String> = HashSet::new();
+        for task in imported {
+            let key = normalize(&task.description);
+            let existing = task.uuid.as_deref().and_then(|uuid| task_list.find_by_uuid(uuid))
+                .or_else(|| task_list.get_tasks().iter().find(|t| normalize(t.description()) == key))
+                .map(Task::id);
+            let duplicate_in_batch = !seen.insert(key);
+            if !allow_duplicates && (existing.is_some() || duplicate_in_batch) {
+                plan.duplicates.push((task, existing));
+            } else {
+                plan.tasks.push(task);
+            }
+        }
+        plan
+    }
+
+    // 执行导入，返回新任务的ID
+    pub fn apply(self, task_list: &mut TaskList) -> Vec<usize> {
+        self.tasks.into_iter().map(|task| task_list.import_task(task)).collect()
+    }
+}
+
+fn normalize(description: &str) -> String {
+    description.split_whitespace().collect::<Vec<_>>().join(" ").to_lowercase()
+}
+
+// YYYY-MM-DD 转为当天本地零点的时间戳
+fn date_to_timestamp(token: &str) -> Option<String> {
+    let date = NaiveDate::parse_from_str(token, "%Y-%m-%d").ok()?;
+    Some(time_tracking::local_midnight_string(date))
+}
+
+// 接受本工具的时间格式、RFC 3339 以及单独的日期
+fn parse_timestamp(value: &str) -> Option<String> {
+    let value = value.trim();
+    if value.is_empty() {
+        return None;
+    }
+    let normalized = value.replacen('T', " ", 1);
+    if time_tracking::parse_time(&normalized).is_some() {
+        return Some(normalized);
+    }
+    if let Ok(t) = chrono::DateTime::parse_from_rfc3339(value) {
+        return Some(t.with_timezone(&chrono::Utc).format(time_tracking::TIME_FORMAT).to_string());
+    }
+    date_to_timestamp(value.get(..10).unwrap_or(value))
+}
+
+fn parse_date(value: &str) -> Option<NaiveDate> {
+    NaiveDate::parse_from_str(value.trim(), "%Y-%m-%d").ok()
+}
+
+// todo.txt 的 (A)/(B)/(C) 对应高/中/低优先级
+pub(crate) fn priority_from_letter(letter: &str) -> Option<Priority> {
+    match letter {
+        "A" => Some(Priority::High),
+        "B" => Some(Priority::Medium),
+        "C" => Some(Priority::Low),
+        _ => None,
+    }
+}
+
+// 只接受合法的 UUID，统一为小写带连字符的格式
+pub(crate) fn parse_uuid(value: &str) -> Option<String> {
+    uuid::Uuid::parse_str(value.trim()).ok().map(|uuid| uuid.to_string())
+}
+
+fn parse_status(value: &str) -> Option<TaskStatus> {
+    match value.trim().to_lowercase().as_str() {
+        "x" | "true" | "yes" | "y" | "1" | "done" | "completed" | "complete" | "closed" => Some(TaskStatus::Completed),
+        "false" | "no" | "n" | "0" | "" | "todo" | "open" | "pending" => Some(TaskStatus::Pending),
+        other => other.parse().ok(),
+    }
+}
+
+// todo.txt: [x [完成日期]] [(A)] [创建日期] 描述 +项目 @上下文 key:value
+// 项目和上下文都作为标签，(A)~(C) 为优先级，其余字母作为 pri-D 形式的标签
+fn parse_todo_txt_line(line: &str) -> Option<ImportedTask> {
+    let mut tokens = line.split_whitespace().peekable();
+    tokens.peek()?;
+
+    let mut task = ImportedTask::new(String::new());
+    if tokens.peek() == Some(&"x") {
+        tokens.next();
+        task.status = TaskStatus::Completed;
+        if let Some(completed) = tokens.peek().and_then(|t| date_to_timestamp(t)) {
+            tokens.next();
+            task.completed_at = Some(completed);
+        }
+    }
+    if let Some(priority) = tokens.peek().and_then(|t| t.strip_prefix('(')?.strip_suffix(')').filter(|p| p.len() == 1)) {
+        match priority_from_letter(priority) {
+            Some(priority) => task.priority = Some(priority),
+            None => task.add_tag(&format!("pri-{}", priority)),
+        }
+        tokens.next();
+    }
+    if let Some(created) = tokens.peek().and_then(|t| date_to_timestamp(t)) {
+        tokens.next();
+        task.created_at = Some(created);
+    }
+
+    let mut words = Vec::new();
+    for token in tokens {
+        if let Some(tag) = token.strip_prefix('+').or_else(|| token.strip_prefix('@')).filter(|t| !t.is_empty()) {
+            task.add_tag(tag);
+            continue;
+        }
+        match token.split_once(':') {
+            // 导出时写入的字段，id 会重新分配
+            Some(("id", _)) => {}
+            Some(("uuid", value)) => task.uuid = parse_uuid(value),
+            Some(("est", value)) => task.estimate = value.parse().ok(),
+            Some(("due", value)) if parse_date(value).is_some() => task.due = parse_date(value),
+            Some(("rec", value)) if value.parse::<Recurrence>().is_ok() => task.recurrence = value.parse().ok(),
+            // 已完成的任务没有 (A) 前缀，导出时写为 pri:A
+            Some(("pri", value)) if priority_from_letter(value).is_some() => task.priority = priority_from_letter(value),
+            Some(("pomo", value)) => task.pomodoros = value.parse().unwrap_or(0),
+            Some(("status", "suspended")) if task.status == TaskStatus::Pending => task.status = TaskStatus::Suspended,
+            Some(("note", value)) => task.notes = decode_note(value),
+            _ => words.push(token),
+        }
+    }
+    task.description = words.join(" ");
+    (!task.description.is_empty()).then_some(task)
+}
+
+// note: 的值为 URL 编码，无法解码的 % 原样保留
+fn decode_note(value: &str) -> String {
+    let bytes = value.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        match value.get(i + 1..i + 3).filter(|_| bytes[i] == b'%').and_then(|hex| u8::from_str_radix(hex, 16).ok()) {
+            Some(byte) => {
+                decoded.push(byte);
+                i += 3;
+            }
+            None => {
+                decoded.push(bytes[i]);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+// 任务下面缩进的非列表行作为该任务的备注
+fn parse_markdown(content: &str) -> Vec<ImportedTask> {
+    let mut tasks: Vec<ImportedTask> = Vec::new();
+    let mut last_is_task = false;
+    for line in content.lines() {
+        if let Some(task) = parse_markdown_line(line) {
+            tasks.push(task);
+            last_is_task = true;
+            continue;
+        }
+        let indented = line.starts_with([' ', '\t']) && !line.trim().is_empty();
+        match tasks.last_mut() {
+            Some(task) if last_is_task && indented => {
+                if !task.notes.is_empty() {
+                    task.notes.push('\n');
+                }
+                task.notes.push_str(line.strip_prefix("  ").unwrap_or(line.trim_start()));
+            }
+            _ => last_is_task = false,
+        }
+    }
+    tasks
+}
+
+// - [ ] 描述 #标签，兼容导出时附加的 <!-- key:value --> 注释
+fn parse_markdown_line(line: &str) -> Option<ImportedTask> {
+    let line = line.trim_start();
+    let rest = ["- ", "* ", "+ "].iter().find_map(|bullet| line.strip_prefix(bullet))?.trim_start();
+    let (done, rest) = match rest.get(..3)? {
+        "[ ]" => (false, &rest[3..]),
+        "[x]" | "[X]" => (true, &rest[3..]),
+        _ => return None,
+    };
+
+    let (text, meta) = match rest.split_once("<!--") {
+        Some((text, meta)) => (text, meta.trim_end().trim_end_matches("-->")),
+        None => (rest, ""),
+    };
+
+    let mut task = ImportedTask::new(String::new());
+    if done {
+        task.status = TaskStatus::Completed;
+    }
+    let mut words = Vec::new();
+    for word in text.split_whitespace() {
+        match word.strip_prefix('#').filter(|t| !t.is_empty()) {
+            Some(tag) => task.add_tag(tag),
+            None => words.push(word),
+        }
+    }
+    task.description = words.join(" ");
+
+    for (key, value) in meta.split_whitespace().filter_map(|pair| pair.split_once(':')) {
+        match key {
+            "uuid" => task.uuid = parse_uuid(value),
+            "status" => task.status = parse_status(value).unwrap_or(task.status.clone()),
+            "created" => task.created_at = parse_timestamp(value),
+            "completed" => task.completed_at = parse_timestamp(value),
+            "estimate" => task.estimate = value.parse().ok(),
+            "due" => task.due = parse_date(value),
+            "priority" => task.priority = value.parse().ok(),
+            "repeat" => task.recurrence = value.parse().ok(),
+            "pomodoros" => task.pomodoros = value.parse().unwrap_or(0),
+            _ => {}
+        }
+    }
+    (!task.description.is_empty()).then_some(task)
+}
+
+// 按列名读取 CSV；未在映射中指定的字段依次尝试默认列名
+fn parse_csv(content: &str, mapping: &ColumnMapping) -> Result<Vec<ImportedTask>> {
+    let mut reader = csv::ReaderBuilder::new().flexible(true).from_reader(content.as_bytes());
+    let headers: Vec<String> = reader.headers()?.iter().map(|h| h.trim().to_lowercase()).collect();
+
+    let column = |field: &str, defaults: &[&str]| -> Option<usize> {
+        match mapping.get(field) {
+            Some(name) => headers.iter().position(|h| *h == name.trim().to_lowercase()),
+            None => defaults.iter().find_map(|name| headers.iter().position(|h| h == name)),
+        }
+    };
+    let description = column("description", &["description", "title", "name", "task", "summary", "content"])
+        .ok_or_else(|| TodoError::InvalidInput("CSV 中找不到描述列，可用 --map description=列名 指定".to_string()))?;
+    let uuid = column("uuid", &["uuid"]);
+    let status = column("status", &["status", "done", "completed", "state"]);
+    let created = column("created_at", &["created_at", "created", "creation date"]);
+    let completed = column("completed_at", &["completed_at", "completion date", "done_at"]);
+    let tags = column("tags", &["tags", "labels", "categories", "project"]);
+    let estimate = column("estimate", &["estimate"]);
+    let due = column("due", &["due", "due date", "due_date", "deadline"]);
+    let priority = column("priority", &["priority"]);
+    let recurrence = column("repeat", &["repeat", "recurrence"]);
+    let notes = column("notes", &["notes", "note", "body"]);
+    let pomodoros = column("pomodoros", &["pomodoros"]);
+    let sessions = column("sessions", &["sessions"]);
+
+    let mut tasks = Vec::new();
+    for record in reader.records() {
+        let record = record?;
+        let field = |index: Option<usize>| index.and_then(|i| record.get(i)).map(str::trim).unwrap_or("");
+        let text = field(Some(description));
+        if text.is_empty() {
+            continue;
+        }
+
+        let mut task = ImportedTask::new(text.to_string());
+        task.uuid = parse_uuid(field(uuid));
+        task.status = parse_status(field(status)).unwrap_or(TaskStatus::Pending);
+        task.created_at = parse_timestamp(field(created));
+        task.completed_at = parse_timestamp(field(completed));
+        for tag in field(tags).split([',', ';']) {
+            task.add_tag(tag);
+        }
+        task.estimate = field(estimate).parse().ok();
+        task.due = parse_date(field(due).get(..10).unwrap_or(""));
+        task.priority = field(priority).parse().ok();
+        task.recurrence = field(recurrence).parse().ok();
+        task.notes = field(notes).to_string();
+        task.pomodoros = field(pomodoros).parse().unwrap_or(0);
+        task.sessions = field(sessions).split(';')
+            .filter_map(|s| s.split_once('~'))
+            .filter_map(|(start, end)| Some(WorkSession {
+                start: parse_timestamp(start)?,
+                end: Some(parse_timestamp(end)?),
+            }))
+            .collect();
+        tasks.push(task);
+    }
+    Ok(tasks)
+}
+
+// 本工具的 tasks.json，或其他工具导出的任务数组（也可以在 tasks / items 字段中）
+fn parse_json(content: &str) -> Result<Vec<ImportedTask>> {
+    if let Ok(task_list) = serde_json::from_str::<TaskList>(content) {
+        return Ok(task_list.get_tasks().iter().map(from_task).collect());
+    }
+
+    let value: Value = serde_json::from_str(content)?;
+    let items = match &value {
+        Value::Array(items) => items,
+        Value::Object(object) => ["tasks", "items", "todos", "data"].iter()
+            .find_map(|key| object.get(*key)?.as_array())
+            .ok_or_else(|| TodoError::InvalidInput("JSON 中找不到任务数组".to_string()))?,
+        _ => return Err(TodoError::InvalidInput("JSON 中找不到任务数组".to_string())),
+    };
+    Ok(items.iter().filter_map(from_json_item).collect())
+}
+
+fn from_task(task: &Task) -> ImportedTask {
+    ImportedTask {
+        uuid: Some(task.uuid().to_string()).filter(|uuid| !uuid.is_empty()),
+        description: task.description().to_string(),
+        status: task.status().clone(),
+        created_at: Some(task.created_at().to_string()),
+        completed_at: task.completed_at().map(str::to_string),
+        tags: task.tags().to_vec(),
+        estimate: task.estimate(),
+        due: task.due(),
+        priority: task.priority(),
+        recurrence: task.recurrence(),
+        notes: task.notes().to_string(),
+        pomodoros: task.pomodoros(),
+        sessions: task.sessions().iter().filter(|s| !s.is_running()).cloned().collect(),
+    }
+}
+
+fn from_json_item(item: &Value) -> Option<ImportedTask> {
+    let text = |keys: &[&str]| keys.iter().find_map(|key| item.get(*key)?.as_str().map(str::to_string));
+    let description = text(&["description", "title", "name", "content", "text", "summary"])?;
+    let mut task = ImportedTask::new(description.trim().to_string());
+    task.uuid = text(&["uuid", "uid"]).and_then(|uuid| parse_uuid(&uuid));
+
+    let status = ["completed", "done", "checked", "isCompleted", "status"].iter()
+        .find_map(|key| match item.get(*key)? {
+            Value::Bool(done) => Some(if *done { TaskStatus::Completed } else { TaskStatus::Pending }),
+            Value::String(s) => parse_status(s),
+            _ => None,
+        });
+    task.status = status.unwrap_or(TaskStatus::Pending);
+    task.created_at = text(&["created_at", "created", "createdAt", "dateCreated"]).and_then(|s| parse_timestamp(&s));
+    task.completed_at = text(&["completed_at", "completedAt", "dateCompleted"]).and_then(|s| parse_timestamp(&s));
+    task.due = text(&["due", "due_date", "dueDate"]).and_then(|s| parse_date(s.get(..10)?));
+    task.priority = text(&["priority"]).and_then(|s| s.parse().ok());
+    task.notes = text(&["notes", "note", "body"]).map(|s| s.trim().to_string()).unwrap_or_default();
+
+    match ["tags", "labels", "categories"].iter().find_map(|key| item.get(*key)) {
+        Some(Value::Array(tags)) => tags.iter().filter_map(Value::as_str).for_each(|tag| task.add_tag(tag)),
+        Some(Value::String(tags)) => tags.split(',').for_each(|tag| task.add_tag(tag)),
+        _ => {}
+    }
+    (!task.description.is_empty()).then_some(task)
+}
+
+impl FromStr for ImportFormat {
+    type Err = TodoError;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "todo.txt" | "todotxt" | "txt" => Ok(ImportFormat::TodoTxt),
+            "csv" => Ok(ImportFormat::Csv),
+            "md" | "markdown" => Ok(ImportFormat::Markdown),
+            "json" => Ok(ImportFormat::Json),
+            "ics" | "ical" | "icalendar" => Ok(ImportFormat::ICalendar),
+            other => Err(TodoError::InvalidInput(format!("未知的导入格式: {}", other))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::export::{self, ExportFormat};
+
+    fn parse_all(content: &str, format: ImportFormat) -> Vec<ImportedTask> {
+        parse(content, format, &ColumnMapping::new()).unwrap()
+    }
+
+    fn sample_list() -> TaskList {
+        let mut task_list = TaskList::new();
+        task_list.add_task("写报告".to_string());
+        task_list.add_task("买牛奶".to_string());
+        let report = &mut task_list.tasks[0];
+        report.set_tags(vec!["work".to_string()]);
+        report.set_due(NaiveDate::from_ymd_opt(2025, 7, 2));
+        report.set_priority(Some(Priority::High));
+        report.set_estimate(Some(Estimate::Minutes(90)));
+        report.set_notes("第一行 100%\n  第二行".to_string());
+        let milk = &mut task_list.tasks[1];
+        milk.set_priority(Some(Priority::Low));
+        milk.complete();
+        task_list
+    }
+
+    #[test]
+    fn parses_todo_txt_fields() {
+        let tasks = parse_all("x 2024-01-03 (B) 2024-01-01 买牛奶 +home @shop due:2024-01-05 est:30m\n(D) 其他\n\n", ImportFormat::TodoTxt);
+        assert_eq!(tasks.len(), 2);
+        let task = &tasks[0];
+        assert_eq!(task.description, "买牛奶");
+        assert_eq!(task.status, TaskStatus::Completed);
+        assert_eq!(task.tags, ["home", "shop"]);
+        assert_eq!(task.priority, Some(Priority::Medium));
+        assert_eq!(task.due, NaiveDate::from_ymd_opt(2024, 1, 5));
+        assert_eq!(task.estimate, Some(Estimate::Minutes(30)));
+        assert!(task.completed_at.is_some() && task.created_at.is_some());
+        // 没有对应优先级的字母仍作为标签
+        assert_eq!(tasks[1].tags, ["pri-D"]);
+        assert_eq!(tasks[1].priority, None);
+    }
+
+    #[test]
+    fn parses_markdown_checkboxes() {
+        let tasks = parse_all("# 标题\n- [ ] 写报告 #work\n  先列提纲\n* [x] 买牛奶\n- 普通列表\n  不是备注\n", ImportFormat::Markdown);
+        assert_eq!(tasks.len(), 2);
+        assert_eq!(tasks[0].notes, "先列提纲");
+        assert_eq!(tasks[1].notes, "");
+        assert_eq!(tasks[0].tags, ["work"]);
+        assert_eq!(tasks[1].status, TaskStatus::Completed);
+    }
+
+    #[test]
+    fn csv_uses_column_mapping() {
+        let content = "Title,Done,Labels\n写报告,yes,work;q3\n,no,\n";
+        let tasks = parse_all(content, ImportFormat::Csv);
+        assert_eq!(tasks.len(), 1);
+        assert_eq!(tasks[0].status, TaskStatus::Completed);
+        assert_eq!(tasks[0].tags, ["work", "q3"]);
+
+        let mapping = ColumnMapping::from([("description".to_string(), "Labels".to_string())]);
+        let tasks = parse(content, ImportFormat::Csv, &mapping).unwrap();
+        assert_eq!(tasks[0].description, "work;q3");
+        assert!(parse("a,b\n1,2\n", ImportFormat::Csv, &ColumnMapping::new()).is_err());
+    }
+
+    #[test]
+    fn parses_json_from_other_tools() {
+        let content = r#"{"items": [{"title": "写报告", "completed": true, "labels": ["work"], "dueDate": "2025-07-02T00:00:00Z", "priority": "high"}, {"id": 3}]}"#;
+        let tasks = parse_all(content, ImportFormat::Json);
+        assert_eq!(tasks.len(), 1);
+        assert_eq!(tasks[0].status, TaskStatus::Completed);
+        assert_eq!(tasks[0].due, NaiveDate::from_ymd_opt(2025, 7, 2));
+        assert_eq!(tasks[0].priority, Some(Priority::High));
+        assert!(parse("42", ImportFormat::Json, &ColumnMapping::new()).is_err());
+    }
+
+    #[test]
+    fn export_then_import_keeps_fields() {
+        let task_list = sample_list();
+        for (export_format, import_format) in [
+            (ExportFormat::Csv, ImportFormat::Csv),
+            (ExportFormat::Markdown, ImportFormat::Markdown),
+            (ExportFormat::TodoTxt, ImportFormat::TodoTxt),
+        ] {
+            let content = export::export(task_list.get_tasks(), export_format).unwrap();
+            let tasks = parse_all(&content, import_format);
+            assert_eq!(tasks.len(), 2, "{:?}", export_format);
+            for (imported, original) in tasks.iter().zip(task_list.get_tasks()) {
+                assert_eq!(imported.uuid.as_deref(), Some(original.uuid()), "{:?}", export_format);
+                assert_eq!(imported.description, original.description());
+                assert_eq!(&imported.status, original.status());
+                assert_eq!(imported.tags, original.tags());
+                assert_eq!(imported.estimate, original.estimate());
+                assert_eq!(imported.due, original.due(), "{:?}", export_format);
+                assert_eq!(imported.priority, original.priority(), "{:?}", export_format);
+                assert_eq!(imported.notes, original.notes(), "{:?}", export_format);
+            }
+        }
+    }
+
+    #[test]
+    fn plan_skips_duplicates() {
+        let task_list = sample_list();
+        let imported = parse_all("写报告\n  买牛奶 \n新任务\n新任务\n", ImportFormat::TodoTxt);
+        let plan = ImportPlan::new(&task_list, imported.clone(), false);
+        assert_eq!(plan.tasks.len(), 1);
+        let existing: Vec<Option<usize>> = plan.duplicates.iter().map(|(_, id)| *id).collect();
+        assert_eq!(existing, [Some(1), Some(2), None]);
+        assert_eq!(ImportPlan::new(&task_list, imported, true).tasks.len(), 4);
+    }
+}
@@ -0,0 +1,24 @@
+// 命令行和图形界面共用的任务模型与存储
+pub mod task;
+pub mod task_list;
+pub mod storage;
+pub mod error;
+pub mod query;
+pub mod view;
+pub mod time_tracking;
+pub mod pomodoro;
+pub mod estimate;
+pub mod recurrence;
+pub mod stats;
+pub mod report;
+pub mod export;
+pub mod import;
+pub mod ical;
+pub mod caldav;
+pub mod git;
+pub mod api;
+pub mod server;
+pub mod rpc;
+pub mod tui;
+pub mod shell;
+pub mod completions;
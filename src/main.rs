@@ -1,25 +1,74 @@
-mod task;
-mod task_list;
-mod storage;
-mod error;
-
 use std::io::{self, Write};
-use storage::Storage;
-use task_list::TaskList;
-use error::Result;
+use std::path::Path;
+use todolist_cli::storage::{self, Storage};
+use todolist_cli::git;
+use todolist_cli::task::{self, Priority, TaskStatus, TaskUpdate};
+use todolist_cli::task_list::{Placement, TaskList};
+use todolist_cli::error::{Result, TodoError};
+use todolist_cli::query::{QueryTerm, TaskQuery};
+use todolist_cli::view::ViewOptions;
+use todolist_cli::time_tracking;
+use todolist_cli::estimate::{self, Estimate};
+use todolist_cli::recurrence::Recurrence;
+use todolist_cli::stats::Statistics;
+use todolist_cli::report::{self, ReportFormat, ReportRange};
+use todolist_cli::export::{self, ExportFormat};
+use todolist_cli::import::{self, ColumnMapping, ImportFormat, ImportPlan};
+use todolist_cli::caldav::{self, CalDavClient, CalDavConfig, ConflictPolicy, SyncState};
+use todolist_cli::server::{self, ServerOptions};
+use todolist_cli::rpc;
+use todolist_cli::tui;
+use todolist_cli::shell;
+use todolist_cli::completions::{self, Shell};
+use todolist_cli::api::{NewTask, OutputFormat, StatsRecord, TaskRecord};
+use chrono::{NaiveDate, Utc};
 
 fn main() {
-    if let Err(e) = run() {
-        eprintln!("错误: {}", e);
+    let mut args: Vec<String> = std::env::args().skip(1).collect();
+    let result = take_format_arg(&mut args);
+    let format = result.as_ref().copied().unwrap_or_default();
+    // --format 时错误以 {"code", "message"} 输出到标准错误
+    if let Err(e) = result.and_then(|format| run(&args, format)) {
+        if format.is_text() {
+            eprintln!("错误: {}", e);
+        } else {
+            eprintln!("{}", format.render_error(&e));
+        }
         std::process::exit(1);
     }
 }
 
-fn run() -> Result<()> {
+// --format <text|json|jsonl|tsv> 可以出现在命令之前，或支持输出格式的命令的任意位置；
+// report、export、import 有自己的 --format (文件格式)，不能在这里取走
+fn take_format_arg(args: &mut Vec<String>) -> Result<OutputFormat> {
+    let is_format = |arg: &String| arg == "--format" || arg.starts_with("--format=");
+    let index = match args.first() {
+        Some(first) if is_format(first) => 0,
+        Some(command) if FORMATTED_COMMANDS.contains(&command.as_str()) => match args.iter().position(is_format) {
+            Some(index) => index,
+            None => return Ok(OutputFormat::Text),
+        },
+        _ => return Ok(OutputFormat::Text),
+    };
+    let arg = args.remove(index);
+    let value = match arg.strip_prefix("--format=") {
+        Some(value) => value.to_string(),
+        None if index < args.len() => args.remove(index),
+        None => return Err(TodoError::InvalidInput("--format 需要一个值: text|json|jsonl|tsv".to_string())),
+    };
+    value.parse()
+}
+
+fn run(args: &[String], format: OutputFormat) -> Result<()> {
+    // 带参数时执行单条命令，否则进入交互模式
+    if !args.is_empty() {
+        return run_command(args, format);
+    }
+
     println!("=== Todo List CLI ===");
     
     // 加载或创建任务列表
-    let mut task_list = match Storage::load_tasks() {
+    let task_list = match Storage::load_tasks() {
         Ok(tasks) => {
             if Storage::storage_exists() {
                 println!("✅ 成功读取 tasks.json 文件");
@@ -30,76 +79,204 @@ fn run() -> Result<()> {
             }
             tasks
         }
+        // 不用空列表覆盖无法读取的文件，修复或移走后再启动
         Err(e) => {
-            eprintln!("❌ 读取任务文件失败: {}", e);
-            println!("📝 创建新的任务列表");
-            let new_list = TaskList::new();
-            Storage::save_tasks(&new_list)?;
-            new_list
+            eprintln!("❌ 读取 tasks.json 失败，请修复或移走该文件后重试");
+            return Err(e);
         }
     };
     
     // 显示当前任务列表
     display_tasks(&task_list);
+    println!("💡 输入 help 查看命令，Tab 补全命令、任务ID和标签");
     
-    // 主循环
-    loop {
-        display_menu();
-        
-        let choice = get_user_input("请选择操作 (0-4): ")?;
-        
-        match choice.trim() {
-            "0" => {
-                println!("👋 再见！");
-                break;
+    // 交互模式中的命令与单条命令相同
+    shell::run(|args| {
+        let mut args = args.to_vec();
+        let format = take_format_arg(&mut args)?;
+        if args.is_empty() {
+            return Err(TodoError::InvalidInput("缺少命令，输入 help 查看可用命令".to_string()));
+        }
+        run_command(&args, format)
+    })
+}
+
+// 支持 --format 的命令：列表、统计，以及输出被修改任务的命令
+const FORMATTED_COMMANDS: [&str; 16] = [
+    "add", "list", "ls", "stats", "edit", "done", "complete", "suspend", "delete", "rm",
+    "estimate", "move", "start", "stop", "tag", "untag",
+];
+
+// 单条命令模式: todolist-cli <命令> [参数...]
+fn run_command(args: &[String], format: OutputFormat) -> Result<()> {
+    if !format.is_text() && !FORMATTED_COMMANDS.contains(&args[0].as_str()) {
+        return Err(TodoError::InvalidInput(format!("{} 命令不支持 --format", args[0])));
+    }
+    // tui 自己加载任务，先确认有终端再读取 tasks.json
+    if args[0] == "tui" {
+        return tui::run();
+    }
+    let mut task_list = Storage::load_tasks()?;
+    // 被修改的任务，删除的任务为删除前的状态
+    let mut changed: Vec<TaskRecord> = Vec::new();
+
+    match args[0].as_str() {
+        // 只读命令，不需要保存
+        "list" | "ls" => return list_with_args(&task_list, &args[1..], format),
+        "edit" => {
+            let id = edit_task_with_args(&mut task_list, &args[1..], format)?;
+            changed = task_records(&task_list, &[id]);
+        }
+        "add" => {
+            let id = add_with_args(&mut task_list, &args[1..])?;
+            if format.is_text() {
+                let task = task_list.get_task(id).ok_or(TodoError::TaskNotFound(id))?;
+                println!("✅ 已添加任务 #{}: {}", id, task.description());
             }
-            "1" => {
-                add_task(&mut task_list)?;
+            changed = task_records(&task_list, &[id]);
+        }
+        "done" | "complete" => changed = bulk_with_args(&mut task_list, BulkOp::Complete, &args[1..], format)?,
+        "suspend" => changed = bulk_with_args(&mut task_list, BulkOp::Suspend, &args[1..], format)?,
+        "delete" | "rm" => changed = bulk_with_args(&mut task_list, BulkOp::Delete, &args[1..], format)?,
+        "time" => return show_time_report(&task_list, &args[1..]),
+        "estimates" => return show_estimate_report(&task_list),
+        "report" => return report_with_args(&task_list, &args[1..]),
+        "export" => return export_with_args(&task_list, &args[1..]),
+        "import" => {
+            // 预览模式不保存
+            if !import_with_args(&mut task_list, &args[1..])? {
+                return Ok(());
             }
-            "2" => {
-                suspend_task(&mut task_list)?;
+        }
+        // git 命令直接操作 tasks.json，不能再用之前加载的任务覆盖
+        "git-init" => {
+            Storage::save_tasks(&task_list)?;
+            git::init(storage::STORAGE_FILE, args.get(1).map(String::as_str))?;
+            println!("📚 已开启 git 存储，之后每次保存都会自动提交");
+            return Ok(());
+        }
+        "log" => return show_history(&args[1..]),
+        // sync [远程名|地址] 为 git 同步；sync --replica <文件|目录> 与共享文件夹中的副本合并
+        "sync" => match args.get(1).map(String::as_str) {
+            Some("--replica") => {
+                let path = args.get(2)
+                    .ok_or_else(|| TodoError::InvalidInput("用法: sync --replica <文件或目录>".to_string()))?;
+                sync_with_replica(&mut task_list, Path::new(path))?;
+            }
+            remote => {
+                let summary = git::sync(storage::STORAGE_FILE, remote)?;
+                println!("🔄 {}", summary);
+                return Ok(());
             }
-            "3" => {
-                complete_task(&mut task_list)?;
+        },
+        "caldav" => caldav_sync_with_args(&mut task_list, &args[1..])?,
+        "serve" => return serve_with_args(&args[1..]),
+        // 编辑器插件等通过标准输入输出调用，每行一个 JSON-RPC 消息
+        "rpc" => return rpc::serve(io::stdin().lock(), io::stdout()),
+        // ids 和 tags 供补全脚本读取当前的任务
+        "completions" => {
+            let output = match args.get(1).map(String::as_str) {
+                Some("ids") => completions::task_ids(&task_list),
+                Some("tags") => completions::tags(&task_list),
+                Some(shell) => shell.parse::<Shell>()?.script(),
+                None => return Err(TodoError::InvalidInput("用法: completions <bash|zsh|fish>".to_string())),
+            };
+            print_output(&output);
+            return Ok(());
+        }
+        "man" => {
+            print_output(&completions::man_page());
+            return Ok(());
+        }
+        "stats" if !format.is_text() => {
+            let stats = Statistics::compute(task_list.get_tasks(), Utc::now());
+            print_output(&format.render_object(&StatsRecord::from(&stats)));
+            return Ok(());
+        }
+        "stats" => {
+            show_statistics(&task_list);
+            return Ok(());
+        }
+        "estimate" => {
+            let id = parse_id_arg(&task_list, args.get(1), "用法: estimate <id> <30m|2h|3pt|->")?;
+            let value = args.get(2)
+                .ok_or_else(|| TodoError::InvalidInput("用法: estimate <id> <30m|2h|3pt|->".to_string()))?;
+            let update = TaskUpdate { estimate: Some(parse_estimate(value)?), ..Default::default() };
+            task_list.update_task(id, update)?;
+            if format.is_text() {
+                println!("⌛ 任务 #{} 的预估已更新", id);
             }
-            "4" => {
-                delete_task(&mut task_list)?;
+            changed = task_records(&task_list, &[id]);
+        }
+        "move" => {
+            let id = move_task_with_args(&mut task_list, &args[1..], format)?;
+            changed = task_records(&task_list, &[id]);
+        }
+        "start" => {
+            let id = parse_id_arg(&task_list, args.get(1), "用法: start <id>")?;
+            if format.is_text() {
+                start_timer_for(&mut task_list, id)?;
+            } else {
+                // 同时输出被暂停计时的任务
+                let paused = task_list.start_timer(id)?;
+                changed = task_records(&task_list, &paused.into_iter().chain([id]).collect::<Vec<_>>());
             }
-            _ => {
-                println!("❌ 无效选择，请输入 0-4 之间的数字");
-                continue;
+        }
+        "stop" if !format.is_text() => {
+            if let Some(id) = task_list.stop_timer() {
+                changed = task_records(&task_list, &[id]);
             }
         }
-        
-        // 保存任务列表
-        Storage::save_tasks(&task_list)?;
-        
-        // 显示更新后的任务列表
-        println!();
-        display_tasks(&task_list);
+        "stop" => stop_timer(&mut task_list),
+        "tag" | "untag" => {
+            let tag = args.get(1)
+                .ok_or_else(|| TodoError::InvalidInput(format!("用法: {} <标签> <任务ID...>", args[0])))?
+                .clone();
+            let op = if args[0] == "tag" { BulkOp::Tag(tag) } else { BulkOp::Untag(tag) };
+            changed = bulk_with_args(&mut task_list, op, &args[2..], format)?;
+        }
+        other => return Err(TodoError::InvalidInput(format!("未知命令: {}", other))),
+    }
+
+    Storage::save_tasks(&task_list)?;
+    if !format.is_text() {
+        print_output(&format.render_tasks(&changed));
     }
-    
     Ok(())
 }
 
-fn display_menu() {
-    println!("\n=== 操作菜单 ===");
-    println!("0. 退出程序");
-    println!("1. 添加任务");
-    println!("2. 挂起任务");
-    println!("3. 完成任务");
-    println!("4. 删除任务");
+// --format 的输出常接到 head、jq 等管道，对方提前关闭时不报错
+fn print_output(text: &str) {
+    writeln!(io::stdout().lock(), "{}", text).ok();
+}
+
+fn task_records(task_list: &TaskList, ids: &[usize]) -> Vec<TaskRecord> {
+    ids.iter().filter_map(|&id| task_list.get_task(id)).map(TaskRecord::from).collect()
 }
 
 fn display_tasks(task_list: &TaskList) {
+    display_view(task_list, &ViewOptions::default(), &TaskQuery::default(), false);
+}
+
+fn display_view(task_list: &TaskList, view: &ViewOptions, query: &TaskQuery, show_uuid: bool) {
     println!("\n📋 当前任务列表:");
     let tasks = task_list.get_tasks();
+    let groups = view.apply(tasks.iter().filter(|t| query.matches(t)));
     
-    if tasks.is_empty() {
+    if groups.iter().all(|g| g.tasks.is_empty()) {
         println!("  (暂无任务)");
     } else {
-        for task in tasks {
-            println!("  {}", task);
+        for group in groups {
+            if !group.title.is_empty() {
+                println!(" ▸ {} ({})", group.title, group.tasks.len());
+            }
+            for task in group.tasks {
+                if show_uuid {
+                    println!("  {} ({})", task, task.uuid());
+                } else {
+                    println!("  {}", task);
+                }
+            }
         }
     }
     
@@ -107,86 +284,728 @@ fn display_tasks(task_list: &TaskList) {
     println!("📊 待处理任务: {} 个", pending_count);
 }
 
+// list [--sort <排序>] [--desc] [--group <分组>] [--uuid] [查询条件...]
+fn list_with_args(task_list: &TaskList, args: &[String], format: OutputFormat) -> Result<()> {
+    let mut view = ViewOptions::default();
+    let mut show_uuid = false;
+    let mut query = TaskQuery::default();
+    let mut rest = args.iter();
+    while let Some(arg) = rest.next() {
+        match arg.as_str() {
+            "--sort" => {
+                view.sort = rest.next()
+                    .ok_or_else(|| TodoError::InvalidInput("--sort 需要一个值: manual|created|status|description|completed|due|priority".to_string()))?
+                    .parse()?;
+            }
+            "--group" => {
+                view.group = rest.next()
+                    .ok_or_else(|| TodoError::InvalidInput("--group 需要一个值: none|status|tag|due".to_string()))?
+                    .parse()?;
+            }
+            "--desc" => view.descending = true,
+            "--uuid" => show_uuid = true,
+            term => query.push(term.parse()?),
+        }
+    }
+    
+    if format.is_text() {
+        display_view(task_list, &view, &query, show_uuid);
+    } else {
+        let records: Vec<TaskRecord> = view.apply(task_list.get_tasks().iter().filter(|t| query.matches(t)))
+            .into_iter()
+            .flat_map(|group| group.tasks)
+            .map(TaskRecord::from)
+            .collect();
+        print_output(&format.render_tasks(&records));
+    }
+    Ok(())
+}
+
 fn get_user_input(prompt: &str) -> Result<String> {
     print!("{}", prompt);
-    io::stdout().flush().map_err(|e| error::TodoError::IoError(e))?;
+    io::stdout().flush().map_err(TodoError::IoError)?;
     
     let mut input = String::new();
-    io::stdin().read_line(&mut input).map_err(|e| error::TodoError::IoError(e))?;
+    io::stdin().read_line(&mut input).map_err(TodoError::IoError)?;
     
     Ok(input.trim().to_string())
 }
 
-fn add_task(task_list: &mut TaskList) -> Result<()> {
-    let description = get_user_input("请输入任务描述: ")?;
-    
-    if description.is_empty() {
-        println!("❌ 任务描述不能为空");
-        return Ok(());
+// 可同时作用于多个任务的操作
+enum BulkOp {
+    Complete,
+    Suspend,
+    Delete,
+    Tag(String),
+    Untag(String),
+}
+
+impl BulkOp {
+    fn apply(&self, task_list: &mut TaskList, ids: &[usize]) -> Result<()> {
+        match self {
+            BulkOp::Complete => task_list.complete_tasks(ids),
+            BulkOp::Suspend => task_list.suspend_tasks(ids),
+            BulkOp::Delete => task_list.delete_tasks(ids),
+            BulkOp::Tag(tag) => task_list.tag_tasks(ids, tag),
+            BulkOp::Untag(tag) => task_list.untag_tasks(ids, tag),
+        }
     }
+
+    fn done_message(&self, ids: &[usize]) -> String {
+        let ids = ids.iter().map(|id| format!("#{}", id)).collect::<Vec<_>>().join(" ");
+        match self {
+            BulkOp::Complete => format!("✅ 任务 {} 已完成！", ids),
+            BulkOp::Suspend => format!("⏸️  任务 {} 已挂起", ids),
+            BulkOp::Delete => format!("🗑️  任务 {} 已删除", ids),
+            BulkOp::Tag(tag) => format!("🏷  任务 {} 已添加标签 {}", ids, tag),
+            BulkOp::Untag(tag) => format!("🏷  任务 {} 已移除标签 {}", ids, tag),
+        }
+    }
+}
+
+// add <描述...> [#标签...]，以 # 开头的词作为标签
+fn add_with_args(task_list: &mut TaskList, args: &[String]) -> Result<usize> {
+    let (tags, words): (Vec<&String>, Vec<&String>) = args.iter().partition(|arg| arg.len() > 1 && arg.starts_with('#'));
+    let new_task = NewTask {
+        description: words.iter().map(|word| word.as_str()).collect::<Vec<_>>().join(" "),
+        tags: tags.iter().map(|tag| tag[1..].to_string()).collect(),
+        status: None,
+        estimate: None,
+        due: None,
+        priority: None,
+        repeat: None,
+        notes: None,
+    };
+    new_task.add_to(task_list)
+}
+
+// done|suspend|delete <选择器...>，tag|untag <标签> <选择器...>
+// 返回被修改的任务，删除时为删除前的状态
+fn bulk_with_args(task_list: &mut TaskList, op: BulkOp, args: &[String], format: OutputFormat) -> Result<Vec<TaskRecord>> {
+    let tokens: Vec<&str> = args.iter().map(String::as_str).collect();
+    let ids = select_tasks(task_list, &tokens)?;
+    let deleted = task_records(task_list, &ids);
+    op.apply(task_list, &ids)?;
+    if format.is_text() {
+        println!("{}", op.done_message(&ids));
+    }
+    Ok(match op {
+        BulkOp::Delete => deleted,
+        _ => task_records(task_list, &ids),
+    })
+}
+
+// 解析任务选择器：单个ID、ID范围 (7-10，只取存在的任务) 或带前缀的查询条件，结果去重并保持顺序；
+// 不带前缀的词视为错误，避免输错时批量修改大量任务
+fn select_tasks(task_list: &TaskList, tokens: &[&str]) -> Result<Vec<usize>> {
+    let mut ids = Vec::new();
+    let mut query = TaskQuery::default();
     
-    let id = task_list.add_task(description.clone());
-    println!("✅ 已添加任务 #{}: {}", id, description);
+    for token in tokens {
+        if let Ok(id) = token.parse::<usize>() {
+            ids.push(id);
+        } else if let Some(uuid) = uuid_selector(token) {
+            ids.push(task_list.resolve_uuid(uuid)?);
+        } else if let Some((start, end)) = parse_range(token) {
+            if start > end {
+                return Err(TodoError::InvalidInput(format!("无效的ID范围: {}", token)));
+            }
+            ids.extend(task_list.find_ids(|task| (start..=end).contains(&task.id())));
+        } else {
+            query.push(QueryTerm::parse_prefixed(token)?);
+        }
+    }
+    if !query.is_empty() {
+        ids.extend(task_list.find_ids(|task| query.matches(task)));
+    }
     
-    Ok(())
+    let mut seen = std::collections::HashSet::new();
+    ids.retain(|id| seen.insert(*id));
+    if ids.is_empty() {
+        return Err(TodoError::InvalidInput("没有匹配的任务".to_string()));
+    }
+    Ok(ids)
+}
+
+fn parse_range(token: &str) -> Option<(usize, usize)> {
+    let (start, end) = token.split_once('-')?;
+    Some((start.parse().ok()?, end.parse().ok()?))
 }
 
-fn suspend_task(task_list: &mut TaskList) -> Result<()> {
-    let id_str = get_user_input("请输入要挂起的任务ID: ")?;
+// edit <id> [--status <状态>] [--tags <标签,...>] [--estimate <预估>] [--due <日期>] [--priority <优先级>] [--repeat <周期>] [--notes <备注>] [新描述...]，
+// 未给出任何字段时逐项询问
+fn edit_task_with_args(task_list: &mut TaskList, args: &[String], format: OutputFormat) -> Result<usize> {
+    let id = parse_id_arg(task_list, args.first(), "用法: edit <id> [--status <状态>] [--tags <标签,...>] [--estimate <预估>] [--due <日期>] [--priority <优先级>] [--repeat <周期>] [--notes <备注>] [新描述]")?;
     
-    let id: usize = match id_str.parse() {
-        Ok(id) => id,
-        Err(_) => {
-            println!("❌ 请输入有效的数字ID");
-            return Ok(());
+    let mut update = TaskUpdate::default();
+    let mut words = Vec::new();
+    let mut rest = args[1..].iter();
+    while let Some(arg) = rest.next() {
+        if arg == "--status" {
+            let value = rest.next()
+                .ok_or_else(|| TodoError::InvalidInput("--status 需要一个值".to_string()))?;
+            update.status = Some(value.parse()?);
+        } else if arg == "--estimate" {
+            let value = rest.next()
+                .ok_or_else(|| TodoError::InvalidInput("--estimate 需要一个值".to_string()))?;
+            update.estimate = Some(parse_estimate(value)?);
+        } else if arg == "--due" {
+            let value = rest.next()
+                .ok_or_else(|| TodoError::InvalidInput("--due 需要一个值".to_string()))?;
+            update.due = Some(parse_due(value)?);
+        } else if arg == "--priority" {
+            let value = rest.next()
+                .ok_or_else(|| TodoError::InvalidInput("--priority 需要一个值".to_string()))?;
+            update.priority = Some(parse_priority(value)?);
+        } else if arg == "--repeat" {
+            let value = rest.next()
+                .ok_or_else(|| TodoError::InvalidInput("--repeat 需要一个值".to_string()))?;
+            update.recurrence = Some(parse_recurrence(value)?);
+        } else if arg == "--notes" {
+            let value = rest.next()
+                .ok_or_else(|| TodoError::InvalidInput("--notes 需要一个值".to_string()))?;
+            update.notes = Some(parse_notes(value));
+        } else if arg == "--tags" {
+            let value = rest.next()
+                .ok_or_else(|| TodoError::InvalidInput("--tags 需要一个值".to_string()))?;
+            update.tags = Some(split_tags(value));
+        } else {
+            words.push(arg.as_str());
         }
-    };
+    }
+    if !words.is_empty() {
+        update.description = Some(words.join(" "));
+    }
     
-    if task_list.suspend_task(id) {
-        println!("⏸️  任务 #{} 已挂起", id);
-    } else {
-        println!("❌ 未找到ID为 {} 的任务", id);
+    if update.is_empty() {
+        // 脚本调用时不进入交互输入
+        if !format.is_text() {
+            return Err(TodoError::InvalidInput("没有要修改的内容".to_string()));
+        }
+        update = prompt_task_update(task_list, id)?;
+        if update.is_empty() {
+            println!("ℹ️  未做任何修改");
+            return Ok(id);
+        }
     }
     
+    task_list.update_task(id, update)?;
+    if format.is_text() {
+        println!("✏️  任务 #{} 已更新", id);
+    }
+    Ok(id)
+}
+
+// move <id> before|after <目标id>，move <id> top|bottom
+fn move_task_with_args(task_list: &mut TaskList, args: &[String], format: OutputFormat) -> Result<usize> {
+    let usage = "用法: move <id> before|after <id> 或 move <id> top|bottom";
+    let parse_id = |arg: Option<&String>| parse_id_arg(task_list, arg, usage);
+    
+    let id = parse_id(args.first())?;
+    let placement = match args.get(1).map(String::as_str) {
+        Some("top") => Placement::Top,
+        Some("bottom") => Placement::Bottom,
+        Some("before") => Placement::Before(parse_id(args.get(2))?),
+        Some("after") => Placement::After(parse_id(args.get(2))?),
+        _ => return Err(TodoError::InvalidInput(usage.to_string())),
+    };
+    
+    task_list.move_task(id, placement)?;
+    if format.is_text() {
+        println!("↕️  任务 #{} 已移动", id);
+    }
+    Ok(id)
+}
+
+// 数字ID，或 uuid:<前缀> / 完整 UUID
+fn parse_id_arg(task_list: &TaskList, arg: Option<&String>, usage: &str) -> Result<usize> {
+    let arg = arg.ok_or_else(|| TodoError::InvalidInput(usage.to_string()))?;
+    match uuid_selector(arg) {
+        Some(uuid) => task_list.resolve_uuid(uuid),
+        None => arg.parse().map_err(|_| TodoError::InvalidInput("请输入有效的数字ID".to_string())),
+    }
+}
+
+fn uuid_selector(token: &str) -> Option<&str> {
+    token.strip_prefix("uuid:").or_else(|| uuid::Uuid::parse_str(token).is_ok().then_some(token))
+}
+
+fn start_timer_for(task_list: &mut TaskList, id: usize) -> Result<()> {
+    if let Some(paused) = task_list.start_timer(id)? {
+        println!("⏸️  任务 #{} 的计时已暂停", paused);
+    }
+    println!("⏱  任务 #{} 开始计时", id);
     Ok(())
 }
 
-fn complete_task(task_list: &mut TaskList) -> Result<()> {
-    let id_str = get_user_input("请输入要完成的任务ID: ")?;
+fn stop_timer(task_list: &mut TaskList) {
+    match task_list.stop_timer() {
+        Some(id) => {
+            let total = task_list.get_task(id).map_or(0, |task| task.tracked_seconds(Utc::now()));
+            println!("⏹️  任务 #{} 已停止计时，累计 {}", id, time_tracking::format_duration(total));
+        }
+        None => println!("ℹ️  当前没有正在计时的任务"),
+    }
+}
+
+// time [id]：按任务和按天汇总用时，给出ID时列出该任务的每段计时
+fn show_time_report(task_list: &TaskList, args: &[String]) -> Result<()> {
+    let now = Utc::now();
     
-    let id: usize = match id_str.parse() {
-        Ok(id) => id,
-        Err(_) => {
-            println!("❌ 请输入有效的数字ID");
-            return Ok(());
+    if let Some(arg) = args.first() {
+        let id = parse_id_arg(task_list, Some(arg), "用法: time [id]")?;
+        let task = task_list.get_task(id).ok_or(TodoError::TaskNotFound(id))?;
+        println!("⏱ 任务 #{} {} 的计时记录:", id, task.description());
+        for session in task.sessions() {
+            println!("  {} → {}  {}",
+                session.start,
+                session.end.as_deref().unwrap_or("进行中"),
+                time_tracking::format_duration(session.seconds(now)));
         }
-    };
+        println!("📊 累计: {}", time_tracking::format_duration(task.tracked_seconds(now)));
+        return Ok(());
+    }
     
-    if task_list.complete_task(id) {
-        println!("✅ 任务 #{} 已完成！", id);
-    } else {
-        println!("❌ 未找到ID为 {} 的任务", id);
+    println!("⏱ 按任务统计:");
+    let mut total = 0;
+    for task in task_list.get_tasks() {
+        let seconds = task.tracked_seconds(now);
+        if seconds > 0 {
+            println!("  #{} {}  {}", task.id(), task.description(), time_tracking::format_duration(seconds));
+            total += seconds;
+        }
+    }
+    if total == 0 {
+        println!("  (暂无计时记录)");
     }
     
+    println!("📅 最近 7 天:");
+    let today = time_tracking::today();
+    for offset in (0..7).rev() {
+        let day = today - chrono::Duration::days(offset);
+        let seconds = task_list.tracked_seconds_on(day, now);
+        println!("  {}  {}", day, time_tracking::format_duration(seconds));
+    }
+    println!("📊 累计: {}", time_tracking::format_duration(total));
     Ok(())
 }
 
-fn delete_task(task_list: &mut TaskList) -> Result<()> {
-    let id_str = get_user_input("请输入要删除的任务ID: ")?;
+fn show_statistics(task_list: &TaskList) {
+    let stats = Statistics::compute(task_list.get_tasks(), Utc::now());
     
-    let id: usize = match id_str.parse() {
-        Ok(id) => id,
-        Err(_) => {
-            println!("❌ 请输入有效的数字ID");
-            return Ok(());
+    println!("📊 任务统计");
+    println!("  总计 {} | 待处理 {} | 已挂起 {} | 已完成 {}",
+        stats.total, stats.pending, stats.suspended, stats.completed);
+    println!("  已过期: {}", stats.overdue);
+    println!("  完成率: {:.1}%", stats.completion_rate * 100.0);
+    println!("  平均完成耗时: {}",
+        stats.average_lead_time.map_or("-".to_string(), time_tracking::format_duration));
+    println!("  连续完成: 当前 {} 天，最长 {} 天", stats.current_streak, stats.longest_streak);
+    println!("  番茄: {} 个 | 计时: {}", stats.pomodoros, time_tracking::format_duration(stats.tracked_seconds));
+    
+    println!("📅 最近 7 天完成:");
+    for (day, count) in &stats.daily_throughput {
+        println!("  {}  {:>3} {}", day, count, "█".repeat(*count));
+    }
+    println!("🗓  最近 4 周完成:");
+    for (week, count) in &stats.weekly_throughput {
+        println!("  {} 起  {:>3} {}", week, count, "█".repeat(*count));
+    }
+}
+
+// report [--today|--week|--last-week] [--from 日期] [--to 日期] [--format md|text]，默认本周
+fn report_with_args(task_list: &TaskList, args: &[String]) -> Result<()> {
+    let mut range = ReportRange::this_week();
+    let mut format = ReportFormat::Markdown;
+    let mut rest = args.iter();
+    while let Some(arg) = rest.next() {
+        let mut value = || rest.next()
+            .ok_or_else(|| TodoError::InvalidInput(format!("{} 需要一个值", arg)));
+        match arg.as_str() {
+            "--today" => range = ReportRange::today(),
+            "--week" => range = ReportRange::this_week(),
+            "--last-week" => range = ReportRange::last_week(),
+            "--from" => range.from = report::parse_date(value()?)?,
+            "--to" => range.to = report::parse_date(value()?)?,
+            "--format" => format = value()?.parse()?,
+            other => return Err(TodoError::InvalidInput(format!("未知参数: {}", other))),
         }
-    };
+    }
+    if range.from > range.to {
+        return Err(TodoError::InvalidInput("起始日期不能晚于结束日期".to_string()));
+    }
     
-    if task_list.delete_task(id) {
-        println!("🗑️  任务 #{} 已删除", id);
+    print!("{}", report::generate(task_list.get_tasks(), range, format, Utc::now()));
+    Ok(())
+}
+
+// export [--format csv|md|todo.txt] [--output 文件] [查询条件...]
+// 未指定格式时按输出文件扩展名推断，默认 CSV；未指定输出文件时写到标准输出
+fn export_with_args(task_list: &TaskList, args: &[String]) -> Result<()> {
+    let mut format = None;
+    let mut output = None;
+    let mut query = TaskQuery::default();
+    let mut rest = args.iter();
+    while let Some(arg) = rest.next() {
+        let mut value = || rest.next()
+            .ok_or_else(|| TodoError::InvalidInput(format!("{} 需要一个值", arg)));
+        match arg.as_str() {
+            "--format" => format = Some(value()?.parse::<ExportFormat>()?),
+            "--output" | "-o" => output = Some(std::path::PathBuf::from(value()?)),
+            term => query.push(term.parse()?),
+        }
+    }
+    let format = format
+        .or_else(|| output.as_deref().and_then(ExportFormat::from_path))
+        .unwrap_or(ExportFormat::Csv);
+    
+    let tasks: Vec<_> = task_list.get_tasks().iter().filter(|t| query.matches(t)).collect();
+    let content = export::export(tasks.iter().copied(), format)?;
+    match output {
+        Some(path) => {
+            std::fs::write(&path, content)?;
+            println!("📤 已导出 {} 个任务到 {} ({})", tasks.len(), path.display(), format.label());
+        }
+        None => print!("{}", content),
+    }
+    Ok(())
+}
+
+// 与副本合并后双方内容一致：合并结果同时写回副本
+fn sync_with_replica(task_list: &mut TaskList, path: &Path) -> Result<()> {
+    let file = if path.is_dir() { path.join(storage::STORAGE_FILE) } else { path.to_path_buf() };
+    let replica = Storage::load_from(&file)?;
+    let report = task_list.merge(&replica);
+    Storage::save_to(task_list, &file)?;
+    println!("🔄 已与 {} 合并: 新增 {} 个，更新 {} 个，删除 {} 个",
+        file.display(), report.added, report.updated, report.removed);
+    Ok(())
+}
+
+// 任务文件的提交历史: log [-n 数量]
+fn show_history(args: &[String]) -> Result<()> {
+    let mut limit = 20;
+    let mut rest = args.iter();
+    while let Some(arg) = rest.next() {
+        match arg.as_str() {
+            "-n" => {
+                limit = rest.next()
+                    .and_then(|n| n.parse().ok())
+                    .ok_or_else(|| TodoError::InvalidInput("-n 需要一个数字".to_string()))?;
+            }
+            other => return Err(TodoError::InvalidInput(format!("未知参数: {}", other))),
+        }
+    }
+
+    let entries = git::log(storage::STORAGE_FILE, limit)?;
+    if entries.is_empty() {
+        println!("  (暂无提交记录)");
+    }
+    for entry in entries {
+        let mut lines = entry.message.lines();
+        println!("📝 {} {} {}", entry.hash, entry.date, lines.next().unwrap_or(""));
+        for line in lines.filter(|l| !l.trim().is_empty()) {
+            println!("      {}", line);
+        }
+    }
+    Ok(())
+}
+
+// 从文件导入任务，返回是否修改了任务列表
+fn import_with_args(task_list: &mut TaskList, args: &[String]) -> Result<bool> {
+    let mut path = None;
+    let mut format = None;
+    let mut dry_run = false;
+    let mut allow_duplicates = false;
+    let mut mapping = ColumnMapping::new();
+    let mut rest = args.iter();
+    while let Some(arg) = rest.next() {
+        let mut value = || rest.next()
+            .ok_or_else(|| TodoError::InvalidInput(format!("{} 需要一个值", arg)));
+        match arg.as_str() {
+            "--format" => format = Some(value()?.parse::<ImportFormat>()?),
+            "--dry-run" | "-n" => dry_run = true,
+            "--allow-duplicates" => allow_duplicates = true,
+            // --map description=Title,tags=Labels
+            "--map" => {
+                for pair in value()?.split(',') {
+                    let (field, column) = pair.split_once('=')
+                        .ok_or_else(|| TodoError::InvalidInput(format!("无效的列映射: {}", pair)))?;
+                    mapping.insert(field.trim().to_string(), column.trim().to_string());
+                }
+            }
+            file => path = Some(std::path::PathBuf::from(file)),
+        }
+    }
+    let usage = "用法: import <文件> [--format todo.txt|csv|md|json] [--dry-run] [--allow-duplicates] [--map 字段=列名,...]";
+    let path = path.ok_or_else(|| TodoError::InvalidInput(usage.to_string()))?;
+    let format = format
+        .or_else(|| ImportFormat::from_path(&path))
+        .ok_or_else(|| TodoError::InvalidInput("无法根据扩展名判断格式，请使用 --format".to_string()))?;
+
+    let content = std::fs::read_to_string(&path)?;
+    let plan = ImportPlan::new(task_list, import::parse(&content, format, &mapping)?, allow_duplicates);
+
+    println!("📥 {} 中共 {} 个任务:", path.display(), plan.tasks.len() + plan.duplicates.len());
+    for task in &plan.tasks {
+        let tags: String = task.tags.iter().map(|t| format!(" #{}", t)).collect();
+        println!("  + [{}] {}{}", export::status_name(&task.status), task.description, tags);
+    }
+    for (task, existing) in &plan.duplicates {
+        match existing {
+            Some(id) => println!("  = {} (与任务 #{} 重复，跳过)", task.description, id),
+            None => println!("  = {} (文件中重复，跳过)", task.description),
+        }
+    }
+
+    if dry_run {
+        println!("🔍 预览模式，未导入任何任务");
+        return Ok(false);
+    }
+    let skipped = plan.duplicates.len();
+    let ids = plan.apply(task_list);
+    println!("✅ 已导入 {} 个任务，跳过 {} 个重复任务", ids.len(), skipped);
+    Ok(!ids.is_empty())
+}
+
+// 在 127.0.0.1 上提供 REST 接口，令牌可用 --token 或环境变量指定，否则随机生成
+fn serve_with_args(args: &[String]) -> Result<()> {
+    let mut port = server::DEFAULT_PORT;
+    let mut token = std::env::var(server::TOKEN_ENV).ok().filter(|t| !t.is_empty());
+    let mut rest = args.iter();
+    while let Some(arg) = rest.next() {
+        let mut value = || rest.next()
+            .ok_or_else(|| TodoError::InvalidInput(format!("{} 需要一个值", arg)));
+        match arg.as_str() {
+            "--port" | "-p" => {
+                let value = value()?;
+                port = value.parse()
+                    .map_err(|_| TodoError::InvalidInput(format!("无效的端口: {}", value)))?;
+            }
+            "--token" => token = Some(value()?.clone()),
+            other => return Err(TodoError::InvalidInput(format!("未知参数: {}", other))),
+        }
+    }
+    let generated = token.is_none();
+    let token = token.unwrap_or_else(|| uuid::Uuid::new_v4().simple().to_string());
+
+    println!("🌐 REST 接口运行在 http://127.0.0.1:{}/ (Ctrl+C 退出)", port);
+    println!("📄 接口说明: http://127.0.0.1:{}/openapi.json", port);
+    if generated {
+        println!("🖥️  网页界面: http://127.0.0.1:{}/#token={}", port, token);
+        println!("🔑 访问令牌: {}", token);
+        println!("   请求时添加 Authorization: Bearer <令牌>，也可以通过环境变量 {} 指定令牌", server::TOKEN_ENV);
+    } else {
+        println!("🖥️  网页界面: http://127.0.0.1:{}/ (首次打开时输入令牌)", port);
+    }
+    server::run(&ServerOptions { port, token })
+}
+
+// 与 CalDAV 任务集合双向同步，--url / --user 会保存到 caldav.json
+fn caldav_sync_with_args(task_list: &mut TaskList, args: &[String]) -> Result<()> {
+    let mut config = CalDavConfig::load()?.unwrap_or_default();
+    let mut policy = ConflictPolicy::default();
+    let mut config_changed = false;
+    let mut rest = args.iter();
+    while let Some(arg) = rest.next() {
+        let mut value = || rest.next()
+            .ok_or_else(|| TodoError::InvalidInput(format!("{} 需要一个值", arg)));
+        match arg.as_str() {
+            "--url" => {
+                config.url = value()?.clone();
+                config_changed = true;
+            }
+            "--user" => {
+                config.username = Some(value()?.clone());
+                config_changed = true;
+            }
+            "--password" => config.password = Some(value()?.clone()),
+            "--prefer" => policy = value()?.parse()?,
+            other => return Err(TodoError::InvalidInput(format!("未知参数: {}", other))),
+        }
+    }
+    if config.password.is_none() {
+        config.password = std::env::var(caldav::PASSWORD_ENV).ok();
+    }
+    if config.url.is_empty() {
+        return Err(TodoError::InvalidInput(format!(
+            "用法: caldav --url <任务集合地址> [--user 用户名] [--password 密码] [--prefer local|remote]，密码也可以通过环境变量 {} 提供",
+            caldav::PASSWORD_ENV
+        )));
+    }
+    if config_changed {
+        config.save()?;
+    }
+
+    let client = CalDavClient::new(&config)?;
+    let mut state = SyncState::load(&config.url)?;
+    let report = caldav::sync(task_list, &client, &mut state, policy)?;
+    state.save()?;
+
+    println!("🔄 已与 {} 同步", config.url);
+    println!("  ⬆️  推送修改 {} 个，新建 {} 个，删除 {} 个", report.pushed, report.created_remote, report.deleted_remote);
+    println!("  ⬇️  拉取修改 {} 个，新建 {} 个，删除 {} 个", report.pulled, report.created_local, report.deleted_local);
+    if report.matched > 0 {
+        println!("  🔗 按 UID 对应已有任务 {} 个", report.matched);
+    }
+    if !report.conflicts.is_empty() {
+        println!("⚠️  {} 个冲突未处理 (可使用 --prefer local 或 --prefer remote):", report.conflicts.len());
+        for conflict in &report.conflicts {
+            match conflict.task_id {
+                Some(id) => println!("  #{} {}: {}", id, conflict.uid, conflict.reason),
+                None => println!("  {}: {}", conflict.uid, conflict.reason),
+            }
+        }
+    }
+    Ok(())
+}
+
+// "-" 表示清除预估
+fn parse_estimate(value: &str) -> Result<Option<Estimate>> {
+    if value.trim() == "-" {
+        Ok(None)
     } else {
-        println!("❌ 未找到ID为 {} 的任务", id);
+        value.parse().map(Some)
+    }
+}
+
+// 截止日期、优先级和重复周期同样用 - 清除
+fn parse_due(value: &str) -> Result<Option<NaiveDate>> {
+    if value.trim() == "-" {
+        Ok(None)
+    } else {
+        task::parse_due(value).map(Some)
+    }
+}
+
+fn parse_priority(value: &str) -> Result<Option<Priority>> {
+    if value.trim() == "-" {
+        Ok(None)
+    } else {
+        value.parse().map(Some)
+    }
+}
+
+// "-" 清除备注
+fn parse_notes(value: &str) -> String {
+    if value == "-" { String::new() } else { value.to_string() }
+}
+
+fn parse_recurrence(value: &str) -> Result<Option<Recurrence>> {
+    if value.trim() == "-" {
+        Ok(None)
+    } else {
+        value.parse().map(Some)
+    }
+}
+
+// 剩余预估工作量，以及预估与实际用时的对比
+fn show_estimate_report(task_list: &TaskList) -> Result<()> {
+    let tasks = task_list.get_tasks();
+    let now = Utc::now();
+    
+    println!("⌛ 剩余预估: {}", estimate::remaining_totals(tasks));
+    for (tag, totals) in estimate::remaining_by_tag(tasks) {
+        println!("  🏷 {}  {} ({} 个任务)", tag, totals, totals.tasks);
+    }
+    
+    println!("\n📐 预估与实际:");
+    let comparisons = estimate::compare(tasks, now);
+    if comparisons.is_empty() {
+        println!("  (暂无同时有预估和计时记录的任务)");
+        return Ok(());
+    }
+    
+    let (mut estimated_minutes, mut actual_seconds) = (0u32, 0i64);
+    let (mut points, mut point_seconds) = (0.0, 0i64);
+    for c in &comparisons {
+        let ratio = c.ratio().map(|r| format!("{:.0}%", r * 100.0)).unwrap_or_else(|| "-".to_string());
+        println!("  #{} {}  预估 {}  实际 {}  {}",
+            c.task.id(), c.task.description(), c.estimate,
+            time_tracking::format_duration(c.actual_seconds), ratio);
+        // 只有已完成的任务参与校准
+        if !c.task.is_completed() {
+            continue;
+        }
+        match c.estimate {
+            Estimate::Minutes(minutes) => {
+                estimated_minutes = estimated_minutes.saturating_add(minutes);
+                actual_seconds += c.actual_seconds;
+            }
+            Estimate::Points(p) => {
+                points += p;
+                point_seconds += c.actual_seconds;
+            }
+        }
     }
     
+    if estimated_minutes > 0 {
+        println!("📊 已完成任务: 预估 {}，实际 {}，实际/预估 {:.0}%",
+            Estimate::Minutes(estimated_minutes),
+            time_tracking::format_duration(actual_seconds),
+            actual_seconds as f64 / (f64::from(estimated_minutes) * 60.0) * 100.0);
+    }
+    if points > 0.0 {
+        println!("📊 已完成任务: 平均每点用时 {}", time_tracking::format_duration((point_seconds as f64 / points) as i64));
+    }
     Ok(())
 }
+
+fn prompt_task_update(task_list: &TaskList, id: usize) -> Result<TaskUpdate> {
+    let task = task_list.get_task(id).ok_or(TodoError::TaskNotFound(id))?;
+    println!("当前: {}", task);
+    
+    let mut update = TaskUpdate::default();
+    
+    let description = get_user_input("新描述 (回车保持不变): ")?;
+    if !description.is_empty() {
+        update.description = Some(description);
+    }
+    
+    let status = get_user_input("新状态 pending/suspended/completed (回车保持不变): ")?;
+    if !status.is_empty() {
+        update.status = Some(status.parse::<TaskStatus>()?);
+    }
+    
+    let tags = get_user_input("新标签，逗号分隔，输入 - 清空 (回车保持不变): ")?;
+    if tags == "-" {
+        update.tags = Some(Vec::new());
+    } else if !tags.is_empty() {
+        update.tags = Some(split_tags(&tags));
+    }
+    
+    let estimate = get_user_input("预估，如 30m、2h、3pt，输入 - 清除 (回车保持不变): ")?;
+    if !estimate.is_empty() {
+        update.estimate = Some(parse_estimate(&estimate)?);
+    }
+    
+    let due = get_user_input("截止日期，如 2025-07-02、today、+3，输入 - 清除 (回车保持不变): ")?;
+    if !due.is_empty() {
+        update.due = Some(parse_due(&due)?);
+    }
+    
+    let priority = get_user_input("优先级 high/medium/low，输入 - 清除 (回车保持不变): ")?;
+    if !priority.is_empty() {
+        update.priority = Some(parse_priority(&priority)?);
+    }
+    
+    let recurrence = get_user_input("重复周期，如 daily、weekly、2w、1m，输入 - 取消重复 (回车保持不变): ")?;
+    if !recurrence.is_empty() {
+        update.recurrence = Some(parse_recurrence(&recurrence)?);
+    }
+    
+    let notes = get_user_input("备注，输入 - 清除 (回车保持不变): ")?;
+    if !notes.is_empty() {
+        update.notes = Some(parse_notes(&notes));
+    }
+    
+    Ok(update)
+}
+
+fn split_tags(value: &str) -> Vec<String> {
+    value.split([',', '，'])
+        .map(str::trim)
+        .filter(|tag| !tag.is_empty())
+        .map(str::to_string)
+        .collect()
+}
@@ -0,0 +1,153 @@
+use std::time::{Duration, Instant};
+use serde::{Deserialize, Serialize};
+
+// 番茄钟时长设置（分钟）
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct PomodoroSettings {
+    pub work_minutes: u32,
+    pub short_break_minutes: u32,
+    pub long_break_minutes: u32,
+    // 每完成几个番茄进入一次长休息
+    pub long_break_every: u32,
+}
+
+impl Default for PomodoroSettings {
+    fn default() -> Self {
+        PomodoroSettings {
+            work_minutes: 25,
+            short_break_minutes: 5,
+            long_break_minutes: 15,
+            long_break_every: 4,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PomodoroPhase {
+    Work,
+    ShortBreak,
+    LongBreak,
+}
+
+impl PomodoroPhase {
+    pub fn label(&self) -> &'static str {
+        match self {
+            PomodoroPhase::Work => "专注",
+            PomodoroPhase::ShortBreak => "短休息",
+            PomodoroPhase::LongBreak => "长休息",
+        }
+    }
+}
+
+// 一个阶段结束时产生的事件
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PomodoroEvent {
+    // 完成一个番茄，附带绑定的任务
+    WorkFinished(Option<usize>),
+    BreakFinished,
+}
+
+// 番茄钟状态机：阶段结束后切换到下一阶段并暂停，等待用户开始
+#[derive(Debug, Clone)]
+pub struct Pomodoro {
+    pub settings: PomodoroSettings,
+    pub task_id: Option<usize>,
+    phase: PomodoroPhase,
+    // 暂停时剩余的时间；计时中为开始时刻的剩余时间
+    remaining: Duration,
+    running_since: Option<Instant>,
+    finished_in_cycle: u32,
+}
+
+impl Pomodoro {
+    pub fn new(settings: PomodoroSettings) -> Self {
+        Pomodoro {
+            settings,
+            task_id: None,
+            phase: PomodoroPhase::Work,
+            remaining: minutes(settings.work_minutes),
+            running_since: None,
+            finished_in_cycle: 0,
+        }
+    }
+
+    pub fn phase(&self) -> PomodoroPhase {
+        self.phase
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.running_since.is_some()
+    }
+
+    pub fn remaining(&self, now: Instant) -> Duration {
+        match self.running_since {
+            Some(since) => self.remaining.saturating_sub(now - since),
+            None => self.remaining,
+        }
+    }
+
+    pub fn start(&mut self, now: Instant) {
+        if self.running_since.is_none() {
+            self.running_since = Some(now);
+        }
+    }
+
+    pub fn pause(&mut self, now: Instant) {
+        self.remaining = self.remaining(now);
+        self.running_since = None;
+    }
+
+    // 回到当前阶段的完整时长，修改设置后也用它生效
+    pub fn reset(&mut self) {
+        self.running_since = None;
+        self.remaining = self.phase_length(self.phase);
+    }
+
+    // 跳过当前阶段，不计入完成的番茄
+    pub fn skip(&mut self) {
+        self.advance(false);
+    }
+
+    // 每帧调用，阶段结束时返回事件
+    pub fn tick(&mut self, now: Instant) -> Option<PomodoroEvent> {
+        if !self.is_running() || !self.remaining(now).is_zero() {
+            return None;
+        }
+        let event = match self.phase {
+            PomodoroPhase::Work => PomodoroEvent::WorkFinished(self.task_id),
+            _ => PomodoroEvent::BreakFinished,
+        };
+        self.advance(true);
+        Some(event)
+    }
+
+    fn advance(&mut self, finished: bool) {
+        self.phase = match self.phase {
+            PomodoroPhase::Work => {
+                if finished {
+                    self.finished_in_cycle += 1;
+                }
+                if self.finished_in_cycle >= self.settings.long_break_every.max(1) {
+                    self.finished_in_cycle = 0;
+                    PomodoroPhase::LongBreak
+                } else {
+                    PomodoroPhase::ShortBreak
+                }
+            }
+            PomodoroPhase::ShortBreak | PomodoroPhase::LongBreak => PomodoroPhase::Work,
+        };
+        self.reset();
+    }
+
+    fn phase_length(&self, phase: PomodoroPhase) -> Duration {
+        match phase {
+            PomodoroPhase::Work => minutes(self.settings.work_minutes),
+            PomodoroPhase::ShortBreak => minutes(self.settings.short_break_minutes),
+            PomodoroPhase::LongBreak => minutes(self.settings.long_break_minutes),
+        }
+    }
+}
+
+fn minutes(n: u32) -> Duration {
+    Duration::from_secs(u64::from(n.max(1)) * 60)
+}
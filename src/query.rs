@@ -0,0 +1,118 @@
+use std::str::FromStr;
+use crate::error::TodoError;
+use crate::task::{Task, TaskStatus};
+
+// 查询条件，多个条件之间为"且"关系
+#[derive(Debug, Clone, PartialEq)]
+pub enum QueryTerm {
+    Status(TaskStatus),
+    Tag(String),
+    Text(String),
+}
+
+// 简单查询语法: status:<状态> tag:<标签> text:<关键词>，不带前缀的词按关键词匹配
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TaskQuery {
+    terms: Vec<QueryTerm>,
+}
+
+impl TaskQuery {
+    pub fn is_empty(&self) -> bool {
+        self.terms.is_empty()
+    }
+
+    pub fn push(&mut self, term: QueryTerm) {
+        self.terms.push(term);
+    }
+
+    pub fn matches(&self, task: &Task) -> bool {
+        self.terms.iter().all(|term| match term {
+            QueryTerm::Status(status) => task.status() == status,
+            QueryTerm::Tag(tag) => task.has_tag(tag),
+            QueryTerm::Text(text) => task.description().to_lowercase().contains(&text.to_lowercase()),
+        })
+    }
+}
+
+impl QueryTerm {
+    // 只接受带前缀的条件，批量修改时输错的ID或单词不会被当作关键词匹配到大量任务
+    pub fn parse_prefixed(s: &str) -> Result<Self, TodoError> {
+        match s.split_once(':') {
+            Some((key, _)) if ["status", "is", "tag", "text"].contains(&key) => s.parse(),
+            _ => Err(TodoError::InvalidInput(format!(
+                "无法识别的任务选择: {}，请使用ID、范围 (如 3-5)、uuid: 或 tag:/status:/text: 条件", s))),
+        }
+    }
+}
+
+impl FromStr for QueryTerm {
+    type Err = TodoError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.split_once(':') {
+            Some(("status", value)) | Some(("is", value)) => Ok(QueryTerm::Status(value.parse()?)),
+            Some(("tag", value)) if !value.is_empty() => Ok(QueryTerm::Tag(value.to_string())),
+            Some(("text", value)) if !value.is_empty() => Ok(QueryTerm::Text(value.to_string())),
+            Some((key, _)) if ["tag", "text"].contains(&key) => {
+                Err(TodoError::InvalidInput(format!("查询条件 {} 缺少值", key)))
+            }
+            _ => Ok(QueryTerm::Text(s.to_string())),
+        }
+    }
+}
+
+impl FromStr for TaskQuery {
+    type Err = TodoError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let terms = s.split_whitespace()
+            .map(str::parse)
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(TaskQuery { terms })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn task(description: &str, tags: &[&str], status: TaskStatus) -> Task {
+        let mut task = Task::new(1, description.to_string());
+        task.set_tags(tags.iter().map(|t| t.to_string()).collect());
+        task.set_status(status);
+        task
+    }
+
+    #[test]
+    fn parses_prefixed_and_bare_terms() {
+        assert!("status:done".parse::<QueryTerm>().is_err());
+        assert_eq!("is:c".parse::<QueryTerm>().unwrap(), QueryTerm::Status(TaskStatus::Completed));
+        assert_eq!("tag:工作".parse::<QueryTerm>().unwrap(), QueryTerm::Tag("工作".to_string()));
+        assert_eq!("text:报告".parse::<QueryTerm>().unwrap(), QueryTerm::Text("报告".to_string()));
+        assert_eq!("报告".parse::<QueryTerm>().unwrap(), QueryTerm::Text("报告".to_string()));
+        assert!("tag:".parse::<QueryTerm>().is_err());
+    }
+
+    #[test]
+    fn prefixed_parsing_rejects_bare_words() {
+        assert!(QueryTerm::parse_prefixed("foo").is_err());
+        assert!(QueryTerm::parse_prefixed("url:x").is_err());
+        assert_eq!(QueryTerm::parse_prefixed("text:foo").unwrap(), QueryTerm::Text("foo".to_string()));
+    }
+
+    #[test]
+    fn all_terms_must_match() {
+        let query: TaskQuery = "tag:work status:pending 报告".parse().unwrap();
+        assert!(query.matches(&task("写周报告", &["work"], TaskStatus::Pending)));
+        assert!(!query.matches(&task("写周报告", &["home"], TaskStatus::Pending)));
+        assert!(!query.matches(&task("写周报告", &["work"], TaskStatus::Completed)));
+        assert!(!query.matches(&task("买牛奶", &["work"], TaskStatus::Pending)));
+    }
+
+    #[test]
+    fn text_matching_ignores_case() {
+        let query: TaskQuery = "text:REPORT".parse().unwrap();
+        assert!(query.matches(&task("weekly report", &[], TaskStatus::Pending)));
+        assert!(TaskQuery::default().matches(&task("anything", &[], TaskStatus::Pending)));
+    }
+}
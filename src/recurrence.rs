@@ -0,0 +1,157 @@
+use std::fmt;
+use std::str::FromStr;
+use serde::{Deserialize, Serialize};
+use crate::error::TodoError;
+
+// 重复周期：每 interval 天/周/月/年
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Recurrence {
+    pub frequency: Frequency,
+    pub interval: u32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Frequency {
+    Daily,
+    Weekly,
+    Monthly,
+    Yearly,
+}
+
+impl Recurrence {
+    pub fn label(&self) -> String {
+        let unit = match self.frequency {
+            Frequency::Daily => "天",
+            Frequency::Weekly => "周",
+            Frequency::Monthly => "个月",
+            Frequency::Yearly => "年",
+        };
+        match self.interval {
+            1 => format!("每{}", unit.trim_start_matches('个')),
+            n => format!("每 {} {}", n, unit),
+        }
+    }
+
+    // iCalendar 的 RRULE 值，如 FREQ=WEEKLY;INTERVAL=2
+    pub fn to_rrule(&self) -> String {
+        let frequency = match self.frequency {
+            Frequency::Daily => "DAILY",
+            Frequency::Weekly => "WEEKLY",
+            Frequency::Monthly => "MONTHLY",
+            Frequency::Yearly => "YEARLY",
+        };
+        match self.interval {
+            1 => format!("FREQ={}", frequency),
+            n => format!("FREQ={};INTERVAL={}", frequency, n),
+        }
+    }
+
+    // 只支持 FREQ 和 INTERVAL；带 COUNT、UNTIL、BYDAY 等规则时无法准确表示，返回 None
+    pub fn from_rrule(value: &str) -> Option<Self> {
+        let mut frequency = None;
+        let mut interval = 1;
+        for part in value.split(';').filter(|part| !part.trim().is_empty()) {
+            let (key, value) = part.split_once('=')?;
+            match key.trim().to_uppercase().as_str() {
+                "FREQ" => {
+                    frequency = Some(match value.trim().to_uppercase().as_str() {
+                        "DAILY" => Frequency::Daily,
+                        "WEEKLY" => Frequency::Weekly,
+                        "MONTHLY" => Frequency::Monthly,
+                        "YEARLY" => Frequency::Yearly,
+                        _ => return None,
+                    });
+                }
+                "INTERVAL" => interval = value.trim().parse().ok().filter(|n| *n > 0)?,
+                // 一周从哪天开始不影响按间隔计算
+                "WKST" => {}
+                _ => return None,
+            }
+        }
+        Some(Recurrence { frequency: frequency?, interval })
+    }
+}
+
+impl fmt::Display for Recurrence {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        // 与解析格式一致: 1d、2w、1m、1y
+        let unit = match self.frequency {
+            Frequency::Daily => 'd',
+            Frequency::Weekly => 'w',
+            Frequency::Monthly => 'm',
+            Frequency::Yearly => 'y',
+        };
+        write!(f, "{}{}", self.interval, unit)
+    }
+}
+
+// 支持 daily/weekly/monthly/yearly、1d、2w、3m、1y 以及 RRULE 形式的 FREQ=WEEKLY;INTERVAL=2
+impl FromStr for Recurrence {
+    type Err = TodoError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || TodoError::InvalidInput(format!("无法识别的重复周期: {}，示例: daily、weekly、2w、1m", s));
+        let value = s.trim().to_lowercase();
+        if value.contains('=') {
+            return Recurrence::from_rrule(&value).ok_or_else(invalid);
+        }
+        let named = match value.as_str() {
+            "daily" | "每天" => Some(Frequency::Daily),
+            "weekly" | "每周" => Some(Frequency::Weekly),
+            "monthly" | "每月" => Some(Frequency::Monthly),
+            "yearly" | "每年" => Some(Frequency::Yearly),
+            _ => None,
+        };
+        if let Some(frequency) = named {
+            return Ok(Recurrence { frequency, interval: 1 });
+        }
+
+        let unit = value.chars().last().ok_or_else(invalid)?;
+        let frequency = match unit {
+            'd' => Frequency::Daily,
+            'w' => Frequency::Weekly,
+            'm' => Frequency::Monthly,
+            'y' => Frequency::Yearly,
+            _ => return Err(invalid()),
+        };
+        let number = &value[..value.len() - 1];
+        let interval = if number.is_empty() { 1 } else { number.parse().map_err(|_| invalid())? };
+        if interval == 0 {
+            return Err(invalid());
+        }
+        Ok(Recurrence { frequency, interval })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_short_and_named_forms() {
+        assert_eq!("weekly".parse::<Recurrence>().unwrap(), Recurrence { frequency: Frequency::Weekly, interval: 1 });
+        assert_eq!("2w".parse::<Recurrence>().unwrap(), Recurrence { frequency: Frequency::Weekly, interval: 2 });
+        assert_eq!("d".parse::<Recurrence>().unwrap(), Recurrence { frequency: Frequency::Daily, interval: 1 });
+        assert_eq!("FREQ=MONTHLY;INTERVAL=3".parse::<Recurrence>().unwrap(), Recurrence { frequency: Frequency::Monthly, interval: 3 });
+        for text in ["", "0d", "2x", "w2", "FREQ=HOURLY", "FREQ=WEEKLY;BYDAY=MO"] {
+            assert!(text.parse::<Recurrence>().is_err(), "{} 应被拒绝", text);
+        }
+    }
+
+    #[test]
+    fn display_and_rrule_round_trip() {
+        for text in ["1d", "2w", "1m", "10y"] {
+            let recurrence: Recurrence = text.parse().unwrap();
+            assert_eq!(recurrence.to_string(), text);
+            assert_eq!(Recurrence::from_rrule(&recurrence.to_rrule()), Some(recurrence));
+        }
+        assert_eq!("1w".parse::<Recurrence>().unwrap().to_rrule(), "FREQ=WEEKLY");
+    }
+
+    #[test]
+    fn labels_in_chinese() {
+        assert_eq!("1m".parse::<Recurrence>().unwrap().label(), "每月");
+        assert_eq!("3m".parse::<Recurrence>().unwrap().label(), "每 3 个月");
+        assert_eq!("2w".parse::<Recurrence>().unwrap().label(), "每 2 周");
+    }
+}
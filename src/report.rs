@@ -0,0 +1,262 @@
+use std::fmt::Write;
+use std::str::FromStr;
+use chrono::{DateTime, Datelike, Duration, Local, NaiveDate, Utc, Weekday};
+use crate::error::TodoError;
+use crate::stats;
+use crate::task::Task;
+use crate::time_tracking;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ReportFormat {
+    Markdown,
+    Text,
+}
+
+// 报告覆盖的日期范围（本地日期，首尾都包含）
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ReportRange {
+    pub from: NaiveDate,
+    pub to: NaiveDate,
+}
+
+impl ReportRange {
+    pub fn today() -> Self {
+        let today = time_tracking::today();
+        ReportRange { from: today, to: today }
+    }
+
+    // 本周一到今天
+    pub fn this_week() -> Self {
+        let today = time_tracking::today();
+        let monday = today - Duration::days(i64::from(today.weekday().num_days_from_monday()));
+        ReportRange { from: monday, to: today }
+    }
+
+    // 上周一到上周日
+    pub fn last_week() -> Self {
+        let this_week = Self::this_week();
+        ReportRange { from: this_week.from - Duration::weeks(1), to: this_week.from - Duration::days(1) }
+    }
+
+    pub fn contains(&self, day: NaiveDate) -> bool {
+        self.from <= day && day <= self.to
+    }
+
+    fn days(&self) -> impl Iterator<Item = NaiveDate> {
+        self.from.iter_days().take_while(|day| *day <= self.to)
+    }
+
+    // 单日为日报，从周一开始且不跨周为周报，从 1 号开始且不跨月为月报
+    pub fn title(&self) -> String {
+        let same_week = self.from.iso_week() == self.to.iso_week() && self.to - self.from < Duration::weeks(1);
+        let same_month = self.from.year() == self.to.year() && self.from.month() == self.to.month();
+        if self.from == self.to {
+            format!("日报 {}", self.from)
+        } else if self.from.weekday() == Weekday::Mon && same_week {
+            format!("周报 {} ~ {}", self.from, self.to)
+        } else if self.from.day() == 1 && same_month {
+            format!("月报 {} ~ {}", self.from, self.to)
+        } else {
+            format!("报告 {} ~ {}", self.from, self.to)
+        }
+    }
+}
+
+// 生成日报/周报：按标签分组的已完成任务、新建任务、截至范围结束时已过期仍未完成的任务和计时
+pub fn generate(tasks: &[Task], range: ReportRange, format: ReportFormat, now: DateTime<Utc>) -> String {
+    let completed: Vec<&Task> = tasks.iter()
+        .filter(|t| stats::completion_day(t).is_some_and(|d| range.contains(d)))
+        .collect();
+    let created: Vec<&Task> = tasks.iter()
+        .filter(|t| time_tracking::local_date(t.created_at()).is_some_and(|d| range.contains(d)))
+        .collect();
+    // 范围结束时（或今天，若范围尚未结束）仍未完成且已过截止日期的任务；
+    // 范围之后才完成的任务在当时仍未完成
+    let cutoff = range.to.succ_opt().unwrap_or(range.to).min(now.with_timezone(&Local).date_naive());
+    let overdue: Vec<&Task> = tasks.iter()
+        .filter(|t| time_tracking::local_date(t.created_at()).is_some_and(|d| d <= range.to))
+        .filter(|t| stats::completion_day(t).is_none_or(|d| d > range.to))
+        .filter(|t| t.due().is_some_and(|due| due < cutoff))
+        .collect();
+    let tracked: Vec<(&Task, i64)> = tasks.iter()
+        .map(|t| (t, range.days().map(|day| t.tracked_seconds_on(day, now)).sum::<i64>()))
+        .filter(|(_, seconds)| *seconds > 0)
+        .collect();
+
+    let mut out = Report { out: String::new(), format };
+    out.title(&range.title());
+
+    out.heading(&format!("已完成 ({})", completed.len()));
+    if completed.is_empty() {
+        out.line("(无)");
+    }
+    for (tag, group) in group_by_tag(&completed) {
+        out.subheading(&tag);
+        for task in group {
+            out.item(true, &format!("#{} {}", task.id(), task.description()));
+        }
+    }
+
+    out.heading(&format!("新建 ({})", created.len()));
+    if created.is_empty() {
+        out.line("(无)");
+    }
+    for task in &created {
+        out.item(task.is_completed(), &format!("#{} {}", task.id(), task.description()));
+    }
+
+    out.heading(&format!("已过期未完成 ({})", overdue.len()));
+    if overdue.is_empty() {
+        out.line("(无)");
+    }
+    for task in &overdue {
+        let due = task.due().map(|d| d.to_string()).unwrap_or_default();
+        out.item(false, &format!("#{} {} (截止 {})", task.id(), task.description(), due));
+    }
+
+    let total: i64 = tracked.iter().map(|(_, seconds)| seconds).sum();
+    out.heading(&format!("用时 ({})", time_tracking::format_duration(total)));
+    if tracked.is_empty() {
+        out.line("(无计时记录)");
+    }
+    for (task, seconds) in &tracked {
+        out.bullet(&format!("#{} {}: {}", task.id(), task.description(), time_tracking::format_duration(*seconds)));
+    }
+
+    out.out
+}
+
+// 按标签分组，有多个标签的任务出现在每个组中，无标签的放在最后
+fn group_by_tag<'a>(tasks: &[&'a Task]) -> Vec<(String, Vec<&'a Task>)> {
+    let mut tags: Vec<&String> = tasks.iter().flat_map(|t| t.tags()).collect();
+    tags.sort();
+    tags.dedup();
+    let mut groups: Vec<(String, Vec<&Task>)> = tags.into_iter()
+        .map(|tag| (tag.clone(), tasks.iter().copied().filter(|t| t.has_tag(tag)).collect()))
+        .collect();
+    let untagged: Vec<&Task> = tasks.iter().copied().filter(|t| t.tags().is_empty()).collect();
+    if !untagged.is_empty() {
+        groups.push(("无标签".to_string(), untagged));
+    }
+    groups
+}
+
+// 按格式输出各级标题和条目
+struct Report {
+    out: String,
+    format: ReportFormat,
+}
+
+impl Report {
+    fn title(&mut self, text: &str) {
+        match self.format {
+            ReportFormat::Markdown => writeln!(self.out, "# {}", text),
+            ReportFormat::Text => writeln!(self.out, "{}\n{}", text, "=".repeat(text.chars().count() * 2)),
+        }.ok();
+    }
+
+    fn heading(&mut self, text: &str) {
+        match self.format {
+            ReportFormat::Markdown => writeln!(self.out, "\n## {}", text),
+            ReportFormat::Text => writeln!(self.out, "\n{}\n{}", text, "-".repeat(text.chars().count() * 2)),
+        }.ok();
+    }
+
+    fn subheading(&mut self, text: &str) {
+        match self.format {
+            ReportFormat::Markdown => writeln!(self.out, "\n### {}", text),
+            ReportFormat::Text => writeln!(self.out, "[{}]", text),
+        }.ok();
+    }
+
+    fn item(&mut self, done: bool, text: &str) {
+        match self.format {
+            ReportFormat::Markdown => writeln!(self.out, "- [{}] {}", if done { "x" } else { " " }, text),
+            ReportFormat::Text => writeln!(self.out, "  {} {}", if done { "✓" } else { "·" }, text),
+        }.ok();
+    }
+
+    fn bullet(&mut self, text: &str) {
+        match self.format {
+            ReportFormat::Markdown => writeln!(self.out, "- {}", text),
+            ReportFormat::Text => writeln!(self.out, "  - {}", text),
+        }.ok();
+    }
+
+    fn line(&mut self, text: &str) {
+        match self.format {
+            ReportFormat::Markdown => writeln!(self.out, "{}", text),
+            ReportFormat::Text => writeln!(self.out, "  {}", text),
+        }.ok();
+    }
+}
+
+impl FromStr for ReportFormat {
+    type Err = TodoError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "md" | "markdown" => Ok(ReportFormat::Markdown),
+            "text" | "txt" | "plain" => Ok(ReportFormat::Text),
+            other => Err(TodoError::InvalidInput(format!("未知的报告格式: {}", other))),
+        }
+    }
+}
+
+// YYYY-MM-DD
+pub fn parse_date(s: &str) -> Result<NaiveDate, TodoError> {
+    NaiveDate::parse_from_str(s.trim(), "%Y-%m-%d")
+        .map_err(|_| TodoError::InvalidInput(format!("无效的日期: {}，格式应为 YYYY-MM-DD", s)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::import::ImportedTask;
+    use crate::task::TaskStatus;
+    use crate::task_list::TaskList;
+
+    fn date(day: u32) -> NaiveDate {
+        // 2025-06-30 是周一
+        NaiveDate::from_ymd_opt(2025, 7, 1).unwrap() + Duration::days(i64::from(day) - 1)
+    }
+
+    fn add(task_list: &mut TaskList, description: &str, due: Option<NaiveDate>, completed: Option<NaiveDate>) {
+        let mut task = ImportedTask::new(description.to_string());
+        task.created_at = Some(time_tracking::local_midnight_string(date(1)));
+        task.due = due;
+        if let Some(completed) = completed {
+            task.status = TaskStatus::Completed;
+            task.completed_at = Some(time_tracking::local_midnight_string(completed));
+        }
+        task_list.import_task(task);
+    }
+
+    #[test]
+    fn lists_only_tasks_overdue_at_range_end() {
+        let mut task_list = TaskList::new();
+        add(&mut task_list, "过期未做", Some(date(2)), None);
+        add(&mut task_list, "范围内完成", Some(date(2)), Some(date(3)));
+        add(&mut task_list, "范围后才完成", Some(date(3)), Some(date(10)));
+        add(&mut task_list, "尚未到期", Some(date(8)), None);
+        add(&mut task_list, "没有截止日期", None, None);
+
+        let range = ReportRange { from: date(1), to: date(6) };
+        let report = generate(task_list.get_tasks(), range, ReportFormat::Markdown, Utc::now());
+        let section = report.split("## ").find(|s| s.starts_with("已过期未完成")).unwrap();
+        assert!(section.starts_with("已过期未完成 (2)"), "{}", report);
+        assert!(section.contains("过期未做") && section.contains("范围后才完成"), "{}", section);
+        assert!(!section.contains("尚未到期") && !section.contains("没有截止日期"), "{}", section);
+    }
+
+    #[test]
+    fn titles_follow_the_range() {
+        let range = |from, to| ReportRange { from: date(from), to: date(to) };
+        assert_eq!(range(3, 3).title(), "日报 2025-07-03");
+        assert!(range(0, 6).title().starts_with("周报"));
+        assert!(range(0, 3).title().starts_with("周报"));
+        assert!(range(1, 31).title().starts_with("月报"));
+        assert!(range(2, 5).title().starts_with("报告"));
+        assert!(range(0, 13).title().starts_with("报告"));
+    }
+}
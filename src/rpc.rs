@@ -0,0 +1,425 @@
+use std::io::{BufRead, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime};
+use chrono::Utc;
+use serde::Deserialize;
+use serde::de::DeserializeOwned;
+use serde_json::{json, Value};
+use crate::api::{self, ErrorRecord, NewTask, StatsRecord, TaskPatch, TaskRecord};
+use crate::error::{Result, TodoError};
+use crate::query::TaskQuery;
+use crate::stats::Statistics;
+use crate::storage::Storage;
+use crate::task::{TaskStatus, TaskUpdate};
+use crate::task_list::{Placement, TaskList};
+
+// 每行一个 JSON-RPC 2.0 消息 (也支持批量请求)，响应同样每行一个
+// tasks.json 变化时 (包括其他进程的修改) 发送 tasks.changed 通知，参数为完整的任务列表
+pub const CHANGED_NOTIFICATION: &str = "tasks.changed";
+
+pub const METHODS: [&str; 16] = [
+    "tasks.list",
+    "tasks.get",
+    "tasks.add",
+    "tasks.update",
+    "tasks.complete",
+    "tasks.suspend",
+    "tasks.reopen",
+    "tasks.delete",
+    "tasks.tag",
+    "tasks.untag",
+    "tasks.move",
+    "timer.start",
+    "timer.stop",
+    "pomodoro.record",
+    "stats",
+    "rpc.methods",
+];
+
+const WATCH_INTERVAL: Duration = Duration::from_millis(500);
+
+// JSON-RPC 规定的错误码，任务相关的错误统一为 -32000，具体原因在 data.code 中
+const PARSE_ERROR: i64 = -32700;
+const INVALID_REQUEST: i64 = -32600;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+const TASK_ERROR: i64 = -32000;
+
+struct RpcError {
+    code: i64,
+    message: String,
+    data: Option<Value>,
+}
+
+impl From<TodoError> for RpcError {
+    fn from(err: TodoError) -> Self {
+        RpcError {
+            code: TASK_ERROR,
+            message: err.to_string(),
+            data: Some(json!(ErrorRecord::from(&err))),
+        }
+    }
+}
+
+impl RpcError {
+    fn new(code: i64, message: impl Into<String>) -> Self {
+        RpcError { code, message: message.into(), data: None }
+    }
+}
+
+type RpcResult = std::result::Result<Value, RpcError>;
+
+// 数字ID 或 UUID (前缀)
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum Selector {
+    Id(usize),
+    Text(String),
+}
+
+impl Selector {
+    fn resolve(&self, task_list: &TaskList) -> Result<usize> {
+        match self {
+            Selector::Id(id) => api::resolve_task(task_list, &id.to_string()),
+            Selector::Text(text) => api::resolve_task(task_list, text),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct ListParams {
+    #[serde(default)]
+    query: String,
+}
+
+#[derive(Deserialize)]
+struct TaskParams {
+    id: Selector,
+}
+
+#[derive(Deserialize)]
+struct UpdateParams {
+    id: Selector,
+    #[serde(flatten)]
+    patch: TaskPatch,
+}
+
+#[derive(Deserialize)]
+struct BulkParams {
+    ids: Vec<Selector>,
+}
+
+#[derive(Deserialize)]
+struct TagParams {
+    ids: Vec<Selector>,
+    tag: String,
+}
+
+// placement: top / bottom / before / after，before 和 after 需要 target
+#[derive(Deserialize)]
+struct MoveParams {
+    id: Selector,
+    placement: String,
+    target: Option<Selector>,
+}
+
+// 输出由请求处理和文件监视共用，整行写入
+struct Session<W: Write> {
+    output: Mutex<W>,
+    // 上次检查时的文件状态和任务列表，内容没有变化的保存不发送通知
+    last_seen: Mutex<(Option<(SystemTime, u64)>, Value)>,
+}
+
+impl<W: Write> Session<W> {
+    fn send(&self, message: &Value) {
+        let mut output = self.output.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        writeln!(output, "{}", message).and_then(|_| output.flush()).ok();
+    }
+
+    fn notify_if_changed(&self) {
+        let current = Storage::stamp();
+        let mut last_seen = self.last_seen.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        if last_seen.0 == current {
+            return;
+        }
+        let Ok(task_list) = Storage::load_tasks() else {
+            return;
+        };
+        let tasks = json!(api::task_records(&task_list));
+        let changed = last_seen.1 != tasks;
+        *last_seen = (current, tasks.clone());
+        drop(last_seen);
+        if changed {
+            self.send(&json!({ "jsonrpc": "2.0", "method": CHANGED_NOTIFICATION, "params": { "tasks": tasks } }));
+        }
+    }
+}
+
+// 读取到输入结束为止，例如 `todolist-cli rpc` 使用标准输入输出
+pub fn serve<R: BufRead, W: Write + Send + 'static>(input: R, output: W) -> Result<()> {
+    let tasks = json!(api::task_records(&Storage::load_tasks()?));
+    let session = Arc::new(Session { output: Mutex::new(output), last_seen: Mutex::new((Storage::stamp(), tasks)) });
+    let running = Arc::new(AtomicBool::new(true));
+    let watcher = {
+        let session = Arc::clone(&session);
+        let running = Arc::clone(&running);
+        std::thread::spawn(move || {
+            while running.load(Ordering::Relaxed) {
+                std::thread::sleep(WATCH_INTERVAL);
+                session.notify_if_changed();
+            }
+        })
+    };
+
+    for line in input.lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        if let Some(response) = handle_line(&line) {
+            session.send(&response);
+        }
+        // 修改后立即通知，不必等待下一次检查
+        session.notify_if_changed();
+    }
+
+    running.store(false, Ordering::Relaxed);
+    watcher.join().ok();
+    Ok(())
+}
+
+fn handle_line(line: &str) -> Option<Value> {
+    let message: Value = match serde_json::from_str(line) {
+        Ok(message) => message,
+        Err(err) => return Some(error_response(Value::Null, RpcError::new(PARSE_ERROR, err.to_string()))),
+    };
+    match message {
+        Value::Array(batch) if batch.is_empty() => {
+            Some(error_response(Value::Null, RpcError::new(INVALID_REQUEST, "批量请求不能为空")))
+        }
+        Value::Array(batch) => {
+            let responses: Vec<Value> = batch.into_iter().filter_map(handle_message).collect();
+            (!responses.is_empty()).then_some(Value::Array(responses))
+        }
+        message => handle_message(message),
+    }
+}
+
+// 没有 id 的请求是通知，不返回响应
+fn handle_message(message: Value) -> Option<Value> {
+    let id = message.get("id").cloned();
+    let method = message.get("method").and_then(Value::as_str);
+    let (method, params) = match (message.get("jsonrpc").and_then(Value::as_str), method) {
+        (Some("2.0"), Some(method)) => (method, message.get("params").cloned().unwrap_or(json!({}))),
+        _ => return Some(error_response(id.unwrap_or(Value::Null), RpcError::new(INVALID_REQUEST, "不是有效的 JSON-RPC 2.0 请求"))),
+    };
+
+    let result = call(method, params);
+    let id = id?;
+    Some(match result {
+        Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+        Err(err) => error_response(id, err),
+    })
+}
+
+fn error_response(id: Value, err: RpcError) -> Value {
+    let mut error = json!({ "code": err.code, "message": err.message });
+    if let Some(data) = err.data {
+        error["data"] = data;
+    }
+    json!({ "jsonrpc": "2.0", "id": id, "error": error })
+}
+
+fn params<T: DeserializeOwned>(params: Value) -> std::result::Result<T, RpcError> {
+    serde_json::from_value(params).map_err(|err| RpcError::new(INVALID_PARAMS, err.to_string()))
+}
+
+fn call(method: &str, raw: Value) -> RpcResult {
+    match method {
+        "rpc.methods" => Ok(json!(METHODS)),
+        "tasks.list" => {
+            let ListParams { query } = params(raw)?;
+            let query: TaskQuery = query.parse()?;
+            let task_list = Storage::load_tasks()?;
+            let tasks: Vec<TaskRecord> = task_list.get_tasks().iter()
+                .filter(|task| query.matches(task))
+                .map(TaskRecord::from)
+                .collect();
+            Ok(json!(tasks))
+        }
+        "tasks.get" => {
+            let TaskParams { id } = params(raw)?;
+            let task_list = Storage::load_tasks()?;
+            let id = id.resolve(&task_list)?;
+            task_result(&task_list, id)
+        }
+        "tasks.add" => {
+            let new_task: NewTask = params(raw)?;
+            let mut task_list = Storage::load_tasks()?;
+            let id = new_task.add_to(&mut task_list)?;
+            Storage::save_tasks(&task_list)?;
+            task_result(&task_list, id)
+        }
+        "tasks.update" => {
+            let UpdateParams { id, patch } = params(raw)?;
+            modify_one(|task_list| {
+                let id = id.resolve(task_list)?;
+                task_list.update_task(id, patch.into_update()?)?;
+                Ok(id)
+            })
+        }
+        "tasks.complete" => bulk(raw, |task_list, ids| task_list.complete_tasks(ids)),
+        "tasks.suspend" => bulk(raw, |task_list, ids| task_list.suspend_tasks(ids)),
+        "tasks.reopen" => bulk(raw, |task_list, ids| {
+            for &id in ids {
+                task_list.update_task(id, TaskUpdate { status: Some(TaskStatus::Pending), ..Default::default() })?;
+            }
+            Ok(())
+        }),
+        "tasks.tag" | "tasks.untag" => {
+            let TagParams { ids, tag } = params(raw)?;
+            modify(|task_list| {
+                let ids = resolve_all(task_list, &ids)?;
+                if method == "tasks.tag" {
+                    task_list.tag_tasks(&ids, &tag)?;
+                } else {
+                    task_list.untag_tasks(&ids, &tag)?;
+                }
+                Ok(ids)
+            })
+        }
+        "tasks.delete" => {
+            let BulkParams { ids } = params(raw)?;
+            let mut task_list = Storage::load_tasks()?;
+            let ids = resolve_all(&task_list, &ids)?;
+            let deleted: Vec<TaskRecord> = ids.iter()
+                .filter_map(|&id| task_list.get_task(id))
+                .map(TaskRecord::from)
+                .collect();
+            task_list.delete_tasks(&ids)?;
+            Storage::save_tasks(&task_list)?;
+            Ok(json!(deleted))
+        }
+        "tasks.move" => {
+            let MoveParams { id, placement, target } = params(raw)?;
+            modify_one(|task_list| {
+                let id = id.resolve(task_list)?;
+                let target = || -> Result<usize> {
+                    target.as_ref()
+                        .ok_or_else(|| TodoError::InvalidInput(format!("{} 需要 target", placement)))?
+                        .resolve(task_list)
+                };
+                let placement = match placement.as_str() {
+                    "top" => Placement::Top,
+                    "bottom" => Placement::Bottom,
+                    "before" => Placement::Before(target()?),
+                    "after" => Placement::After(target()?),
+                    other => return Err(TodoError::InvalidInput(format!("未知的位置: {}，可选 top、bottom、before、after", other))),
+                };
+                task_list.move_task(id, placement)?;
+                Ok(id)
+            })
+        }
+        "timer.start" => {
+            let TaskParams { id } = params(raw)?;
+            let mut task_list = Storage::load_tasks()?;
+            let id = id.resolve(&task_list)?;
+            let stopped = task_list.start_timer(id)?;
+            Storage::save_tasks(&task_list)?;
+            Ok(json!({ "task": TaskRecord::from(get(&task_list, id)?), "stopped": stopped }))
+        }
+        "timer.stop" => {
+            let mut task_list = Storage::load_tasks()?;
+            let stopped = task_list.stop_timer();
+            if stopped.is_some() {
+                Storage::save_tasks(&task_list)?;
+            }
+            Ok(json!({ "stopped": stopped }))
+        }
+        "pomodoro.record" => {
+            let TaskParams { id } = params(raw)?;
+            modify_one(|task_list| {
+                let id = id.resolve(task_list)?;
+                task_list.record_pomodoro(id)?;
+                Ok(id)
+            })
+        }
+        "stats" => {
+            let task_list = Storage::load_tasks()?;
+            let stats = Statistics::compute(task_list.get_tasks(), Utc::now());
+            Ok(json!(StatsRecord::from(&stats)))
+        }
+        other => Err(RpcError::new(METHOD_NOT_FOUND, format!("未知方法: {}", other))),
+    }
+}
+
+// 加载、修改、保存，返回被修改的任务
+fn modify(f: impl FnOnce(&mut TaskList) -> Result<Vec<usize>>) -> RpcResult {
+    let mut task_list = Storage::load_tasks()?;
+    let ids = f(&mut task_list)?;
+    Storage::save_tasks(&task_list)?;
+    let tasks = ids.iter().map(|&id| get(&task_list, id).map(TaskRecord::from)).collect::<Result<Vec<_>>>()?;
+    Ok(json!(tasks))
+}
+
+fn modify_one(f: impl FnOnce(&mut TaskList) -> Result<usize>) -> RpcResult {
+    let mut tasks = modify(|task_list| f(task_list).map(|id| vec![id]))?;
+    Ok(tasks[0].take())
+}
+
+fn bulk(raw: Value, f: impl FnOnce(&mut TaskList, &[usize]) -> Result<()>) -> RpcResult {
+    let BulkParams { ids } = params(raw)?;
+    modify(|task_list| {
+        let ids = resolve_all(task_list, &ids)?;
+        f(task_list, &ids)?;
+        Ok(ids)
+    })
+}
+
+fn resolve_all(task_list: &TaskList, selectors: &[Selector]) -> Result<Vec<usize>> {
+    if selectors.is_empty() {
+        return Err(TodoError::InvalidInput("ids 不能为空".to_string()));
+    }
+    selectors.iter().map(|selector| selector.resolve(task_list)).collect()
+}
+
+fn get(task_list: &TaskList, id: usize) -> Result<&crate::task::Task> {
+    task_list.get_task(id).ok_or(TodoError::TaskNotFound(id))
+}
+
+fn task_result(task_list: &TaskList, id: usize) -> RpcResult {
+    Ok(json!(TaskRecord::from(get(task_list, id)?)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn error_code(response: &Value) -> Option<i64> {
+        response["error"]["code"].as_i64()
+    }
+
+    #[test]
+    fn rejects_malformed_messages() {
+        assert_eq!(handle_line("{ 不是 JSON").as_ref().and_then(error_code), Some(PARSE_ERROR));
+        assert_eq!(handle_line("[]").as_ref().and_then(error_code), Some(INVALID_REQUEST));
+        let response = handle_line(r#"{"id":1,"method":"rpc.methods"}"#).unwrap();
+        assert_eq!(error_code(&response), Some(INVALID_REQUEST));
+        assert_eq!(response["id"], 1);
+    }
+
+    #[test]
+    fn answers_requests_but_not_notifications() {
+        let response = handle_line(r#"{"jsonrpc":"2.0","id":7,"method":"rpc.methods"}"#).unwrap();
+        assert_eq!(response["id"], 7);
+        assert!(response["result"].as_array().is_some_and(|methods| methods.contains(&json!("tasks.list"))));
+        assert!(handle_line(r#"{"jsonrpc":"2.0","method":"rpc.methods"}"#).is_none());
+
+        let unknown = handle_line(r#"{"jsonrpc":"2.0","id":"a","method":"tasks.fly"}"#).unwrap();
+        assert_eq!(error_code(&unknown), Some(METHOD_NOT_FOUND));
+        // 批量请求中的通知没有响应
+        let batch = handle_line(r#"[{"jsonrpc":"2.0","id":1,"method":"rpc.methods"},{"jsonrpc":"2.0","method":"rpc.methods"}]"#).unwrap();
+        assert_eq!(batch.as_array().map(Vec::len), Some(1));
+    }
+}
@@ -0,0 +1,518 @@
+use std::io::{Read, Write};
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+use serde_json::{json, Value};
+use tiny_http::{Header, Method, Request, Response, Server};
+use crate::api::{self, ErrorRecord, NewTask, TaskPatch, TaskRecord};
+use crate::error::{Result, TodoError};
+use crate::query::TaskQuery;
+use crate::storage::Storage;
+use crate::task::{TaskStatus, TaskUpdate};
+use crate::task_list::TaskList;
+
+// 只监听本机地址，所有 /tasks 请求都需要令牌
+pub const DEFAULT_PORT: u16 = 7878;
+pub const TOKEN_ENV: &str = "TODOLIST_API_TOKEN";
+
+// 网页界面，GET / 无需令牌，令牌放在地址的 #token= 中由页面读取
+const WEB_UI: &str = include_str!("web_ui.html");
+// 每个请求一个线程，读写 tasks.json 时串行
+static STORAGE_LOCK: Mutex<()> = Mutex::new(());
+const WATCH_INTERVAL: Duration = Duration::from_millis(500);
+// 没有变化时也定期发送注释行，写入失败说明连接已断开，结束该线程
+const KEEPALIVE_INTERVAL: Duration = Duration::from_secs(10);
+// 每个事件流占用一个线程，超过上限时返回 503
+const MAX_STREAMS: usize = 16;
+static ACTIVE_STREAMS: AtomicUsize = AtomicUsize::new(0);
+// 请求体的上限，超过时不读取直接返回 413
+const MAX_BODY_BYTES: usize = 1024 * 1024;
+
+pub struct ServerOptions {
+    pub port: u16,
+    pub token: String,
+}
+
+// 一次请求的结果，body 为 None 时返回 204
+struct ApiResponse {
+    status: u16,
+    body: Option<Value>,
+}
+
+// 阻塞运行，直到进程退出
+pub fn run(options: &ServerOptions) -> Result<()> {
+    let server = Server::http(("127.0.0.1", options.port))
+        .map_err(|e| TodoError::IoError(std::io::Error::other(e.to_string())))?;
+    for request in server.incoming_requests() {
+        let token = options.token.clone();
+        std::thread::spawn(move || serve_request(request, &token));
+    }
+    Ok(())
+}
+
+fn serve_request(mut request: Request, token: &str) {
+    if request.method() == &Method::Get {
+        let path = request.url().split('?').next().unwrap_or("/");
+        if path == "/" {
+            let page = Response::from_string(WEB_UI)
+                .with_header(Header::from_bytes("Content-Type", "text/html; charset=utf-8").unwrap());
+            return respond(request, page);
+        }
+        if path == "/events" && authorized(&request, token, true) {
+            let Some(_slot) = StreamSlot::acquire() else {
+                let body = json!(ErrorRecord { code: "too_many_streams".to_string(), message: format!("事件流连接数已达上限 {}", MAX_STREAMS) });
+                return respond(request, Response::from_string(body.to_string()).with_header(json_header()).with_status_code(503));
+            };
+            return stream_events(request);
+        }
+    }
+
+    let response = handle(&mut request, token);
+    let response = match response.body {
+        Some(body) => Response::from_string(body.to_string()).with_header(json_header()),
+        None => Response::from_string(String::new()),
+    }
+    .with_status_code(response.status);
+    respond(request, response);
+}
+
+fn respond<R: std::io::Read>(request: Request, response: Response<R>) {
+    if let Err(err) = request.respond(response) {
+        eprintln!("响应失败: {}", err);
+    }
+}
+
+// 服务器推送事件: 连接后先发送一次任务列表，之后 tasks.json 每次变化都推送最新的列表
+// tiny_http 的分块编码会缓冲输出，这里直接写入连接
+fn stream_events(request: Request) {
+    let mut writer = request.into_writer();
+    let header = "HTTP/1.1 200 OK\r\nContent-Type: text/event-stream; charset=utf-8\r\nCache-Control: no-cache\r\nConnection: close\r\n\r\nretry: 2000\n\n";
+    if writer.write_all(header.as_bytes()).is_err() {
+        return;
+    }
+
+    let mut last_seen = None;
+    let mut last_write = Instant::now();
+    loop {
+        let current = Storage::stamp();
+        let message = if last_seen != Some(current) {
+            last_seen = Some(current);
+            Some(tasks_event())
+        } else if last_write.elapsed() >= KEEPALIVE_INTERVAL {
+            Some(": keepalive\n\n".to_string())
+        } else {
+            None
+        };
+        if let Some(message) = message {
+            if writer.write_all(message.as_bytes()).and_then(|_| writer.flush()).is_err() {
+                return;
+            }
+            last_write = Instant::now();
+        }
+        std::thread::sleep(WATCH_INTERVAL);
+    }
+}
+
+// 占用一个事件流名额，连接结束 (drop) 时释放
+struct StreamSlot;
+
+impl StreamSlot {
+    fn acquire() -> Option<Self> {
+        ACTIVE_STREAMS.fetch_update(Ordering::SeqCst, Ordering::SeqCst, |active| (active < MAX_STREAMS).then_some(active + 1))
+            .ok()
+            .map(|_| StreamSlot)
+    }
+}
+
+impl Drop for StreamSlot {
+    fn drop(&mut self) {
+        ACTIVE_STREAMS.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+fn tasks_event() -> String {
+    let _guard = lock_storage();
+    match Storage::load_tasks() {
+        Ok(task_list) => {
+            format!("event: tasks\ndata: {}\n\n", json!(api::task_records(&task_list)))
+        }
+        Err(err) => format!("event: failure\ndata: {}\n\n", json!(ErrorRecord::from(&err))),
+    }
+}
+
+fn lock_storage() -> std::sync::MutexGuard<'static, ()> {
+    STORAGE_LOCK.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+fn handle(request: &mut Request, token: &str) -> ApiResponse {
+    let url = request.url().to_string();
+    let (path, query) = url.split_once('?').unwrap_or((&url, ""));
+    let segments: Vec<&str> = path.split('/').filter(|s| !s.is_empty()).collect();
+
+    if request.method() == &Method::Get && segments == ["openapi.json"] {
+        return ApiResponse { status: 200, body: Some(openapi()) };
+    }
+    if !authorized(request, token, false) {
+        return error_response(401, "unauthorized", "缺少或错误的访问令牌");
+    }
+
+    let too_large = || error_response(413, "payload_too_large", &format!("请求体不能超过 {} 字节", MAX_BODY_BYTES));
+    if request.body_length().is_some_and(|length| length > MAX_BODY_BYTES) {
+        return too_large();
+    }
+    // 没有 Content-Length (分块传输) 时最多读取上限加一个字节
+    let mut body = String::new();
+    if let Err(err) = request.as_reader().take(MAX_BODY_BYTES as u64 + 1).read_to_string(&mut body) {
+        return failure(&TodoError::IoError(err));
+    }
+    if body.len() > MAX_BODY_BYTES {
+        return too_large();
+    }
+    let _guard = lock_storage();
+    match route(request.method(), &segments, query, &body) {
+        Ok(response) => response,
+        Err(Routing::NotFound) => error_response(404, "not_found", "没有这个接口"),
+        Err(Routing::Failed(err)) => failure(&err),
+    }
+}
+
+enum Routing {
+    NotFound,
+    Failed(TodoError),
+}
+
+impl From<TodoError> for Routing {
+    fn from(err: TodoError) -> Self {
+        Routing::Failed(err)
+    }
+}
+
+impl From<serde_json::Error> for Routing {
+    fn from(err: serde_json::Error) -> Self {
+        Routing::Failed(err.into())
+    }
+}
+
+fn route(method: &Method, segments: &[&str], query: &str, body: &str) -> std::result::Result<ApiResponse, Routing> {
+    match (method, segments) {
+        (Method::Get, ["tasks"]) => {
+            let task_list = Storage::load_tasks()?;
+            let query = parse_query(query)?;
+            let tasks: Vec<TaskRecord> = task_list.get_tasks().iter()
+                .filter(|task| query.matches(task))
+                .map(TaskRecord::from)
+                .collect();
+            Ok(ok(200, json!(tasks)))
+        }
+        (Method::Post, ["tasks"]) => {
+            let new_task: NewTask = serde_json::from_str(body)?;
+            let mut task_list = Storage::load_tasks()?;
+            let id = new_task.add_to(&mut task_list)?;
+            Storage::save_tasks(&task_list)?;
+            Ok(ok(201, task_json(&task_list, id)?))
+        }
+        (Method::Get, ["tasks", id]) => {
+            let task_list = Storage::load_tasks()?;
+            let id = api::resolve_task(&task_list, &percent_decode(id))?;
+            Ok(ok(200, task_json(&task_list, id)?))
+        }
+        (Method::Patch, ["tasks", id]) => {
+            let patch: TaskPatch = serde_json::from_str(body)?;
+            modify(id, |task_list, id| task_list.update_task(id, patch.into_update()?))
+        }
+        (Method::Put, ["tasks", id]) => {
+            let replacement: NewTask = serde_json::from_str(body)?;
+            modify(id, |task_list, id| task_list.update_task(id, replacement.into_replacement()?))
+        }
+        (Method::Post, ["tasks", id, "complete"]) => modify(id, |task_list, id| task_list.complete_tasks(&[id])),
+        (Method::Post, ["tasks", id, "suspend"]) => modify(id, |task_list, id| task_list.suspend_tasks(&[id])),
+        (Method::Post, ["tasks", id, "reopen"]) => modify(id, |task_list, id| {
+            task_list.update_task(id, TaskUpdate { status: Some(TaskStatus::Pending), ..Default::default() })
+        }),
+        (Method::Delete, ["tasks", id]) => {
+            let mut task_list = Storage::load_tasks()?;
+            let id = api::resolve_task(&task_list, &percent_decode(id))?;
+            task_list.delete_tasks(&[id])?;
+            Storage::save_tasks(&task_list)?;
+            Ok(ApiResponse { status: 204, body: None })
+        }
+        _ => Err(Routing::NotFound),
+    }
+}
+
+// 加载、修改、保存，返回修改后的任务
+fn modify(id: &str, f: impl FnOnce(&mut TaskList, usize) -> Result<()>) -> std::result::Result<ApiResponse, Routing> {
+    let mut task_list = Storage::load_tasks()?;
+    let id = api::resolve_task(&task_list, &percent_decode(id))?;
+    f(&mut task_list, id)?;
+    Storage::save_tasks(&task_list)?;
+    Ok(ok(200, task_json(&task_list, id)?))
+}
+
+fn task_json(task_list: &TaskList, id: usize) -> Result<Value> {
+    let task = task_list.get_task(id).ok_or(TodoError::TaskNotFound(id))?;
+    Ok(json!(TaskRecord::from(task)))
+}
+
+// ?q=tag:work%20status:pending，也可以直接用 status= / tag= / text=
+fn parse_query(query: &str) -> Result<TaskQuery> {
+    let mut task_query = TaskQuery::default();
+    for (key, value) in query.split('&').filter_map(|pair| pair.split_once('=')) {
+        let value = percent_decode(value);
+        match key {
+            "q" => {
+                for term in value.split_whitespace() {
+                    task_query.push(term.parse()?);
+                }
+            }
+            "status" | "tag" | "text" => task_query.push(format!("{}:{}", key, value).parse()?),
+            _ => {}
+        }
+    }
+    Ok(task_query)
+}
+
+fn percent_decode(text: &str) -> String {
+    let bytes = text.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'+' => decoded.push(b' '),
+            b'%' if i + 2 < bytes.len() => {
+                match text.get(i + 1..i + 3).and_then(|hex| u8::from_str_radix(hex, 16).ok()) {
+                    Some(byte) => {
+                        decoded.push(byte);
+                        i += 2;
+                    }
+                    None => decoded.push(b'%'),
+                }
+            }
+            byte => decoded.push(byte),
+        }
+        i += 1;
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+// Authorization: Bearer <令牌>、X-Api-Token 头；只有 /events 接受 ?token=
+// (浏览器的 EventSource 不能设置请求头，其他接口的令牌不应出现在地址和日志中)
+fn authorized(request: &Request, token: &str, allow_query: bool) -> bool {
+    let in_query = allow_query && request.url().split_once('?').is_some_and(|(_, query)| {
+        query.split('&').any(|pair| pair.split_once('=').is_some_and(|(key, value)| key == "token" && same_token(&percent_decode(value), token)))
+    });
+    in_query || request.headers().iter().any(|header| {
+        let value = header.value.as_str();
+        if header.field.equiv("Authorization") {
+            value.strip_prefix("Bearer ").is_some_and(|v| same_token(v.trim(), token))
+        } else {
+            header.field.equiv("X-Api-Token") && same_token(value.trim(), token)
+        }
+    })
+}
+
+// 比较耗时与第一个不同的字节位置无关，避免逐字节猜出令牌
+fn same_token(given: &str, token: &str) -> bool {
+    let (given, token) = (given.as_bytes(), token.as_bytes());
+    let mut difference = given.len() ^ token.len();
+    for (i, byte) in token.iter().enumerate() {
+        difference |= usize::from(given.get(i).copied().unwrap_or(0) ^ byte);
+    }
+    difference == 0
+}
+
+fn ok(status: u16, body: Value) -> ApiResponse {
+    ApiResponse { status, body: Some(body) }
+}
+
+fn failure(err: &TodoError) -> ApiResponse {
+    ApiResponse { status: err.http_status(), body: Some(json!(ErrorRecord::from(err))) }
+}
+
+fn error_response(status: u16, code: &str, message: &str) -> ApiResponse {
+    ApiResponse {
+        status,
+        body: Some(json!(ErrorRecord { code: code.to_string(), message: message.to_string() })),
+    }
+}
+
+fn json_header() -> Header {
+    Header::from_bytes("Content-Type", "application/json; charset=utf-8").unwrap()
+}
+
+// 接口说明 (OpenAPI 3.0)，GET /openapi.json 无需令牌
+pub fn openapi() -> Value {
+    let task_id = json!({
+        "name": "id", "in": "path", "required": true,
+        "description": "数字ID，或 UUID / UUID 前缀",
+        "schema": { "type": "string" }
+    });
+    let task_response = |description: &str| json!({
+        "description": description,
+        "content": { "application/json": { "schema": { "$ref": "#/components/schemas/Task" } } }
+    });
+    let error = |description: &str| json!({
+        "description": description,
+        "content": { "application/json": { "schema": { "$ref": "#/components/schemas/Error" } } }
+    });
+    let action = |summary: &str| json!({
+        "post": {
+            "summary": summary,
+            "parameters": [task_id],
+            "responses": { "200": task_response("修改后的任务"), "404": error("任务不存在") }
+        }
+    });
+
+    json!({
+        "openapi": "3.0.3",
+        "info": {
+            "title": "todolist-cli API",
+            "version": env!("CARGO_PKG_VERSION"),
+            "description": "本地任务列表的 REST 接口，只监听 127.0.0.1"
+        },
+        "servers": [{ "url": format!("http://127.0.0.1:{}", DEFAULT_PORT) }],
+        "security": [{ "bearer": [] }],
+        "paths": {
+            "/tasks": {
+                "get": {
+                    "summary": "列出任务",
+                    "parameters": [
+                        { "name": "q", "in": "query", "description": "查询条件，如 \"status:pending tag:work\"", "schema": { "type": "string" } },
+                        { "name": "status", "in": "query", "schema": { "type": "string", "enum": ["pending", "suspended", "completed"] } },
+                        { "name": "tag", "in": "query", "schema": { "type": "string" } },
+                        { "name": "text", "in": "query", "schema": { "type": "string" } }
+                    ],
+                    "responses": {
+                        "200": {
+                            "description": "任务列表",
+                            "content": { "application/json": { "schema": { "type": "array", "items": { "$ref": "#/components/schemas/Task" } } } }
+                        },
+                        "400": error("查询条件无效"),
+                        "401": error("缺少或错误的令牌")
+                    }
+                },
+                "post": {
+                    "summary": "新建任务",
+                    "requestBody": {
+                        "required": true,
+                        "content": { "application/json": { "schema": { "$ref": "#/components/schemas/NewTask" } } }
+                    },
+                    "responses": { "201": task_response("新建的任务"), "400": error("参数无效") }
+                }
+            },
+            "/tasks/{id}": {
+                "get": {
+                    "summary": "获取任务",
+                    "parameters": [task_id],
+                    "responses": { "200": task_response("任务"), "404": error("任务不存在") }
+                },
+                "put": {
+                    "summary": "替换任务，未给出的字段清除，状态默认为 pending；计时记录和番茄数不变",
+                    "parameters": [task_id],
+                    "requestBody": {
+                        "required": true,
+                        "content": { "application/json": { "schema": { "$ref": "#/components/schemas/NewTask" } } }
+                    },
+                    "responses": { "200": task_response("替换后的任务"), "400": error("参数无效"), "404": error("任务不存在"), "413": error("请求体过大") }
+                },
+                "patch": {
+                    "summary": "修改任务，未给出的字段保持不变",
+                    "parameters": [task_id],
+                    "requestBody": {
+                        "required": true,
+                        "content": { "application/json": { "schema": { "$ref": "#/components/schemas/TaskPatch" } } }
+                    },
+                    "responses": { "200": task_response("修改后的任务"), "400": error("参数无效"), "404": error("任务不存在") }
+                },
+                "delete": {
+                    "summary": "删除任务",
+                    "parameters": [task_id],
+                    "responses": { "204": { "description": "已删除" }, "404": error("任务不存在") }
+                }
+            },
+            "/tasks/{id}/complete": action("完成任务"),
+            "/tasks/{id}/suspend": action("挂起任务"),
+            "/tasks/{id}/reopen": action("重新打开任务"),
+            "/events": {
+                "get": {
+                    "summary": "服务器推送事件，连接时和 tasks.json 每次变化时发送 tasks 事件 (数据为完整的任务列表)",
+                    "parameters": [
+                        { "name": "token", "in": "query", "description": "访问令牌，用于无法设置请求头的 EventSource", "schema": { "type": "string" } }
+                    ],
+                    "responses": {
+                        "200": { "description": "事件流", "content": { "text/event-stream": { "schema": { "type": "string" } } } },
+                        "401": error("缺少或错误的令牌"),
+                        "503": error("事件流连接数已达上限")
+                    }
+                }
+            }
+        },
+        "components": {
+            "securitySchemes": {
+                "bearer": { "type": "http", "scheme": "bearer", "description": "serve 启动时显示的令牌，也可用 X-Api-Token 头" }
+            },
+            "schemas": {
+                "Task": {
+                    "type": "object",
+                    "required": ["id", "uuid", "description", "status", "created_at", "tags", "position", "pomodoros", "tracked_seconds", "timer_running", "notes"],
+                    "properties": {
+                        "id": { "type": "integer", "description": "本机显示用的数字ID" },
+                        "uuid": { "type": "string", "format": "uuid" },
+                        "description": { "type": "string" },
+                        "status": { "type": "string", "enum": ["pending", "suspended", "completed"] },
+                        "created_at": { "type": "string", "description": "UTC，格式 YYYY-MM-DD HH:MM:SS" },
+                        "completed_at": { "type": "string", "nullable": true },
+                        "tags": { "type": "array", "items": { "type": "string" } },
+                        "position": { "type": "integer" },
+                        "estimate": { "type": "string", "nullable": true, "example": "1h30m" },
+                        "pomodoros": { "type": "integer" },
+                        "tracked_seconds": { "type": "integer" },
+                        "timer_running": { "type": "boolean" },
+                        "due": { "type": "string", "format": "date", "nullable": true },
+                        "priority": { "type": "string", "enum": ["high", "medium", "low"], "nullable": true },
+                        "repeat": { "type": "string", "nullable": true, "example": "1w" },
+                        "notes": { "type": "string", "description": "备注，没有时为空字符串" }
+                    }
+                },
+                "NewTask": {
+                    "type": "object",
+                    "required": ["description"],
+                    "properties": {
+                        "description": { "type": "string" },
+                        "tags": { "type": "array", "items": { "type": "string" } },
+                        "status": { "type": "string", "enum": ["pending", "suspended", "completed"] },
+                        "estimate": { "type": "string" },
+                        "due": { "type": "string", "example": "2025-07-02" },
+                        "priority": { "type": "string", "enum": ["high", "medium", "low"] },
+                        "repeat": { "type": "string", "example": "1w" },
+                        "notes": { "type": "string" }
+                    }
+                },
+                "TaskPatch": {
+                    "type": "object",
+                    "properties": {
+                        "description": { "type": "string" },
+                        "status": { "type": "string", "enum": ["pending", "suspended", "completed"] },
+                        "tags": { "type": "array", "items": { "type": "string" } },
+                        "estimate": { "type": "string", "nullable": true, "description": "null 表示清除预估" },
+                        "due": { "type": "string", "nullable": true, "description": "YYYY-MM-DD，null 表示清除" },
+                        "priority": { "type": "string", "enum": ["high", "medium", "low"], "nullable": true },
+                        "repeat": { "type": "string", "nullable": true, "description": "1d、2w、1m、1y，null 表示不再重复" },
+                        "notes": { "type": "string", "nullable": true, "description": "null 或空字符串表示清除备注" }
+                    }
+                },
+                "Error": {
+                    "type": "object",
+                    "required": ["code", "message"],
+                    "properties": {
+                        "code": {
+                            "type": "string",
+                            "enum": ["unauthorized", "not_found", "payload_too_large", "too_many_streams", "io_error", "invalid_json", "invalid_csv", "task_not_found", "invalid_input", "sync_failed", "git_error"]
+                        },
+                        "message": { "type": "string" }
+                    }
+                }
+            }
+        }
+    })
+}
@@ -0,0 +1,298 @@
+use chrono::Utc;
+use rustyline::completion::{Completer, Pair};
+use rustyline::error::ReadlineError;
+use rustyline::highlight::Highlighter;
+use rustyline::hint::Hinter;
+use rustyline::history::DefaultHistory;
+use rustyline::validate::Validator;
+use rustyline::{CompletionType, Config, Context, Editor, Helper};
+use crate::error::{Result, TodoError};
+use crate::export;
+use crate::storage::Storage;
+use crate::task::TaskStatus;
+use crate::task_list::TaskList;
+use crate::time_tracking;
+
+// 交互模式: 带行编辑、历史记录和 Tab 补全的命令行，命令与单条命令模式相同
+// 历史记录保存在数据目录中
+pub const HISTORY_FILE: &str = ".todolist_history";
+const MAX_HISTORY: usize = 1000;
+
+// 命令、参数和说明，help、补全脚本和手册页按此顺序显示
+pub const COMMANDS: [(&str, &str, &str); 30] = [
+    ("add", "<描述> [#标签...]", "添加任务"),
+    ("list", "[--sort 排序] [--group 分组] [条件...]", "列出任务"),
+    ("done", "<id|范围|条件...>", "完成任务"),
+    ("suspend", "<id|范围|条件...>", "挂起任务"),
+    ("delete", "<id|范围|条件...>", "删除任务"),
+    ("edit", "<id> [--status 状态] [--tags 标签] [--estimate 预估] [--due 日期] [--priority 优先级] [--repeat 周期] [--notes 备注] [新描述]", "编辑任务"),
+    ("tag", "<标签> <id...>", "添加标签"),
+    ("untag", "<标签> <id...>", "移除标签"),
+    ("move", "<id> before|after <id> | top|bottom", "调整顺序"),
+    ("estimate", "<id> <30m|2h|3pt|->", "设置预估"),
+    ("start", "<id>", "开始计时"),
+    ("stop", "", "停止计时"),
+    ("time", "[id]", "计时统计"),
+    ("estimates", "", "预估与实际用时"),
+    ("stats", "", "效率统计"),
+    ("report", "[--today|--week|--last-week]", "生成报告"),
+    ("export", "[-o 文件]", "导出任务"),
+    ("import", "<文件> [--dry-run]", "导入任务"),
+    ("git-init", "[远程仓库]", "开启 git 存储"),
+    ("log", "[-n 数量]", "修改历史"),
+    ("sync", "[远程名] | --replica <文件|目录>", "同步"),
+    ("caldav", "--url <地址>", "CalDAV 同步"),
+    ("serve", "[--port 端口]", "启动 REST 接口和网页界面"),
+    ("tui", "", "全屏界面"),
+    ("rpc", "", "JSON-RPC 模式"),
+    ("completions", "<bash|zsh|fish>", "生成 shell 补全脚本"),
+    ("man", "", "生成手册页"),
+    ("clear", "", "清屏"),
+    ("help", "", "显示帮助"),
+    ("exit", "", "退出 (也可以按 Ctrl+D)"),
+];
+
+// 只在交互模式中可用的命令
+pub const SHELL_COMMANDS: [&str; 3] = ["clear", "help", "exit"];
+
+// 各命令的选项，用于补全
+pub const FLAGS: [(&str, &[&str]); 9] = [
+    ("list", &["--sort", "--group", "--desc", "--uuid", "--format"]),
+    ("edit", &["--status", "--tags", "--estimate", "--due", "--priority", "--repeat", "--notes", "--format"]),
+    ("import", &["--dry-run", "--allow-duplicates", "--map"]),
+    ("export", &["--output"]),
+    ("report", &["--today", "--week", "--last-week", "--from", "--to"]),
+    ("caldav", &["--url", "--user", "--password", "--prefer"]),
+    ("serve", &["--port", "--token"]),
+    ("log", &["-n"]),
+    ("sync", &["--replica"]),
+];
+
+// 选项的取值，用于补全
+pub const FLAG_VALUES: [(&str, &[&str]); 9] = [
+    ("--sort", &["manual", "created", "status", "description", "completed", "due", "priority"]),
+    ("--group", &["none", "status", "tag", "due"]),
+    ("--status", &["pending", "suspended", "completed"]),
+    ("--format", &["text", "json", "jsonl", "tsv"]),
+    ("--prefer", &["local", "remote"]),
+    ("--estimate", &["15m", "30m", "1h", "2h", "1pt", "3pt", "-"]),
+    ("--due", &["today", "tomorrow", "+7", "-"]),
+    ("--priority", &["high", "medium", "low", "-"]),
+    ("--repeat", &["daily", "weekly", "monthly", "yearly", "2w", "-"]),
+];
+
+// move <id> 之后的位置参数
+const MOVE_PLACEMENTS: [&str; 4] = ["before", "after", "top", "bottom"];
+
+struct ShellHelper;
+
+impl Helper for ShellHelper {}
+impl Hinter for ShellHelper {
+    type Hint = String;
+}
+impl Highlighter for ShellHelper {}
+impl Validator for ShellHelper {}
+
+impl Completer for ShellHelper {
+    type Candidate = Pair;
+
+    fn complete(&self, line: &str, pos: usize, _ctx: &Context<'_>) -> rustyline::Result<(usize, Vec<Pair>)> {
+        let before = &line[..pos];
+        let start = before.char_indices().rev()
+            .find(|(_, c)| c.is_whitespace())
+            .map_or(0, |(i, c)| i + c.len_utf8());
+        let word = &before[start..];
+        let words: Vec<&str> = before[..start].split_whitespace().collect();
+
+        let candidates = match words.first() {
+            None => command_candidates(),
+            Some(command) => argument_candidates(command, &words[1..], word),
+        };
+        Ok((start, candidates.into_iter().filter(|c| c.replacement.starts_with(word)).collect()))
+    }
+}
+
+fn pair(replacement: &str, display: &str) -> Pair {
+    Pair { display: display.to_string(), replacement: replacement.to_string() }
+}
+
+fn command_candidates() -> Vec<Pair> {
+    COMMANDS.iter().map(|(name, _, summary)| pair(name, &format!("{:<12} {}", name, summary))).collect()
+}
+
+fn plain(values: &[&str]) -> Vec<Pair> {
+    values.iter().map(|value| pair(value, value)).collect()
+}
+
+// 根据命令和前面的参数决定补全内容: 选项、选项值、标签、状态或任务ID
+fn argument_candidates(command: &str, previous: &[&str], word: &str) -> Vec<Pair> {
+    // 补全时读取最新的任务，其他程序的修改也能补全
+    let task_list = Storage::load_tasks().unwrap_or_default();
+    let tags = all_tags(&task_list);
+
+    if let Some((_, values)) = previous.last().and_then(|flag| FLAG_VALUES.iter().find(|(name, _)| name == flag)) {
+        return plain(values);
+    }
+    if previous.last() == Some(&"--tags") {
+        return tags.iter().map(|tag| pair(tag, tag)).collect();
+    }
+    if word.starts_with('-') {
+        let flags = FLAGS.iter().find(|(name, _)| *name == command).map_or(&["--format"][..], |(_, flags)| flags);
+        return plain(flags);
+    }
+    if word.starts_with("tag:") || word.starts_with('#') {
+        let prefix = if word.starts_with('#') { "#" } else { "tag:" };
+        return tags.iter().map(|tag| pair(&format!("{}{}", prefix, tag), tag)).collect();
+    }
+    if word.starts_with("status:") {
+        return ["pending", "suspended", "completed"].iter().map(|s| pair(&format!("status:{}", s), s)).collect();
+    }
+    if word.starts_with("uuid:") {
+        return task_list.get_tasks().iter()
+            .map(|task| pair(&format!("uuid:{}", &task.uuid()[..8]), &format!("{}  {}", task.uuid(), task.description())))
+            .collect();
+    }
+
+    match (command, previous.len()) {
+        ("help", _) => command_candidates(),
+        ("completions", 0) => plain(&["bash", "zsh", "fish"]),
+        ("tag" | "untag", 0) => tags.iter().map(|tag| pair(tag, tag)).collect(),
+        ("move", 1) => plain(&MOVE_PLACEMENTS),
+        ("list" | "ls", _) => {
+            let mut candidates = plain(&["tag:", "status:", "text:"]);
+            candidates.extend(plain(&["--sort", "--group", "--desc", "--uuid"]));
+            candidates
+        }
+        ("add" | "man" | "export" | "import" | "report" | "sync" | "git-init" | "caldav" | "serve", _) => Vec::new(),
+        _ => task_candidates(&task_list, command),
+    }
+}
+
+// 任务ID，显示描述；完成命令只列出未完成的任务
+fn task_candidates(task_list: &TaskList, command: &str) -> Vec<Pair> {
+    task_list.get_tasks().iter()
+        .filter(|task| match command {
+            "done" | "complete" => !task.is_completed(),
+            "suspend" => *task.status() == TaskStatus::Pending,
+            _ => true,
+        })
+        .map(|task| pair(
+            &task.id().to_string(),
+            &format!("{:<4} {} ({})", task.id(), task.description(), export::status_name(task.status())),
+        ))
+        .collect()
+}
+
+fn all_tags(task_list: &TaskList) -> Vec<String> {
+    let mut tags: Vec<String> = task_list.get_tasks().iter().flat_map(|t| t.tags()).cloned().collect();
+    tags.sort();
+    tags.dedup();
+    tags
+}
+
+// 提示符前显示正在进行的计时，如 "⏱ #3 00:12:05 | "
+pub fn timer_indicator(task_list: &TaskList) -> String {
+    let now = Utc::now();
+    task_list.active_timer()
+        .and_then(|task| Some((task.id(), task.running_seconds(now)?)))
+        .map(|(id, seconds)| format!("⏱ #{} {} | ", id, time_tracking::format_clock(seconds)))
+        .unwrap_or_default()
+}
+
+pub fn print_help() {
+    println!("可用命令 (Tab 补全命令、任务ID和标签，↑/↓ 浏览历史):");
+    for (name, args, summary) in COMMANDS {
+        let usage = format!("{} {}", name, args);
+        // 中文字符按两列宽对齐
+        let width: usize = usage.chars().map(|c| if c.is_ascii() { 1 } else { 2 }).sum();
+        println!("  {}{}  {}", usage, " ".repeat(40usize.saturating_sub(width)), summary);
+    }
+}
+
+// 按空白分割，支持用单引号或双引号包含空格
+pub fn split_line(line: &str) -> Result<Vec<String>> {
+    let mut args = Vec::new();
+    let mut current = String::new();
+    let mut quote = None;
+    let mut in_word = false;
+    for c in line.chars() {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some(_), c) => current.push(c),
+            (None, '"' | '\'') => {
+                quote = Some(c);
+                in_word = true;
+            }
+            (None, c) if c.is_whitespace() => {
+                if in_word {
+                    args.push(std::mem::take(&mut current));
+                    in_word = false;
+                }
+            }
+            (None, c) => {
+                current.push(c);
+                in_word = true;
+            }
+        }
+    }
+    if quote.is_some() {
+        return Err(TodoError::InvalidInput("引号没有闭合".to_string()));
+    }
+    if in_word {
+        args.push(current);
+    }
+    Ok(args)
+}
+
+// 逐行读取命令交给 dispatch 执行，出错时显示错误并继续；exit 或 Ctrl+D 退出
+pub fn run(mut dispatch: impl FnMut(&[String]) -> Result<()>) -> Result<()> {
+    let config = Config::builder()
+        .completion_type(CompletionType::List)
+        .max_history_size(MAX_HISTORY)?
+        .history_ignore_dups(true)?
+        .history_ignore_space(true)
+        .build();
+    let mut editor: Editor<ShellHelper, DefaultHistory> = Editor::with_config(config)?;
+    editor.set_helper(Some(ShellHelper));
+    // 首次运行时还没有历史文件
+    editor.load_history(HISTORY_FILE).ok();
+
+    loop {
+        let task_list = Storage::load_tasks().unwrap_or_default();
+        let prompt = format!("{}todo> ", timer_indicator(&task_list));
+        let line = match editor.readline(&prompt) {
+            Ok(line) => line,
+            // Ctrl+C 放弃当前输入
+            Err(ReadlineError::Interrupted) => continue,
+            Err(ReadlineError::Eof) => break,
+            Err(err) => return Err(err.into()),
+        };
+        if line.trim().is_empty() {
+            continue;
+        }
+        editor.add_history_entry(line.as_str())?;
+        if let Err(e) = editor.save_history(HISTORY_FILE) {
+            eprintln!("⚠️  无法保存历史记录: {}", e);
+        }
+
+        let args = match split_line(&line) {
+            Ok(args) => args,
+            Err(e) => {
+                println!("❌ {}", e);
+                continue;
+            }
+        };
+        match args[0].as_str() {
+            "exit" | "quit" | "q" => break,
+            "help" | "?" => print_help(),
+            "clear" => editor.clear_screen()?,
+            _ => {
+                if let Err(e) = dispatch(&args) {
+                    println!("❌ {}", e);
+                }
+            }
+        }
+    }
+    println!("👋 再见！");
+    Ok(())
+}
@@ -0,0 +1,213 @@
+use chrono::{DateTime, Datelike, Duration, NaiveDate, Utc};
+use crate::task::{Task, TaskStatus};
+use crate::time_tracking;
+
+// 任务列表的效率统计
+#[derive(Debug, Clone, PartialEq)]
+pub struct Statistics {
+    pub total: usize,
+    pub pending: usize,
+    pub suspended: usize,
+    pub completed: usize,
+    // 未完成且截止日期早于今天
+    pub overdue: usize,
+    // 已完成 / 总数，没有任务时为 0
+    pub completion_rate: f64,
+    // 创建到完成的平均时长（秒）
+    pub average_lead_time: Option<i64>,
+    // 最近 7 天每天完成的任务数，从早到晚
+    pub daily_throughput: Vec<(NaiveDate, usize)>,
+    // 最近 4 周（周一开始）每周完成的任务数，从早到晚
+    pub weekly_throughput: Vec<(NaiveDate, usize)>,
+    // 截至今天（今天还没完成任务时截至昨天）连续有任务完成的天数
+    pub current_streak: usize,
+    pub longest_streak: usize,
+    pub pomodoros: u32,
+    pub tracked_seconds: i64,
+}
+
+impl Statistics {
+    pub fn compute(tasks: &[Task], now: DateTime<Utc>) -> Self {
+        let today = time_tracking::today();
+        let count = |status: TaskStatus| tasks.iter().filter(|t| *t.status() == status).count();
+        let (pending, suspended, completed) = (
+            count(TaskStatus::Pending),
+            count(TaskStatus::Suspended),
+            count(TaskStatus::Completed),
+        );
+
+        let lead_times: Vec<i64> = tasks.iter().filter_map(lead_time).collect();
+        let average_lead_time = (!lead_times.is_empty())
+            .then(|| lead_times.iter().sum::<i64>() / lead_times.len() as i64);
+
+        let completion_days: Vec<NaiveDate> = tasks.iter().filter_map(completion_day).collect();
+        let completed_on = |day: NaiveDate| completion_days.iter().filter(|d| **d == day).count();
+
+        let daily_throughput = (0..7).rev()
+            .map(|offset| today - Duration::days(offset))
+            .map(|day| (day, completed_on(day)))
+            .collect();
+
+        let this_week = today - Duration::days(i64::from(today.weekday().num_days_from_monday()));
+        let weekly_throughput = (0..4).rev()
+            .map(|offset| this_week - Duration::weeks(offset))
+            .map(|week| {
+                let done = completion_days.iter()
+                    .filter(|d| **d >= week && **d < week + Duration::weeks(1))
+                    .count();
+                (week, done)
+            })
+            .collect();
+
+        let (current_streak, longest_streak) = streaks(&completion_days, today);
+
+        Statistics {
+            total: tasks.len(),
+            pending,
+            suspended,
+            completed,
+            overdue: tasks.iter().filter(|t| t.is_overdue(today)).count(),
+            completion_rate: if tasks.is_empty() { 0.0 } else { completed as f64 / tasks.len() as f64 },
+            average_lead_time,
+            daily_throughput,
+            weekly_throughput,
+            current_streak,
+            longest_streak,
+            pomodoros: tasks.iter().map(|t| t.pomodoros()).sum(),
+            tracked_seconds: tasks.iter().map(|t| t.tracked_seconds(now)).sum(),
+        }
+    }
+}
+
+// 创建到完成的时长（秒）
+pub fn lead_time(task: &Task) -> Option<i64> {
+    let created = time_tracking::parse_time(task.created_at())?;
+    let completed = time_tracking::parse_time(task.completed_at()?)?;
+    Some((completed - created).num_seconds().max(0))
+}
+
+// 完成当天的本地日期
+pub fn completion_day(task: &Task) -> Option<NaiveDate> {
+    task.completed_at().and_then(time_tracking::local_date)
+}
+
+// (当前连续天数, 最长连续天数)
+fn streaks(days: &[NaiveDate], today: NaiveDate) -> (usize, usize) {
+    let mut days = days.to_vec();
+    days.sort();
+    days.dedup();
+
+    let mut longest = 0;
+    let mut run = 0;
+    let mut previous: Option<NaiveDate> = None;
+    for &day in &days {
+        run = match previous {
+            Some(p) if p.succ_opt() == Some(day) => run + 1,
+            _ => 1,
+        };
+        longest = longest.max(run);
+        previous = Some(day);
+    }
+
+    // 最后一段连续记录需要延续到今天或昨天才算"当前"
+    let current = match previous {
+        Some(last) if last == today || last.succ_opt() == Some(today) => run,
+        _ => 0,
+    };
+    (current, longest)
+}
+
+// 某一天新建和完成的任务数，以及当天结束时仍未完成的任务数
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DayActivity {
+    pub day: NaiveDate,
+    pub created: usize,
+    pub completed: usize,
+    pub open: usize,
+}
+
+// 最近 days 天（含今天）的每日活动，从早到晚；已删除的任务无从统计。
+// 任务不记录状态变更历史，只能根据创建时间和当前的完成时间推算：重新打开时 completed_at 被清空，
+// 因此完成后又重新打开的任务在之前的日子里仍算作未完成，也不计入当时的完成数；
+// 多次完成的任务只按最后一次完成的日期统计
+pub fn daily_activity(tasks: &[Task], days: i64) -> Vec<DayActivity> {
+    let today = time_tracking::today();
+    let dates: Vec<(Option<NaiveDate>, Option<NaiveDate>)> = tasks.iter()
+        .map(|task| (time_tracking::local_date(task.created_at()), completion_day(task)))
+        .collect();
+
+    (0..days.max(1)).rev()
+        .map(|offset| today - Duration::days(offset))
+        .map(|day| DayActivity {
+            day,
+            created: dates.iter().filter(|(created, _)| *created == Some(day)).count(),
+            completed: dates.iter().filter(|(_, completed)| *completed == Some(day)).count(),
+            open: dates.iter()
+                .filter(|(created, completed)| {
+                    created.is_some_and(|c| c <= day) && completed.is_none_or(|c| c > day)
+                })
+                .count(),
+        })
+        .collect()
+}
+
+// 各标签下的任务数，按数量从多到少；没有标签的任务计入"无标签"
+pub fn tag_distribution(tasks: &[Task]) -> Vec<(String, usize)> {
+    let mut counts: Vec<(String, usize)> = Vec::new();
+    for task in tasks {
+        let tags: Vec<&str> = if task.tags().is_empty() {
+            vec!["无标签"]
+        } else {
+            task.tags().iter().map(String::as_str).collect()
+        };
+        for tag in tags {
+            match counts.iter_mut().find(|(name, _)| name == tag) {
+                Some((_, count)) => *count += 1,
+                None => counts.push((tag.to_string(), 1)),
+            }
+        }
+    }
+    counts.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    counts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn day(offset: i64) -> NaiveDate {
+        time_tracking::today() + Duration::days(offset)
+    }
+
+    #[test]
+    fn counts_only_open_tasks_past_due_as_overdue() {
+        let mut tasks: Vec<Task> = (1..=4).map(|id| Task::new(id, format!("任务{}", id))).collect();
+        tasks[0].set_due(Some(day(-1)));
+        tasks[1].set_due(Some(day(-3)));
+        tasks[1].complete();
+        tasks[2].set_due(Some(day(0)));
+        let stats = Statistics::compute(&tasks, Utc::now());
+        assert_eq!(stats.overdue, 1);
+        assert_eq!((stats.total, stats.pending, stats.completed), (4, 3, 1));
+        assert_eq!(stats.completion_rate, 0.25);
+    }
+
+    #[test]
+    fn streaks_need_consecutive_days_up_to_yesterday() {
+        let today = day(0);
+        assert_eq!(streaks(&[day(-1), day(-2), day(-2), day(-5)], today), (2, 2));
+        assert_eq!(streaks(&[day(-2), day(-3), day(-4)], today), (0, 3));
+        assert_eq!(streaks(&[], today), (0, 0));
+    }
+
+    #[test]
+    fn daily_activity_counts_open_tasks_per_day() {
+        let mut tasks = vec![Task::new(1, "a".to_string()), Task::new(2, "b".to_string())];
+        tasks[0].complete();
+        let activity = daily_activity(&tasks, 3);
+        assert_eq!(activity.len(), 3);
+        let today = activity.last().unwrap();
+        assert_eq!((today.created, today.completed, today.open), (2, 1, 1));
+        assert_eq!(activity[0].open, 0);
+    }
+}
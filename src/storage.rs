@@ -1,32 +1,81 @@
 use crate::error::Result;
+use crate::git;
 use crate::task_list::TaskList;
 use std::fs;
 use std::path::Path;
+use std::time::SystemTime;
 
-const STORAGE_FILE: &str = "tasks.json";
+pub const STORAGE_FILE: &str = "tasks.json";
+// 升级文件格式前的备份
+const BACKUP_FILE: &str = "tasks.json.bak";
 
 pub struct Storage;
 
 impl Storage {
     
-    // 保存任务到文件
+    // 保存任务到文件，开启 git 存储时同时提交，提交说明根据修改内容生成
     pub fn save_tasks(task_list: &TaskList) -> Result<()> {
         let json = serde_json::to_string_pretty(task_list)?;
+        if !git::is_enabled() {
+            fs::write(STORAGE_FILE, json)?;
+            return Ok(());
+        }
+
+        let previous = fs::read_to_string(STORAGE_FILE).ok()
+            .and_then(|content| serde_json::from_str::<TaskList>(&content).ok())
+            .unwrap_or_default();
         fs::write(STORAGE_FILE, json)?;
-        Ok(())
+        match git::describe_changes(&previous, task_list) {
+            Some(message) => git::commit(STORAGE_FILE, &message),
+            None => Ok(()),
+        }
     }
 
-    // 从文件加载任务
+    // 从文件加载任务；旧格式的文件先备份再升级保存
     pub fn load_tasks() -> Result<TaskList> {
-        if !Path::new(STORAGE_FILE).exists() {
+        let mut task_list = Self::load_from(Path::new(STORAGE_FILE))?;
+        if Path::new(STORAGE_FILE).exists() && task_list.migrate() {
+            fs::copy(STORAGE_FILE, BACKUP_FILE)?;
+            Self::save_tasks(&task_list)?;
+            if git::is_enabled() {
+                git::commit(STORAGE_FILE, "migrate task list: assign UUIDs")?;
+            }
+        }
+        Ok(task_list)
+    }
+
+    // 从指定文件加载任务，文件不存在时返回空列表
+    pub fn load_from(path: &Path) -> Result<TaskList> {
+        if !path.exists() {
             return Ok(TaskList::new());
         }
 
-        let content = fs::read_to_string(STORAGE_FILE)?;
-        let task_list: TaskList = serde_json::from_str(&content)?;
+        let content = fs::read_to_string(path)?;
+        let mut task_list: TaskList = serde_json::from_str(&content)?;
+        task_list.normalize_order();
+        task_list.ensure_uuids();
         Ok(task_list)
     }
 
+    // 保存到指定文件，例如共享文件夹中的副本，不会提交到 git
+    pub fn save_to(task_list: &TaskList, path: &Path) -> Result<()> {
+        fs::write(path, serde_json::to_string_pretty(task_list)?)?;
+        Ok(())
+    }
+
+    // 存储文件的修改时间和大小，用于发现其他进程的修改；文件不存在时为 None
+    pub fn stamp() -> Option<(SystemTime, u64)> {
+        let metadata = fs::metadata(STORAGE_FILE).ok()?;
+        Some((metadata.modified().ok()?, metadata.len()))
+    }
+
+    // 无法读取的 tasks.json 复制一份再继续，避免之后的保存覆盖原有数据；返回备份文件名
+    pub fn back_up_unreadable() -> Result<String> {
+        let backup = format!("{}.unreadable-{}", STORAGE_FILE, chrono::Local::now().format("%Y%m%d%H%M%S"));
+        fs::copy(STORAGE_FILE, &backup)?;
+        Ok(backup)
+    }
+
     // 检查存储文件是否存在
     pub fn storage_exists() -> bool {
         Path::new(STORAGE_FILE).exists()
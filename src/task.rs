@@ -1,5 +1,11 @@
 use std::fmt;
+use std::str::FromStr;
 use serde::{Deserialize, Serialize};
+use chrono::{DateTime, NaiveDate, Utc};
+use crate::error::TodoError;
+use crate::estimate::Estimate;
+use crate::recurrence::Recurrence;
+use crate::time_tracking::{self, WorkSession};
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub enum TaskStatus {
@@ -8,34 +14,413 @@ pub enum TaskStatus {
     Completed, 
 }
 
+impl FromStr for TaskStatus {
+    type Err = TodoError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "pending" | "p" | "待处理" => Ok(TaskStatus::Pending),
+            "suspended" | "s" | "挂起" => Ok(TaskStatus::Suspended),
+            "completed" | "c" | "已完成" => Ok(TaskStatus::Completed),
+            other => Err(TodoError::InvalidInput(format!("未知的任务状态: {}", other))),
+        }
+    }
+}
+
+// 优先级，High 最高
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum Priority {
+    High,
+    Medium,
+    Low,
+}
+
+impl Priority {
+    pub const ALL: [Priority; 3] = [Priority::High, Priority::Medium, Priority::Low];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Priority::High => "高",
+            Priority::Medium => "中",
+            Priority::Low => "低",
+        }
+    }
+
+    // 用于导出和 API 的英文名
+    pub fn name(&self) -> &'static str {
+        match self {
+            Priority::High => "high",
+            Priority::Medium => "medium",
+            Priority::Low => "low",
+        }
+    }
+}
+
+impl fmt::Display for Priority {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.label())
+    }
+}
+
+impl FromStr for Priority {
+    type Err = TodoError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "high" | "h" | "高" => Ok(Priority::High),
+            "medium" | "m" | "中" => Ok(Priority::Medium),
+            "low" | "l" | "低" => Ok(Priority::Low),
+            other => Err(TodoError::InvalidInput(format!("未知的优先级: {}，可选: high、medium、low", other))),
+        }
+    }
+}
+
+// 解析截止日期: YYYY-MM-DD、today/今天、tomorrow/明天 或 +N（N 天后）
+pub fn parse_due(s: &str) -> Result<NaiveDate, TodoError> {
+    let today = time_tracking::today();
+    let value = s.trim().to_lowercase();
+    let days = match value.as_str() {
+        "today" | "今天" => Some(0),
+        "tomorrow" | "明天" => Some(1),
+        _ => value.strip_prefix('+').and_then(|n| n.trim_end_matches('d').parse::<u32>().ok()),
+    };
+    if let Some(days) = days {
+        return today.checked_add_days(chrono::Days::new(days.into()))
+            .ok_or_else(|| TodoError::InvalidInput(format!("截止日期超出范围: {}", s)));
+    }
+    NaiveDate::parse_from_str(&value, "%Y-%m-%d")
+        .map_err(|_| TodoError::InvalidInput(format!("无法识别的截止日期: {}，示例: 2025-07-02、today、tomorrow、+3", s)))
+}
+
+// 任务的可编辑字段，None 表示保持不变
+#[derive(Debug, Clone, Default)]
+pub struct TaskUpdate {
+    pub description: Option<String>,
+    pub status: Option<TaskStatus>,
+    pub tags: Option<Vec<String>>,
+    // Some(None) 表示清除预估
+    pub estimate: Option<Option<Estimate>>,
+    pub due: Option<Option<NaiveDate>>,
+    pub priority: Option<Option<Priority>>,
+    pub recurrence: Option<Option<Recurrence>>,
+    pub notes: Option<String>,
+}
+
+impl TaskUpdate {
+    pub fn is_empty(&self) -> bool {
+        self.description.is_none() && self.status.is_none() && self.tags.is_none() && self.estimate.is_none()
+            && self.due.is_none() && self.priority.is_none() && self.recurrence.is_none() && self.notes.is_none()
+    }
+}
+
+// 各字段最后一次修改的时间，多设备合并时每个字段取较新的一方
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct FieldTimes {
+    pub description: String,
+    pub status: String,
+    pub tags: String,
+    pub estimate: String,
+    pub position: String,
+    // 旧版本没有截止日期和优先级
+    #[serde(default)]
+    pub due: String,
+    #[serde(default)]
+    pub priority: String,
+    #[serde(default)]
+    pub recurrence: String,
+    #[serde(default)]
+    pub notes: String,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Task {
     id: usize, 
+    // 全局唯一标识，不同设备上的任务ID可能重复
+    #[serde(default)]
+    uuid: String,
     description: String, 
     status: TaskStatus,
     // ISO 8601: 2025-07-02T16:05:25
     created_at: String,
     completed_at: Option<String>,
+    // 旧版本的 tasks.json 没有该字段
+    #[serde(default)]
+    tags: Vec<String>,
+    // 手动排序位置，从 0 开始，由 TaskList 维护
+    #[serde(default)]
+    position: usize,
+    // 计时记录，最多只有最后一段在进行中
+    #[serde(default)]
+    sessions: Vec<WorkSession>,
+    // 已完成的番茄数
+    #[serde(default)]
+    pomodoros: u32,
+    #[serde(default)]
+    estimate: Option<Estimate>,
+    #[serde(default)]
+    due: Option<NaiveDate>,
+    #[serde(default)]
+    priority: Option<Priority>,
+    // 重复周期，对应 iCalendar 的 RRULE；只保存，不会自动生成下一次任务
+    #[serde(default)]
+    recurrence: Option<Recurrence>,
+    // 备注，可以有多行
+    #[serde(default)]
+    notes: String,
+    #[serde(default)]
+    modified: FieldTimes,
 }
 
 impl Task {
     pub fn new(id: usize, description: String) -> Self {
-        let created_at = chrono::Utc::now().format("%Y-%m-%d %H:%M:%S").to_string();
+        let created_at = time_tracking::now_string();
+        let modified = time_tracking::clock_string();
         Task {
             id,
+            uuid: new_uuid(),
             description,
             status: TaskStatus::Pending,
             created_at,
-            completed_at: None
+            completed_at: None,
+            tags: Vec::new(),
+            position: 0,
+            sessions: Vec::new(),
+            pomodoros: 0,
+            estimate: None,
+            due: None,
+            priority: None,
+            recurrence: None,
+            notes: String::new(),
+            modified: FieldTimes {
+                description: modified.clone(),
+                status: modified.clone(),
+                tags: modified.clone(),
+                estimate: modified.clone(),
+                position: modified.clone(),
+                due: modified.clone(),
+                priority: modified.clone(),
+                recurrence: modified.clone(),
+                notes: modified,
+            },
         }
     }
 
     pub fn suspend(&mut self) {
-        self.status = TaskStatus::Suspended;
+        self.set_status(TaskStatus::Suspended);
     }
 
     pub fn complete(&mut self) {
-        self.status = TaskStatus::Completed;
+        self.set_status(TaskStatus::Completed);
+    }
+
+    // 修改状态，同时维护完成时间；离开待处理状态时停止计时
+    pub fn set_status(&mut self, status: TaskStatus) {
+        if status != TaskStatus::Completed {
+            self.completed_at = None;
+        } else if self.completed_at.is_none() {
+            self.completed_at = Some(time_tracking::now_string());
+        }
+        if status != TaskStatus::Pending {
+            self.stop_timer();
+        }
+        if status != self.status {
+            self.modified.status = time_tracking::clock_string();
+        }
+        self.status = status;
+    }
+
+    // 开始计时，已在计时时返回 false
+    pub fn start_timer(&mut self) -> bool {
+        if self.is_timer_running() {
+            return false;
+        }
+        self.sessions.push(WorkSession::start_now());
+        true
+    }
+
+    // 停止计时，没有进行中的计时时返回 false
+    pub fn stop_timer(&mut self) -> bool {
+        match self.sessions.last_mut().filter(|session| session.is_running()) {
+            Some(session) => {
+                session.end = Some(time_tracking::now_string());
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn is_timer_running(&self) -> bool {
+        self.sessions.last().is_some_and(|session| session.is_running())
+    }
+
+    pub fn set_estimate(&mut self, estimate: Option<Estimate>) {
+        if estimate != self.estimate {
+            self.modified.estimate = time_tracking::clock_string();
+        }
+        self.estimate = estimate;
+    }
+
+    pub fn estimate(&self) -> Option<Estimate> {
+        self.estimate
+    }
+
+    pub fn set_due(&mut self, due: Option<NaiveDate>) {
+        if due != self.due {
+            self.modified.due = time_tracking::clock_string();
+        }
+        self.due = due;
+    }
+
+    pub fn due(&self) -> Option<NaiveDate> {
+        self.due
+    }
+
+    // 未完成且截止日期早于今天
+    pub fn is_overdue(&self, today: NaiveDate) -> bool {
+        !self.is_completed() && self.due.is_some_and(|due| due < today)
+    }
+
+    pub fn set_priority(&mut self, priority: Option<Priority>) {
+        if priority != self.priority {
+            self.modified.priority = time_tracking::clock_string();
+        }
+        self.priority = priority;
+    }
+
+    pub fn priority(&self) -> Option<Priority> {
+        self.priority
+    }
+
+    pub fn set_recurrence(&mut self, recurrence: Option<Recurrence>) {
+        if recurrence != self.recurrence {
+            self.modified.recurrence = time_tracking::clock_string();
+        }
+        self.recurrence = recurrence;
+    }
+
+    pub fn recurrence(&self) -> Option<Recurrence> {
+        self.recurrence
+    }
+
+    pub fn set_notes(&mut self, notes: String) {
+        if notes != self.notes {
+            self.modified.notes = time_tracking::clock_string();
+        }
+        self.notes = notes;
+    }
+
+    pub fn notes(&self) -> &str {
+        &self.notes
+    }
+
+    // 各字段最后一次修改的时间 (UTC，精确到秒)，用于合并时比较；只保存最后一次，不是完整的修改历史。
+    // 旧版本文件中没有记录的字段为空字符串
+    pub fn last_modified(&self) -> [(&'static str, String); 9] {
+        let second = |time: &str| time.get(..19).unwrap_or(time).to_string();
+        [
+            ("描述", second(&self.modified.description)),
+            ("状态", second(&self.modified.status)),
+            ("标签", second(&self.modified.tags)),
+            ("预估", second(&self.modified.estimate)),
+            ("截止日期", second(&self.modified.due)),
+            ("优先级", second(&self.modified.priority)),
+            ("重复", second(&self.modified.recurrence)),
+            ("备注", second(&self.modified.notes)),
+            ("顺序", second(&self.modified.position)),
+        ]
+    }
+
+    pub fn add_pomodoro(&mut self) {
+        self.pomodoros += 1;
+    }
+
+    pub fn pomodoros(&self) -> u32 {
+        self.pomodoros
+    }
+
+    pub fn sessions(&self) -> &[WorkSession] {
+        &self.sessions
+    }
+
+    // 累计用时（秒），包含进行中的计时
+    pub fn tracked_seconds(&self, now: DateTime<Utc>) -> i64 {
+        self.sessions.iter().map(|session| session.seconds(now)).sum()
+    }
+
+    // 某一天（本地日期）的用时（秒）
+    pub fn tracked_seconds_on(&self, day: NaiveDate, now: DateTime<Utc>) -> i64 {
+        self.sessions.iter().map(|session| session.seconds_on(day, now)).sum()
+    }
+
+    // 当前计时已进行的秒数
+    pub fn running_seconds(&self, now: DateTime<Utc>) -> Option<i64> {
+        self.sessions.last()
+            .filter(|session| session.is_running())
+            .map(|session| session.seconds(now))
+    }
+
+    pub fn set_description(&mut self, description: String) {
+        if description != self.description {
+            self.modified.description = time_tracking::clock_string();
+        }
+        self.description = description;
+    }
+
+    // 导入时保留原有的时间和计时记录
+    pub(crate) fn restore_history(&mut self, created_at: Option<String>, completed_at: Option<String>, sessions: Vec<WorkSession>) {
+        if let Some(created_at) = created_at {
+            self.created_at = created_at;
+        }
+        if self.status == TaskStatus::Completed && completed_at.is_some() {
+            self.completed_at = completed_at;
+        }
+        self.sessions = sessions;
+    }
+
+    // 同步时保留另一端记录的完成时间，而不是用本次修改的时间
+    pub(crate) fn restore_completed_at(&mut self, completed_at: String) {
+        if self.status == TaskStatus::Completed {
+            self.completed_at = Some(completed_at);
+        }
+    }
+
+    pub(crate) fn set_pomodoros(&mut self, pomodoros: u32) {
+        self.pomodoros = pomodoros;
+    }
+
+    // 添加标签，已存在或为空时返回 false
+    pub fn add_tag(&mut self, tag: &str) -> bool {
+        let tag = tag.trim();
+        if tag.is_empty() || self.has_tag(tag) {
+            return false;
+        }
+        self.tags.push(tag.to_string());
+        self.modified.tags = time_tracking::clock_string();
+        true
+    }
+
+    pub fn remove_tag(&mut self, tag: &str) -> bool {
+        let len = self.tags.len();
+        self.tags.retain(|t| t != tag.trim());
+        if self.tags.len() == len {
+            return false;
+        }
+        self.modified.tags = time_tracking::clock_string();
+        true
+    }
+
+    pub fn set_tags(&mut self, tags: Vec<String>) {
+        let previous = std::mem::take(&mut self.tags);
+        for tag in tags {
+            self.add_tag(&tag);
+        }
+        if self.tags != previous {
+            self.modified.tags = time_tracking::clock_string();
+        }
+    }
+
+    pub fn has_tag(&self, tag: &str) -> bool {
+        self.tags.iter().any(|t| t == tag)
     }
 
     pub fn is_completed(&self) -> bool {
@@ -45,6 +430,157 @@ impl Task {
     pub fn id(&self) -> usize {
         self.id
     }
+
+    pub(crate) fn set_id(&mut self, id: usize) {
+        self.id = id;
+    }
+
+    pub fn uuid(&self) -> &str {
+        &self.uuid
+    }
+
+    pub(crate) fn set_uuid(&mut self, uuid: String) {
+        self.uuid = uuid;
+    }
+
+    // 旧版本的任务没有 UUID，加载时由ID、创建时间和描述推导，
+    // 各设备分别升级同一份旧文件时得到相同的 UUID，合并时不会重复
+    pub(crate) fn ensure_uuid(&mut self) {
+        if self.uuid.is_empty() {
+            let name = format!("{}\n{}\n{}", self.id, self.created_at, self.description);
+            self.uuid = uuid::Uuid::new_v5(&MIGRATION_NAMESPACE, name.as_bytes()).to_string();
+        }
+    }
+
+    // 合并另一设备上的同一任务：各字段取修改时间较新的一方，
+    // 计时记录取并集，番茄数取较大值；返回是否有变化
+    pub(crate) fn merge_from(&mut self, other: &Task) -> bool {
+        let mut changed = false;
+        if newer(&self.modified.description, &self.description, &other.modified.description, &other.description) {
+            self.description = other.description.clone();
+            self.modified.description = other.modified.description.clone();
+            changed = true;
+        }
+        if newer(&self.modified.status, &self.status, &other.modified.status, &other.status) {
+            self.status = other.status.clone();
+            self.completed_at = other.completed_at.clone();
+            self.modified.status = other.modified.status.clone();
+            changed = true;
+        }
+        if newer(&self.modified.tags, &self.tags, &other.modified.tags, &other.tags) {
+            self.tags = other.tags.clone();
+            self.modified.tags = other.modified.tags.clone();
+            changed = true;
+        }
+        if newer(&self.modified.estimate, &self.estimate, &other.modified.estimate, &other.estimate) {
+            self.estimate = other.estimate;
+            self.modified.estimate = other.modified.estimate.clone();
+            changed = true;
+        }
+        if newer(&self.modified.due, &self.due, &other.modified.due, &other.due) {
+            self.due = other.due;
+            self.modified.due = other.modified.due.clone();
+            changed = true;
+        }
+        if newer(&self.modified.priority, &self.priority, &other.modified.priority, &other.priority) {
+            self.priority = other.priority;
+            self.modified.priority = other.modified.priority.clone();
+            changed = true;
+        }
+        if newer(&self.modified.recurrence, &self.recurrence, &other.modified.recurrence, &other.recurrence) {
+            self.recurrence = other.recurrence;
+            self.modified.recurrence = other.modified.recurrence.clone();
+            changed = true;
+        }
+        if newer(&self.modified.notes, &self.notes, &other.modified.notes, &other.notes) {
+            self.notes = other.notes.clone();
+            self.modified.notes = other.modified.notes.clone();
+            changed = true;
+        }
+        if newer(&self.modified.position, &self.position, &other.modified.position, &other.position) {
+            self.position = other.position;
+            self.modified.position = other.modified.position.clone();
+            changed = true;
+        }
+        if other.created_at < self.created_at {
+            self.created_at = other.created_at.clone();
+            changed = true;
+        }
+        if other.pomodoros > self.pomodoros {
+            self.pomodoros = other.pomodoros;
+            changed = true;
+        }
+        for session in &other.sessions {
+            match self.sessions.iter_mut().find(|s| s.start == session.start) {
+                Some(existing) if existing.is_running() && !session.is_running() => {
+                    existing.end = session.end.clone();
+                    changed = true;
+                }
+                Some(_) => {}
+                None => {
+                    self.sessions.push(session.clone());
+                    changed = true;
+                }
+            }
+        }
+        self.sessions.sort_by(|a, b| a.start.cmp(&b.start));
+        if self.status != TaskStatus::Pending {
+            self.stop_timer();
+        }
+        changed
+    }
+
+    pub fn description(&self) -> &str {
+        &self.description
+    }
+
+    pub fn status(&self) -> &TaskStatus {
+        &self.status
+    }
+
+    pub fn created_at(&self) -> &str {
+        &self.created_at
+    }
+
+    pub fn completed_at(&self) -> Option<&str> {
+        self.completed_at.as_deref()
+    }
+
+    pub fn tags(&self) -> &[String] {
+        &self.tags
+    }
+
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    pub(crate) fn set_position(&mut self, position: usize) {
+        if position != self.position {
+            self.modified.position = time_tracking::clock_string();
+        }
+        self.position = position;
+    }
+}
+
+// 升级旧文件时推导 UUID 所用的命名空间，不能修改
+const MIGRATION_NAMESPACE: uuid::Uuid = uuid::Uuid::from_u128(0x6f1c_2a4e_8b3d_4c7a_9e51_d2f0_a8b6_c314);
+
+fn new_uuid() -> String {
+    uuid::Uuid::new_v4().to_string()
+}
+
+// 对方的值修改时间更晚时返回 true；时间相同时比较值本身，保证两边合并结果一致
+fn newer<T: Serialize + PartialEq>(mine_time: &str, mine: &T, theirs_time: &str, theirs: &T) -> bool {
+    if mine == theirs {
+        return false;
+    }
+    match theirs_time.cmp(mine_time) {
+        std::cmp::Ordering::Equal => {
+            let encode = |value: &T| serde_json::to_string(value).unwrap_or_default();
+            encode(theirs) > encode(mine)
+        }
+        ordering => ordering.is_gt(),
+    }
 }
 
 impl fmt::Display for Task {
@@ -54,8 +590,135 @@ impl fmt::Display for Task {
             TaskStatus::Suspended => "[.]suspended",
             TaskStatus::Completed => "[✓]completed",
         };
-        write!(f, "{} - id: {} - {}", status_symbol, self.id, self.description)
+        write!(f, "{} - id: {} - {}", status_symbol, self.id, self.description)?;
+        if let Some(priority) = self.priority {
+            write!(f, " ❗{}", priority)?;
+        }
+        if let Some(due) = self.due {
+            write!(f, " 📅 {}", due)?;
+        }
+        if let Some(recurrence) = self.recurrence {
+            write!(f, " 🔁 {}", recurrence.label())?;
+        }
+        if !self.tags.is_empty() {
+            write!(f, " 🏷 {}", self.tags.join(", "))?;
+        }
+        if let Some(estimate) = self.estimate {
+            write!(f, " ⌛ {}", estimate)?;
+        }
+        if self.pomodoros > 0 {
+            write!(f, " 🍅 {}", self.pomodoros)?;
+        }
+        let tracked = self.tracked_seconds(Utc::now());
+        if self.is_timer_running() {
+            write!(f, " ⏱ {} (计时中)", time_tracking::format_duration(tracked))?;
+        } else if tracked > 0 {
+            write!(f, " ⏱ {}", time_tracking::format_duration(tracked))?;
+        }
+        Ok(())
     }
 }
 
 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn suspend_stops_running_timer() {
+        let mut task = Task::new(1, "写报告".to_string());
+        assert!(task.start_timer());
+        task.suspend();
+        assert_eq!(*task.status(), TaskStatus::Suspended);
+        assert!(!task.is_timer_running());
+        assert_eq!(task.sessions().len(), 1);
+    }
+
+    #[test]
+    fn status_changes_maintain_completed_at() {
+        let mut task = Task::new(1, "写报告".to_string());
+        task.complete();
+        assert!(task.completed_at().is_some());
+        task.suspend();
+        assert!(task.completed_at().is_none());
+        task.set_status(TaskStatus::Pending);
+        assert!(task.completed_at().is_none());
+    }
+
+    #[test]
+    fn last_modified_tracks_each_field() {
+        let mut task = Task::new(1, "写报告".to_string());
+        task.modified.notes = "2020-01-01 00:00:00.000000".to_string();
+        let before = task.last_modified();
+        assert_eq!(before[7], ("备注", "2020-01-01 00:00:00".to_string()));
+        task.set_notes("第二节要补数据".to_string());
+        let after = task.last_modified();
+        assert!(after[7].1 > before[7].1);
+        // 其他字段不受影响
+        assert_eq!(after[..7], before[..7]);
+    }
+
+    #[test]
+    fn merge_takes_the_newer_notes() {
+        let mut mine = Task::new(1, "写报告".to_string());
+        let mut theirs = mine.clone();
+        theirs.set_notes("另一台设备上写的备注".to_string());
+        assert!(mine.merge_from(&theirs));
+        assert_eq!(mine.notes(), "另一台设备上写的备注");
+    }
+
+    #[test]
+    fn tags_are_trimmed_and_unique() {
+        let mut task = Task::new(1, "写报告".to_string());
+        task.set_tags(vec![" work ".to_string(), "work".to_string(), "".to_string(), "home".to_string()]);
+        assert_eq!(task.tags(), ["work", "home"]);
+        assert!(!task.add_tag("home"));
+        assert!(task.remove_tag("work"));
+        assert_eq!(task.tags(), ["home"]);
+    }
+
+    #[test]
+    fn parses_status_aliases() {
+        assert_eq!("P".parse::<TaskStatus>().unwrap(), TaskStatus::Pending);
+        assert_eq!("挂起".parse::<TaskStatus>().unwrap(), TaskStatus::Suspended);
+        assert_eq!(" completed ".parse::<TaskStatus>().unwrap(), TaskStatus::Completed);
+        assert!("done".parse::<TaskStatus>().is_err());
+    }
+
+    #[test]
+    fn parses_due_dates_and_priorities() {
+        let today = time_tracking::today();
+        assert_eq!(parse_due("2025-07-02").unwrap(), NaiveDate::from_ymd_opt(2025, 7, 2).unwrap());
+        assert_eq!(parse_due("today").unwrap(), today);
+        assert_eq!(parse_due("+3").unwrap(), today + chrono::Days::new(3));
+        assert!(parse_due("2025-13-01").is_err());
+        assert!(parse_due("next week").is_err());
+        assert_eq!("H".parse::<Priority>().unwrap(), Priority::High);
+        assert_eq!("低".parse::<Priority>().unwrap(), Priority::Low);
+        assert!("urgent".parse::<Priority>().is_err());
+    }
+
+    #[test]
+    fn old_files_without_due_or_priority_still_load() {
+        let json = r#"{"id":1,"description":"写报告","status":"Pending","created_at":"2025-07-02 16:05:25","completed_at":null,
+            "modified":{"description":"a","status":"a","tags":"a","estimate":"a","position":"a"}}"#;
+        let task: Task = serde_json::from_str(json).unwrap();
+        assert_eq!(task.due(), None);
+        assert_eq!(task.priority(), None);
+    }
+
+    #[test]
+    fn overdue_only_for_open_tasks_before_today() {
+        let today = NaiveDate::from_ymd_opt(2025, 7, 2).unwrap();
+        let mut task = Task::new(1, "写报告".to_string());
+        assert!(!task.is_overdue(today));
+        task.set_due(today.pred_opt());
+        assert!(task.is_overdue(today));
+        task.set_due(Some(today));
+        assert!(!task.is_overdue(today));
+        task.set_due(today.pred_opt());
+        task.complete();
+        assert!(!task.is_overdue(today));
+    }
+}
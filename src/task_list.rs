@@ -1,11 +1,54 @@
+use std::collections::HashSet;
 use serde::{Deserialize, Serialize};
-use crate::task::Task;
+use crate::error::{Result, TodoError};
+use chrono::{DateTime, NaiveDate, Utc};
+use crate::import::ImportedTask;
+use crate::task::{Task, TaskStatus, TaskUpdate};
+use crate::time_tracking;
+
+// 移动任务时的目标位置
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Placement {
+    Before(usize),
+    After(usize),
+    Top,
+    Bottom,
+}
 
 #[derive(Debug, Clone, Serialize, Deserialize)] // 添加Clone
 pub struct TaskList {
     pub name: String, 
     pub tasks: Vec<Task>,
     next_id: usize,
+    // 已删除任务的记录，合并时删除优先于另一边的修改
+    #[serde(default)]
+    deleted: Vec<Tombstone>,
+    // 文件格式版本，旧文件为 0
+    #[serde(default)]
+    version: u32,
+}
+
+// 1: 每个任务都有 UUID
+pub const FORMAT_VERSION: u32 = 1;
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Tombstone {
+    pub uuid: String,
+    pub deleted_at: String,
+}
+
+// 合并另一份任务列表的结果
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct MergeReport {
+    pub added: usize,
+    pub updated: usize,
+    pub removed: usize,
+}
+
+impl Default for TaskList {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl TaskList {
@@ -14,16 +57,41 @@ impl TaskList {
             name: "Task List".to_string(), 
             tasks: Vec::new(), 
             next_id: 1, 
+            deleted: Vec::new(),
+            version: FORMAT_VERSION,
         }
     }
     
     pub fn add_task(&mut self, description: String) -> usize{
         let id = self.next_id;
-        self.tasks.push(Task::new(id, description));
+        let mut task = Task::new(id, description);
+        task.set_position(self.tasks.len());
+        self.tasks.push(task);
         self.next_id += 1;
         id
     }
     
+    // 添加导入的任务，分配新的ID；UUID 与已有任务不重复时保留
+    pub fn import_task(&mut self, imported: ImportedTask) -> usize {
+        let uuid = imported.uuid.filter(|uuid| self.find_by_uuid(uuid).is_none());
+        let id = self.add_task(imported.description);
+        if let Some(task) = self.tasks.last_mut() {
+            if let Some(uuid) = uuid {
+                task.set_uuid(uuid);
+            }
+            task.set_status(imported.status);
+            task.set_tags(imported.tags);
+            task.set_estimate(imported.estimate);
+            task.set_due(imported.due);
+            task.set_priority(imported.priority);
+            task.set_recurrence(imported.recurrence);
+            task.set_notes(imported.notes);
+            task.set_pomodoros(imported.pomodoros);
+            task.restore_history(imported.created_at, imported.completed_at, imported.sessions);
+        }
+        id
+    }
+    
     // 先用bool返回值占位，后期换成自定义错误
     pub fn complete_task(&mut self, id: usize) -> bool {
         match self.tasks.iter_mut().find(|task| task.id() == id) { 
@@ -46,15 +114,438 @@ impl TaskList {
         let index = self.tasks.iter().position(|task| task.id() == id);
         match index { 
             Some(i) => {
-                self.tasks.remove(i);
+                let task = self.tasks.remove(i);
+                self.record_deletion(&task);
+                self.renumber_positions();
                 true
             }, 
             None => false
         }
     }
 
+    // 编辑任务：先校验全部字段，再一次性写入，失败时任务保持原样
+    pub fn update_task(&mut self, id: usize, update: TaskUpdate) -> Result<()> {
+        let description = match update.description {
+            Some(description) => {
+                let description = description.trim().to_string();
+                if description.is_empty() {
+                    return Err(TodoError::InvalidInput("任务描述不能为空".to_string()));
+                }
+                Some(description)
+            }
+            None => None,
+        };
+
+        let task = self.tasks.iter_mut()
+            .find(|task| task.id() == id)
+            .ok_or(TodoError::TaskNotFound(id))?;
+
+        if let Some(description) = description {
+            task.set_description(description);
+        }
+        if let Some(status) = update.status {
+            task.set_status(status);
+        }
+        if let Some(tags) = update.tags {
+            task.set_tags(tags);
+        }
+        if let Some(estimate) = update.estimate {
+            task.set_estimate(estimate);
+        }
+        if let Some(due) = update.due {
+            task.set_due(due);
+        }
+        if let Some(priority) = update.priority {
+            task.set_priority(priority);
+        }
+        if let Some(recurrence) = update.recurrence {
+            task.set_recurrence(recurrence);
+        }
+        if let Some(notes) = update.notes {
+            task.set_notes(notes.trim_end().to_string());
+        }
+        Ok(())
+    }
+
+    // 批量操作：任一ID不存在时整体失败，不修改任何任务
+    pub fn complete_tasks(&mut self, ids: &[usize]) -> Result<()> {
+        self.for_each_task(ids, |task| task.complete())
+    }
+
+    pub fn suspend_tasks(&mut self, ids: &[usize]) -> Result<()> {
+        self.for_each_task(ids, |task| task.suspend())
+    }
+
+    pub fn tag_tasks(&mut self, ids: &[usize], tag: &str) -> Result<()> {
+        if tag.trim().is_empty() {
+            return Err(TodoError::InvalidInput("标签不能为空".to_string()));
+        }
+        self.for_each_task(ids, |task| { task.add_tag(tag); })
+    }
+
+    pub fn untag_tasks(&mut self, ids: &[usize], tag: &str) -> Result<()> {
+        self.for_each_task(ids, |task| { task.remove_tag(tag); })
+    }
+
+    pub fn delete_tasks(&mut self, ids: &[usize]) -> Result<()> {
+        self.ensure_exist(ids)?;
+        let (deleted, kept): (Vec<Task>, Vec<Task>) = std::mem::take(&mut self.tasks)
+            .into_iter()
+            .partition(|task| ids.contains(&task.id()));
+        self.tasks = kept;
+        for task in &deleted {
+            self.record_deletion(task);
+        }
+        self.renumber_positions();
+        Ok(())
+    }
+
+    // 手动调整任务顺序
+    pub fn move_task(&mut self, id: usize, placement: Placement) -> Result<()> {
+        if let Placement::Before(target) | Placement::After(target) = placement {
+            if target == id {
+                return Err(TodoError::InvalidInput("不能相对自身移动任务".to_string()));
+            }
+            self.ensure_exist(&[target])?;
+        }
+        let index = self.tasks.iter()
+            .position(|task| task.id() == id)
+            .ok_or(TodoError::TaskNotFound(id))?;
+
+        let task = self.tasks.remove(index);
+        let index = match placement {
+            Placement::Top => 0,
+            Placement::Bottom => self.tasks.len(),
+            Placement::Before(target) => self.index_of(target),
+            Placement::After(target) => self.index_of(target) + 1,
+        };
+        self.tasks.insert(index, task);
+        self.renumber_positions();
+        Ok(())
+    }
+
+    // 按保存的位置排序并重新编号，兼容没有位置字段的旧文件
+    pub fn normalize_order(&mut self) {
+        self.tasks.sort_by_key(|task| task.position());
+        self.renumber_positions();
+    }
+
+    // 旧版本的任务没有 UUID，加载时补上
+    pub fn ensure_uuids(&mut self) {
+        for task in &mut self.tasks {
+            task.ensure_uuid();
+        }
+    }
+
+    // 升级旧格式的文件，返回是否需要保存
+    pub fn migrate(&mut self) -> bool {
+        if self.version >= FORMAT_VERSION {
+            return false;
+        }
+        self.ensure_uuids();
+        self.version = FORMAT_VERSION;
+        true
+    }
+
+    pub fn find_by_uuid(&self, uuid: &str) -> Option<&Task> {
+        self.tasks.iter().find(|task| task.uuid() == uuid)
+    }
+
+    // 按完整 UUID 或唯一的前缀查找任务ID，前缀至少 4 位
+    pub fn resolve_uuid(&self, prefix: &str) -> Result<usize> {
+        let prefix = prefix.trim().to_lowercase();
+        if prefix.len() < 4 {
+            return Err(TodoError::InvalidInput("UUID 前缀至少需要 4 位".to_string()));
+        }
+        let mut matches = self.tasks.iter().filter(|task| task.uuid().starts_with(&prefix));
+        match (matches.next(), matches.next()) {
+            (Some(task), None) => Ok(task.id()),
+            (Some(_), Some(_)) => Err(TodoError::InvalidInput(format!("UUID 前缀 {} 匹配多个任务", prefix))),
+            (None, _) => Err(TodoError::InvalidInput(format!("没有 UUID 为 {} 的任务", prefix))),
+        }
+    }
+
+    // 与另一台设备上的任务列表合并，按 UUID 对应任务，结果与合并顺序无关
+    // 新任务的ID与本地冲突时重新分配
+    pub fn merge(&mut self, other: &TaskList) -> MergeReport {
+        let mut report = MergeReport::default();
+        for tombstone in &other.deleted {
+            match self.deleted.iter_mut().find(|t| t.uuid == tombstone.uuid) {
+                Some(existing) if existing.deleted_at < tombstone.deleted_at => existing.deleted_at = tombstone.deleted_at.clone(),
+                Some(_) => {}
+                None => self.deleted.push(tombstone.clone()),
+            }
+        }
+        let deleted: HashSet<String> = self.deleted.iter().map(|t| t.uuid.clone()).collect();
+
+        for theirs in other.tasks.iter().filter(|t| !deleted.contains(t.uuid())) {
+            match self.tasks.iter_mut().find(|t| t.uuid() == theirs.uuid()) {
+                Some(mine) => {
+                    if mine.merge_from(theirs) {
+                        report.updated += 1;
+                    }
+                }
+                None => {
+                    let mut task = theirs.clone();
+                    if self.get_task(task.id()).is_some() {
+                        task.set_id(self.next_id);
+                    }
+                    self.next_id = self.next_id.max(task.id() + 1);
+                    self.tasks.push(task);
+                    report.added += 1;
+                }
+            }
+        }
+
+        let before = self.tasks.len();
+        self.tasks.retain(|task| !deleted.contains(task.uuid()));
+        report.removed = before - self.tasks.len();
+
+        // 位置相同时按 UUID 排序，两边得到相同的顺序
+        self.tasks.sort_by(|a, b| a.position().cmp(&b.position()).then_with(|| a.uuid().cmp(b.uuid())));
+        self.renumber_positions();
+        report
+    }
+
+    fn record_deletion(&mut self, task: &Task) {
+        self.deleted.push(Tombstone {
+            uuid: task.uuid().to_string(),
+            deleted_at: time_tracking::clock_string(),
+        });
+    }
+
+    fn renumber_positions(&mut self) {
+        for (position, task) in self.tasks.iter_mut().enumerate() {
+            task.set_position(position);
+        }
+    }
+
+    fn index_of(&self, id: usize) -> usize {
+        self.tasks.iter().position(|task| task.id() == id).unwrap_or(self.tasks.len())
+    }
+
+    fn for_each_task(&mut self, ids: &[usize], mut f: impl FnMut(&mut Task)) -> Result<()> {
+        self.ensure_exist(ids)?;
+        for task in self.tasks.iter_mut().filter(|task| ids.contains(&task.id())) {
+            f(task);
+        }
+        Ok(())
+    }
+
+    fn ensure_exist(&self, ids: &[usize]) -> Result<()> {
+        match ids.iter().find(|&&id| self.get_task(id).is_none()) {
+            Some(&id) => Err(TodoError::TaskNotFound(id)),
+            None => Ok(()),
+        }
+    }
+
+    // 开始计时，同一时间只有一个任务在计时；返回被暂停的任务ID
+    pub fn start_timer(&mut self, id: usize) -> Result<Option<usize>> {
+        let task = self.get_task(id).ok_or(TodoError::TaskNotFound(id))?;
+        if *task.status() != TaskStatus::Pending {
+            return Err(TodoError::InvalidInput(format!("任务 #{} 不是待处理状态，不能计时", id)));
+        }
+        if task.is_timer_running() {
+            return Ok(None);
+        }
+
+        let paused = self.stop_timer();
+        if let Some(task) = self.tasks.iter_mut().find(|task| task.id() == id) {
+            task.start_timer();
+        }
+        Ok(paused)
+    }
+
+    // 停止当前计时，返回停止的任务ID
+    pub fn stop_timer(&mut self) -> Option<usize> {
+        self.tasks.iter_mut()
+            .find(|task| task.is_timer_running())
+            .map(|task| {
+                task.stop_timer();
+                task.id()
+            })
+    }
+
+    pub fn active_timer(&self) -> Option<&Task> {
+        self.tasks.iter().find(|task| task.is_timer_running())
+    }
+
+    // 记录一个完成的番茄
+    pub fn record_pomodoro(&mut self, id: usize) -> Result<()> {
+        self.for_each_task(&[id], |task| task.add_pomodoro())
+    }
+
+    pub fn total_pomodoros(&self) -> u32 {
+        self.tasks.iter().map(|task| task.pomodoros()).sum()
+    }
+
+    // 某一天所有任务的用时（秒）
+    pub fn tracked_seconds_on(&self, day: NaiveDate, now: DateTime<Utc>) -> i64 {
+        self.tasks.iter().map(|task| task.tracked_seconds_on(day, now)).sum()
+    }
+
+    // 返回满足条件的任务ID，按列表顺序
+    pub fn find_ids(&self, predicate: impl Fn(&Task) -> bool) -> Vec<usize> {
+        self.tasks.iter().filter(|task| predicate(task)).map(|task| task.id()).collect()
+    }
+
+    pub fn get_task(&self, id: usize) -> Option<&Task> {
+        self.tasks.iter().find(|task| task.id() == id)
+    }
+
     // 获取所有任务
     pub fn get_tasks(&self) -> &Vec<Task> {
         &self.tasks
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    // 用 JSON 构造任务，便于指定 UUID 和各字段的修改时间
+    fn task(id: usize, uuid: &str, description: &str, status: &str, modified: &str) -> serde_json::Value {
+        json!({
+            "id": id,
+            "uuid": uuid,
+            "description": description,
+            "status": status,
+            "created_at": "2025-01-01T09:00:00",
+            "completed_at": if status == "Completed" { json!("2025-01-02T09:00:00") } else { json!(null) },
+            "tags": [],
+            "position": id - 1,
+            "modified": {
+                "description": modified,
+                "status": modified,
+                "tags": modified,
+                "estimate": modified,
+                "position": modified,
+            },
+        })
+    }
+
+    fn list(tasks: Vec<serde_json::Value>, deleted: Vec<(&str, &str)>) -> TaskList {
+        let next_id = tasks.iter().filter_map(|t| t["id"].as_u64()).max().unwrap_or(0) + 1;
+        let deleted: Vec<_> = deleted.into_iter().map(|(uuid, at)| json!({ "uuid": uuid, "deleted_at": at })).collect();
+        serde_json::from_value(json!({
+            "name": "Task List",
+            "tasks": tasks,
+            "next_id": next_id,
+            "deleted": deleted,
+            "version": FORMAT_VERSION,
+        })).unwrap()
+    }
+
+    fn descriptions(task_list: &TaskList) -> Vec<(usize, &str)> {
+        task_list.get_tasks().iter().map(|t| (t.id(), t.description())).collect()
+    }
+
+    #[test]
+    fn merge_takes_newer_value_per_field() {
+        let mut mine = list(vec![task(1, "u1", "本地描述", "Pending", "2025-01-01 10:00:00")], vec![]);
+        let mut theirs = mine.clone();
+        // 本地改了描述 (较新)，对方改了状态 (较新)
+        mine.tasks[0].set_description("本地描述 v2".to_string());
+        theirs.tasks[0].set_status(TaskStatus::Completed);
+        theirs.tasks[0].set_description("旧的对方描述".to_string());
+        mine.tasks[0].set_description("本地描述 v3".to_string());
+
+        let report = mine.merge(&theirs);
+        let task = &mine.get_tasks()[0];
+        assert_eq!(report, MergeReport { added: 0, updated: 1, removed: 0 });
+        assert_eq!(task.description(), "本地描述 v3");
+        assert_eq!(*task.status(), TaskStatus::Completed);
+        assert!(task.completed_at().is_some());
+    }
+
+    #[test]
+    fn merge_is_symmetric_for_equal_times() {
+        let time = "2025-01-01 10:00:00";
+        let a = list(vec![task(1, "u1", "甲", "Pending", time)], vec![]);
+        let b = list(vec![task(1, "u1", "乙", "Pending", time)], vec![]);
+        let (mut ab, mut ba) = (a.clone(), b.clone());
+        ab.merge(&b);
+        ba.merge(&a);
+        assert_eq!(descriptions(&ab), descriptions(&ba));
+    }
+
+    #[test]
+    fn merge_keeps_unchanged_lists_unchanged() {
+        let mut mine = list(vec![task(1, "u1", "a", "Pending", "2025-01-01 10:00:00")], vec![]);
+        let theirs = mine.clone();
+        assert_eq!(mine.merge(&theirs), MergeReport::default());
+    }
+
+    #[test]
+    fn merge_applies_tombstones_over_edits() {
+        let mut mine = list(vec![
+            task(1, "u1", "a", "Pending", "2025-01-01 10:00:00"),
+            task(2, "u2", "b", "Pending", "2025-01-01 10:00:00"),
+        ], vec![]);
+        // 对方删除了 u1，本地随后又修改了它，删除仍然优先
+        let theirs = list(vec![task(2, "u2", "b", "Pending", "2025-01-01 10:00:00")], vec![("u1", "2025-01-01 11:00:00")]);
+        mine.tasks[0].set_description("a v2".to_string());
+
+        let report = mine.merge(&theirs);
+        assert_eq!(report.removed, 1);
+        assert_eq!(descriptions(&mine), vec![(2, "b")]);
+        // 墓碑被记录下来，再与仍有该任务的旧副本合并时不会复活
+        let stale = list(vec![task(1, "u1", "a", "Pending", "2025-01-01 09:00:00")], vec![]);
+        assert_eq!(mine.merge(&stale).added, 0);
+        assert_eq!(descriptions(&mine), vec![(2, "b")]);
+    }
+
+    #[test]
+    fn merge_keeps_latest_tombstone_time() {
+        let mut mine = list(vec![], vec![("u1", "2025-01-01 10:00:00")]);
+        let theirs = list(vec![], vec![("u1", "2025-01-02 10:00:00")]);
+        mine.merge(&theirs);
+        assert_eq!(mine.deleted, vec![Tombstone { uuid: "u1".to_string(), deleted_at: "2025-01-02 10:00:00".to_string() }]);
+    }
+
+    #[test]
+    fn merge_renumbers_colliding_ids() {
+        let mut mine = list(vec![task(1, "u1", "本地", "Pending", "2025-01-01 10:00:00")], vec![]);
+        let theirs = list(vec![
+            task(1, "u2", "对方 1", "Pending", "2025-01-01 10:00:00"),
+            task(2, "u3", "对方 2", "Pending", "2025-01-01 10:00:00"),
+        ], vec![]);
+
+        let report = mine.merge(&theirs);
+        assert_eq!(report.added, 2);
+        let mut ids: Vec<usize> = mine.get_tasks().iter().map(|t| t.id()).collect();
+        ids.sort();
+        assert_eq!(ids, vec![1, 2, 3]);
+        assert_eq!(mine.find_by_uuid("u1").unwrap().id(), 1);
+        assert_ne!(mine.find_by_uuid("u2").unwrap().id(), 1);
+        // 之后新建的任务不会与合并进来的ID冲突
+        assert_eq!(mine.add_task("新任务".to_string()), 4);
+    }
+
+    #[test]
+    fn merging_two_migrated_replicas_does_not_duplicate_tasks() {
+        // 同一份没有 UUID 的旧文件在两台设备上分别升级
+        let legacy = || {
+            let mut value = serde_json::to_value(list(vec![
+                task(1, "", "写报告", "Pending", ""),
+                task(2, "", "买菜", "Pending", ""),
+            ], vec![])).unwrap();
+            value["version"] = json!(0);
+            let mut task_list: TaskList = serde_json::from_value(value).unwrap();
+            assert!(task_list.migrate());
+            task_list
+        };
+        let mut laptop = legacy();
+        let mut desktop = legacy();
+        laptop.update_task(1, TaskUpdate { description: Some("写月报".to_string()), ..Default::default() }).unwrap();
+        desktop.complete_task(2);
+
+        let report = desktop.merge(&laptop);
+        assert_eq!((report.added, report.updated), (0, 1));
+        assert_eq!(descriptions(&desktop), vec![(1, "写月报"), (2, "买菜")]);
+        assert!(desktop.get_task(2).unwrap().is_completed());
+        laptop.merge(&desktop);
+        assert_eq!(serde_json::to_value(&laptop).unwrap(), serde_json::to_value(&desktop).unwrap());
+    }
+}
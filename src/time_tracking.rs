@@ -0,0 +1,105 @@
+use chrono::{DateTime, Local, NaiveDate, NaiveDateTime, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+
+// 与 created_at 等字段相同的时间格式 (UTC)
+pub const TIME_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+
+pub fn now_string() -> String {
+    Utc::now().format(TIME_FORMAT).to_string()
+}
+
+// 带微秒的时间戳，用于记录字段修改时间，字符串顺序即时间顺序
+pub fn clock_string() -> String {
+    Utc::now().format("%Y-%m-%d %H:%M:%S%.6f").to_string()
+}
+
+pub fn parse_time(s: &str) -> Option<DateTime<Utc>> {
+    NaiveDateTime::parse_from_str(s, TIME_FORMAT).ok().map(|t| Utc.from_utc_datetime(&t))
+}
+
+// 一段工作时间，end 为 None 表示计时仍在进行
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct WorkSession {
+    pub start: String,
+    pub end: Option<String>,
+}
+
+impl WorkSession {
+    pub fn start_now() -> Self {
+        WorkSession { start: now_string(), end: None }
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.end.is_none()
+    }
+
+    // 起止时间，进行中的计时以 now 为结束
+    pub fn interval(&self, now: DateTime<Utc>) -> Option<(DateTime<Utc>, DateTime<Utc>)> {
+        let start = parse_time(&self.start)?;
+        let end = match &self.end {
+            Some(end) => parse_time(end)?,
+            None => now,
+        };
+        Some((start, end.max(start)))
+    }
+
+    pub fn seconds(&self, now: DateTime<Utc>) -> i64 {
+        self.interval(now).map_or(0, |(start, end)| (end - start).num_seconds())
+    }
+
+    // 落在本地日期 day 内的秒数，跨午夜的计时按天拆分
+    pub fn seconds_on(&self, day: NaiveDate, now: DateTime<Utc>) -> i64 {
+        let Some((start, end)) = self.interval(now) else {
+            return 0;
+        };
+        let Some(day_start) = local_midnight(day) else {
+            return 0;
+        };
+        let Some(day_end) = day.succ_opt().and_then(local_midnight) else {
+            return 0;
+        };
+        let from = start.max(day_start);
+        let to = end.min(day_end);
+        if to > from { (to - from).num_seconds() } else { 0 }
+    }
+}
+
+fn local_midnight(day: NaiveDate) -> Option<DateTime<Utc>> {
+    Local.from_local_datetime(&day.and_hms_opt(0, 0, 0)?)
+        .earliest()
+        .map(|t| t.with_timezone(&Utc))
+}
+
+// 本地日期零点对应的时间戳，用于只有日期的外部数据
+pub fn local_midnight_string(day: NaiveDate) -> String {
+    local_midnight(day)
+        .unwrap_or_else(|| Utc.from_utc_datetime(&day.and_time(chrono::NaiveTime::MIN)))
+        .format(TIME_FORMAT)
+        .to_string()
+}
+
+// 时间戳对应的本地日期
+pub fn local_date(s: &str) -> Option<NaiveDate> {
+    parse_time(s).map(|t| t.with_timezone(&Local).date_naive())
+}
+
+pub fn today() -> NaiveDate {
+    Local::now().date_naive()
+}
+
+// 2d 03h / 1h 05m / 12m 30s 形式
+pub fn format_duration(seconds: i64) -> String {
+    let (h, m, s) = (seconds / 3600, seconds % 3600 / 60, seconds % 60);
+    if h >= 24 {
+        format!("{}d {:02}h", h / 24, h % 24)
+    } else if h > 0 {
+        format!("{}h {:02}m", h, m)
+    } else {
+        format!("{}m {:02}s", m, s)
+    }
+}
+
+// 计时器显示用的 00:12:05 形式
+pub fn format_clock(seconds: i64) -> String {
+    format!("{:02}:{:02}:{:02}", seconds / 3600, seconds % 3600 / 60, seconds % 60)
+}
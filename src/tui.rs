@@ -0,0 +1,559 @@
+use std::io::IsTerminal;
+use std::time::{Duration, SystemTime};
+use chrono::Utc;
+use ratatui::DefaultTerminal;
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use ratatui::layout::{Constraint, Flex, Layout, Rect};
+use ratatui::style::{Color, Modifier, Style, Stylize};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Clear, List, ListItem, ListState, Paragraph, Tabs};
+use ratatui::Frame;
+use crate::error::{Result, TodoError};
+use crate::query::TaskQuery;
+use crate::storage::Storage;
+use crate::task::{Task, TaskStatus, TaskUpdate};
+use crate::task_list::{Placement, TaskList};
+use crate::time_tracking;
+use crate::view::ViewOptions;
+
+// 全屏终端界面: todolist-cli tui
+// 没有按键时每隔一段时间检查 tasks.json，其他程序修改后自动重新加载
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+const HELP: [(&str, &str); 16] = [
+    ("j / k / ↓ / ↑", "下移 / 上移"),
+    ("g / G", "跳到第一个 / 最后一个任务"),
+    ("Tab / Shift+Tab / h / l", "切换标签页"),
+    ("1-9", "跳到第 N 个标签页"),
+    ("a", "添加任务 (描述中的 #标签 会成为标签)"),
+    ("e", "编辑描述"),
+    ("x", "完成 / 重新打开"),
+    ("s", "挂起 / 恢复"),
+    ("d", "删除 (y 确认)"),
+    ("t", "开始 / 停止计时"),
+    ("J / K", "下移 / 上移任务顺序"),
+    ("/", "搜索，支持 tag:标签 status:状态"),
+    ("Esc", "清除搜索"),
+    ("r", "重新加载"),
+    ("?", "显示 / 关闭帮助"),
+    ("q", "退出"),
+];
+
+// 标签页: 按状态筛选，以及每个标签一页
+#[derive(Debug, Clone, PartialEq)]
+enum TabFilter {
+    All,
+    Status(TaskStatus),
+    Tag(String),
+}
+
+impl TabFilter {
+    fn title(&self) -> String {
+        match self {
+            TabFilter::All => "全部".to_string(),
+            TabFilter::Status(TaskStatus::Pending) => "待处理".to_string(),
+            TabFilter::Status(TaskStatus::Suspended) => "已挂起".to_string(),
+            TabFilter::Status(TaskStatus::Completed) => "已完成".to_string(),
+            TabFilter::Tag(tag) => format!("#{}", tag),
+        }
+    }
+
+    fn matches(&self, task: &Task) -> bool {
+        match self {
+            TabFilter::All => true,
+            TabFilter::Status(status) => task.status() == status,
+            TabFilter::Tag(tag) => task.has_tag(tag),
+        }
+    }
+}
+
+// 底部输入行的用途
+#[derive(Debug, Clone, PartialEq)]
+enum Mode {
+    Normal,
+    Add(String),
+    Edit(usize, String),
+    Search(String),
+    ConfirmDelete(usize),
+    Help,
+}
+
+struct App {
+    task_list: TaskList,
+    tab: TabFilter,
+    selected_id: Option<usize>,
+    search: String,
+    mode: Mode,
+    status_message: String,
+    // 最近一次加载或保存后 tasks.json 的状态
+    stamp: Option<(SystemTime, u64)>,
+    quit: bool,
+}
+
+pub fn run() -> Result<()> {
+    // 没有终端时 (输出被重定向、在后台运行) 返回错误，而不是 panic；
+    // 在加载之前检查，避免迁移或备份 tasks.json 等副作用
+    if !std::io::stdout().is_terminal() {
+        return Err(TodoError::InvalidInput("tui 需要在交互式终端中运行".to_string()));
+    }
+    let mut app = App {
+        task_list: Storage::load_tasks()?,
+        tab: TabFilter::All,
+        selected_id: None,
+        search: String::new(),
+        mode: Mode::Normal,
+        status_message: "按 ? 查看快捷键".to_string(),
+        stamp: Storage::stamp(),
+        quit: false,
+    };
+    app.select_index(0);
+
+    let mut terminal = ratatui::try_init().map_err(|e| {
+        ratatui::restore();
+        TodoError::IoError(std::io::Error::new(e.kind(), format!("无法初始化终端: {}", e)))
+    })?;
+    let result = app.run(&mut terminal);
+    ratatui::restore();
+    result
+}
+
+impl App {
+    fn run(&mut self, terminal: &mut DefaultTerminal) -> Result<()> {
+        while !self.quit {
+            terminal.draw(|frame| self.draw(frame))?;
+            if event::poll(POLL_INTERVAL)? {
+                if let Event::Key(key) = event::read()?
+                    && key.kind == KeyEventKind::Press
+                {
+                    self.handle_key(key);
+                }
+            } else {
+                self.reload_if_changed();
+            }
+        }
+        Ok(())
+    }
+
+    fn tabs(&self) -> Vec<TabFilter> {
+        let mut tabs = vec![
+            TabFilter::All,
+            TabFilter::Status(TaskStatus::Pending),
+            TabFilter::Status(TaskStatus::Suspended),
+            TabFilter::Status(TaskStatus::Completed),
+        ];
+        let mut tags: Vec<&String> = self.task_list.get_tasks().iter().flat_map(|t| t.tags()).collect();
+        tags.sort();
+        tags.dedup();
+        tabs.extend(tags.into_iter().map(|tag| TabFilter::Tag(tag.clone())));
+        tabs
+    }
+
+    // 当前标签页和搜索条件下可见的任务，按手动顺序
+    fn visible_tasks(&self) -> Vec<&Task> {
+        let query: TaskQuery = match &self.mode {
+            Mode::Search(text) => text.parse().unwrap_or_default(),
+            _ => self.search.parse().unwrap_or_default(),
+        };
+        ViewOptions::default()
+            .apply(self.task_list.get_tasks().iter().filter(|t| self.tab.matches(t) && query.matches(t)))
+            .into_iter()
+            .flat_map(|group| group.tasks)
+            .collect()
+    }
+
+    fn selected_index(&self) -> Option<usize> {
+        let id = self.selected_id?;
+        self.visible_tasks().iter().position(|task| task.id() == id)
+    }
+
+    fn select_index(&mut self, index: usize) {
+        let ids: Vec<usize> = self.visible_tasks().iter().map(|task| task.id()).collect();
+        self.selected_id = ids.get(index.min(ids.len().saturating_sub(1))).copied();
+    }
+
+    // 选中的任务不再可见时 (例如完成后切到了其他标签页) 选中相邻的任务
+    fn keep_selection(&mut self, previous_index: Option<usize>) {
+        if self.selected_index().is_none() {
+            self.select_index(previous_index.unwrap_or(0));
+        }
+    }
+
+    fn move_selection(&mut self, offset: isize) {
+        let count = self.visible_tasks().len();
+        if count == 0 {
+            return;
+        }
+        let index = match self.selected_index() {
+            Some(index) => index.saturating_add_signed(offset).min(count - 1),
+            None => 0,
+        };
+        self.select_index(index);
+    }
+
+    fn switch_tab(&mut self, tab: TabFilter) {
+        self.tab = tab;
+        self.select_index(0);
+    }
+
+    fn cycle_tab(&mut self, forward: bool) {
+        let tabs = self.tabs();
+        let current = tabs.iter().position(|tab| *tab == self.tab).unwrap_or(0);
+        let next = if forward { (current + 1) % tabs.len() } else { (current + tabs.len() - 1) % tabs.len() };
+        self.switch_tab(tabs[next].clone());
+    }
+
+    fn handle_key(&mut self, key: KeyEvent) {
+        if key.modifiers.contains(KeyModifiers::CONTROL) && key.code == KeyCode::Char('c') {
+            self.quit = true;
+            return;
+        }
+        match self.mode.clone() {
+            Mode::Normal => self.handle_normal_key(key),
+            Mode::Help => self.mode = Mode::Normal,
+            Mode::ConfirmDelete(id) => {
+                self.mode = Mode::Normal;
+                if matches!(key.code, KeyCode::Char('y') | KeyCode::Char('Y')) {
+                    self.delete_task(id);
+                } else {
+                    self.status_message = "已取消删除".to_string();
+                }
+            }
+            Mode::Add(text) | Mode::Edit(_, text) | Mode::Search(text) => self.handle_input_key(key, text),
+        }
+    }
+
+    fn handle_normal_key(&mut self, key: KeyEvent) {
+        let selected = self.selected_id;
+        match key.code {
+            KeyCode::Char('q') => self.quit = true,
+            KeyCode::Char('j') | KeyCode::Down => self.move_selection(1),
+            KeyCode::Char('k') | KeyCode::Up => self.move_selection(-1),
+            KeyCode::Char('g') | KeyCode::Home => self.select_index(0),
+            KeyCode::Char('G') | KeyCode::End => self.select_index(usize::MAX),
+            KeyCode::PageDown => self.move_selection(10),
+            KeyCode::PageUp => self.move_selection(-10),
+            KeyCode::Tab | KeyCode::Char('l') | KeyCode::Right => self.cycle_tab(true),
+            KeyCode::BackTab | KeyCode::Char('h') | KeyCode::Left => self.cycle_tab(false),
+            KeyCode::Char(digit @ '1'..='9') => {
+                if let Some(tab) = self.tabs().get(digit as usize - '1' as usize) {
+                    self.switch_tab(tab.clone());
+                }
+            }
+            KeyCode::Char('a') => self.mode = Mode::Add(String::new()),
+            KeyCode::Char('/') => self.mode = Mode::Search(self.search.clone()),
+            KeyCode::Char('?') => self.mode = Mode::Help,
+            KeyCode::Char('r') => self.reload(),
+            KeyCode::Esc => {
+                self.search.clear();
+                self.keep_selection(self.selected_index());
+            }
+            _ => {
+                if let Some(id) = selected {
+                    self.handle_task_key(key.code, id);
+                }
+            }
+        }
+    }
+
+    // 作用于选中任务的按键
+    fn handle_task_key(&mut self, code: KeyCode, id: usize) {
+        let Some(task) = self.task_list.get_task(id) else {
+            return;
+        };
+        let status = task.status().clone();
+        let index = self.selected_index();
+        match code {
+            KeyCode::Char('e') | KeyCode::Enter => self.mode = Mode::Edit(id, task.description().to_string()),
+            KeyCode::Char('d') => self.mode = Mode::ConfirmDelete(id),
+            KeyCode::Char('x') if status == TaskStatus::Completed => self.set_status(id, TaskStatus::Pending, "↩️ 任务 #{} 已重新打开"),
+            KeyCode::Char('x') => self.set_status(id, TaskStatus::Completed, "✅ 任务 #{} 已完成"),
+            KeyCode::Char('s') if status == TaskStatus::Suspended => self.set_status(id, TaskStatus::Pending, "▶️ 任务 #{} 已恢复"),
+            KeyCode::Char('s') => self.set_status(id, TaskStatus::Suspended, "⏸️ 任务 #{} 已挂起"),
+            KeyCode::Char('t') => self.toggle_timer(id),
+            KeyCode::Char('J') => self.move_task(id, 1),
+            KeyCode::Char('K') => self.move_task(id, -1),
+            _ => return,
+        }
+        self.keep_selection(index);
+    }
+
+    fn handle_input_key(&mut self, key: KeyEvent, mut text: String) {
+        match key.code {
+            KeyCode::Esc => {
+                self.mode = Mode::Normal;
+                return;
+            }
+            KeyCode::Enter => {
+                let mode = std::mem::replace(&mut self.mode, Mode::Normal);
+                match mode {
+                    Mode::Add(_) => self.add_task(&text),
+                    Mode::Edit(id, _) => self.edit_task(id, &text),
+                    Mode::Search(_) => {
+                        self.search = text.trim().to_string();
+                        self.keep_selection(Some(0));
+                    }
+                    _ => {}
+                }
+                return;
+            }
+            KeyCode::Backspace => {
+                text.pop();
+            }
+            KeyCode::Char(c) => text.push(c),
+            _ => return,
+        }
+        match &mut self.mode {
+            Mode::Add(buffer) | Mode::Edit(_, buffer) | Mode::Search(buffer) => *buffer = text,
+            _ => {}
+        }
+        if matches!(self.mode, Mode::Search(_)) {
+            self.keep_selection(Some(0));
+        }
+    }
+
+    // 描述中的 #标签 作为任务标签
+    fn add_task(&mut self, input: &str) {
+        let (words, tags): (Vec<&str>, Vec<&str>) = input.split_whitespace()
+            .partition(|word| !(word.len() > 1 && word.starts_with('#')));
+        let description = words.join(" ");
+        if description.is_empty() {
+            self.status_message = "❌ 任务描述不能为空".to_string();
+            return;
+        }
+        let id = self.task_list.add_task(description);
+        if !tags.is_empty() {
+            let tags = tags.iter().map(|tag| tag[1..].to_string()).collect();
+            self.task_list.update_task(id, TaskUpdate { tags: Some(tags), ..Default::default() }).ok();
+        }
+        self.save(format!("✅ 已添加任务 #{}", id));
+        self.selected_id = Some(id);
+        self.keep_selection(None);
+    }
+
+    fn edit_task(&mut self, id: usize, description: &str) {
+        let description = description.trim();
+        if description.is_empty() {
+            self.status_message = "❌ 任务描述不能为空".to_string();
+            return;
+        }
+        let update = TaskUpdate { description: Some(description.to_string()), ..Default::default() };
+        match self.task_list.update_task(id, update) {
+            Ok(()) => self.save(format!("✏️ 任务 #{} 已更新", id)),
+            Err(e) => self.status_message = format!("❌ {}", e),
+        }
+    }
+
+    fn set_status(&mut self, id: usize, status: TaskStatus, message: &str) {
+        match self.task_list.update_task(id, TaskUpdate { status: Some(status), ..Default::default() }) {
+            Ok(()) => self.save(message.replace("{}", &id.to_string())),
+            Err(e) => self.status_message = format!("❌ {}", e),
+        }
+    }
+
+    fn delete_task(&mut self, id: usize) {
+        let index = self.selected_index();
+        if self.task_list.delete_task(id) {
+            self.save(format!("🗑️ 任务 #{} 已删除", id));
+            self.keep_selection(index);
+        }
+    }
+
+    fn toggle_timer(&mut self, id: usize) {
+        let running = self.task_list.active_timer().map(|task| task.id());
+        if running == Some(id) {
+            self.task_list.stop_timer();
+            self.save(format!("⏹️ 任务 #{} 已停止计时", id));
+            return;
+        }
+        match self.task_list.start_timer(id) {
+            Ok(_) => self.save(format!("⏱ 任务 #{} 开始计时", id)),
+            Err(e) => self.status_message = format!("❌ {}", e),
+        }
+    }
+
+    // 与当前列表中相邻的任务交换位置
+    fn move_task(&mut self, id: usize, offset: isize) {
+        let ids: Vec<usize> = self.visible_tasks().iter().map(|task| task.id()).collect();
+        let Some(index) = ids.iter().position(|&i| i == id) else {
+            return;
+        };
+        let Some(&target) = index.checked_add_signed(offset).and_then(|i| ids.get(i)) else {
+            return;
+        };
+        let placement = if offset > 0 { Placement::After(target) } else { Placement::Before(target) };
+        match self.task_list.move_task(id, placement) {
+            Ok(()) => self.save(format!("↕️ 任务 #{} 已移动", id)),
+            Err(e) => self.status_message = format!("❌ {}", e),
+        }
+    }
+
+    // 上次加载或保存后其他进程修改了 tasks.json 时先合并对方的修改再保存，避免覆盖
+    fn save(&mut self, mut message: String) {
+        if Storage::stamp() != self.stamp {
+            match Storage::load_tasks() {
+                Ok(on_disk) => {
+                    self.task_list.merge(&on_disk);
+                    message.push_str(" (已合并其他程序的修改)");
+                }
+                Err(e) => {
+                    self.status_message = format!("❌ 保存失败: tasks.json 已被其他程序修改且无法读取: {}", e);
+                    return;
+                }
+            }
+        }
+        match Storage::save_tasks(&self.task_list) {
+            Ok(()) => {
+                self.status_message = message;
+                self.stamp = Storage::stamp();
+            }
+            Err(e) => self.status_message = format!("❌ 保存失败: {}", e),
+        }
+    }
+
+    fn reload(&mut self) {
+        let index = self.selected_index();
+        match Storage::load_tasks() {
+            Ok(task_list) => {
+                self.task_list = task_list;
+                self.stamp = Storage::stamp();
+                self.status_message = "🔄 已重新加载".to_string();
+            }
+            Err(e) => self.status_message = format!("❌ 加载失败: {}", e),
+        }
+        if !self.tabs().contains(&self.tab) {
+            self.tab = TabFilter::All;
+        }
+        self.keep_selection(index);
+    }
+
+    fn reload_if_changed(&mut self) {
+        if Storage::stamp() != self.stamp {
+            self.reload();
+        }
+    }
+
+    fn draw(&self, frame: &mut Frame) {
+        let [tabs_area, list_area, input_area, help_area] = Layout::vertical([
+            Constraint::Length(3),
+            Constraint::Min(3),
+            Constraint::Length(1),
+            Constraint::Length(1),
+        ])
+        .areas(frame.area());
+
+        let tabs = self.tabs();
+        let titles: Vec<String> = tabs.iter().enumerate().map(|(i, tab)| {
+            let count = self.task_list.get_tasks().iter().filter(|t| tab.matches(t)).count();
+            if i < 9 { format!("{} {} ({})", i + 1, tab.title(), count) } else { format!("{} ({})", tab.title(), count) }
+        }).collect();
+        let selected_tab = tabs.iter().position(|tab| *tab == self.tab).unwrap_or(0);
+        frame.render_widget(
+            Tabs::new(titles)
+                .select(selected_tab)
+                .block(Block::bordered().title(" 📝 待办事项 "))
+                .highlight_style(Style::new().fg(Color::Yellow).add_modifier(Modifier::BOLD)),
+            tabs_area,
+        );
+
+        let tasks = self.visible_tasks();
+        let items: Vec<ListItem> = tasks.iter().map(|task| ListItem::new(task_line(task))).collect();
+        let mut title = format!(" {} 个任务 ", tasks.len());
+        if !self.search.is_empty() {
+            title = format!(" 搜索: {} —{}", self.search, title);
+        }
+        let list = List::new(items)
+            .block(Block::bordered().title(title))
+            .highlight_style(Style::new().bg(Color::DarkGray).add_modifier(Modifier::BOLD))
+            .highlight_symbol("▶ ");
+        let mut state = ListState::default().with_selected(self.selected_index());
+        frame.render_stateful_widget(list, list_area, &mut state);
+        if tasks.is_empty() {
+            let inner = list_area.inner(ratatui::layout::Margin::new(2, 1));
+            frame.render_widget(Paragraph::new("(暂无任务，按 a 添加)").dark_gray(), inner);
+        }
+
+        let (prompt, text) = match &self.mode {
+            Mode::Add(text) => ("添加: ", Some(text)),
+            Mode::Edit(_, text) => ("编辑: ", Some(text)),
+            Mode::Search(text) => ("/", Some(text)),
+            _ => ("", None),
+        };
+        match (&self.mode, text) {
+            (_, Some(text)) => {
+                let line = Line::from(vec![Span::raw(prompt).bold(), Span::raw(text.as_str())]);
+                let x = input_area.x + line.width() as u16;
+                frame.render_widget(Paragraph::new(line), input_area);
+                frame.set_cursor_position((x.min(input_area.right().saturating_sub(1)), input_area.y));
+            }
+            (Mode::ConfirmDelete(id), None) => {
+                frame.render_widget(Paragraph::new(format!("删除任务 #{}? (y/n)", id)).red().bold(), input_area);
+            }
+            _ => frame.render_widget(Paragraph::new(self.status_message.as_str()), input_area),
+        }
+
+        let hint = match self.mode {
+            Mode::Normal => "a 添加  x 完成  s 挂起  d 删除  e 编辑  t 计时  / 搜索  Tab 切换  ? 帮助  q 退出",
+            Mode::Help | Mode::ConfirmDelete(_) => "",
+            _ => "Enter 确认  Esc 取消",
+        };
+        frame.render_widget(Paragraph::new(hint).dark_gray(), help_area);
+
+        if self.mode == Mode::Help {
+            draw_help(frame);
+        }
+    }
+}
+
+fn task_line(task: &Task) -> Line<'static> {
+    let (symbol, style) = match task.status() {
+        TaskStatus::Pending => ("[ ]", Style::new()),
+        TaskStatus::Suspended => ("[.]", Style::new().fg(Color::Yellow)),
+        TaskStatus::Completed => ("[✓]", Style::new().fg(Color::DarkGray).add_modifier(Modifier::CROSSED_OUT)),
+    };
+    let mut spans = vec![
+        Span::styled(format!("{} ", symbol), style.remove_modifier(Modifier::CROSSED_OUT)),
+        Span::raw(format!("#{:<3} ", task.id())).dark_gray(),
+        Span::styled(task.description().to_string(), style),
+    ];
+    for tag in task.tags() {
+        spans.push(Span::raw(format!(" #{}", tag)).cyan());
+    }
+    if let Some(priority) = task.priority() {
+        spans.push(Span::raw(format!(" ❗{}", priority)).magenta());
+    }
+    if let Some(due) = task.due() {
+        let span = Span::raw(format!(" 📅 {}", due));
+        spans.push(if task.is_overdue(time_tracking::today()) { span.red() } else { span.dark_gray() });
+    }
+    if let Some(recurrence) = task.recurrence() {
+        spans.push(Span::raw(format!(" 🔁 {}", recurrence.label())).dark_gray());
+    }
+    if let Some(estimate) = task.estimate() {
+        spans.push(Span::raw(format!(" ⌛ {}", estimate)).dark_gray());
+    }
+    if task.pomodoros() > 0 {
+        spans.push(Span::raw(format!(" 🍅 {}", task.pomodoros())));
+    }
+    let tracked = task.tracked_seconds(Utc::now());
+    if task.is_timer_running() {
+        spans.push(Span::raw(format!(" ⏱ {} (计时中)", time_tracking::format_duration(tracked))).green());
+    } else if tracked > 0 {
+        spans.push(Span::raw(format!(" ⏱ {}", time_tracking::format_duration(tracked))).dark_gray());
+    }
+    Line::from(spans)
+}
+
+fn draw_help(frame: &mut Frame) {
+    let lines: Vec<Line> = HELP.iter()
+        .map(|(keys, action)| Line::from(vec![Span::raw(format!("{:<24}", keys)).yellow(), Span::raw(*action)]))
+        .collect();
+    let area = centered(frame.area(), 66, lines.len() as u16 + 2);
+    frame.render_widget(Clear, area);
+    frame.render_widget(Paragraph::new(lines).block(Block::bordered().title(" 快捷键 (任意键关闭) ")), area);
+}
+
+fn centered(area: Rect, width: u16, height: u16) -> Rect {
+    let [area] = Layout::horizontal([Constraint::Length(width)]).flex(Flex::Center).areas(area);
+    let [area] = Layout::vertical([Constraint::Length(height)]).flex(Flex::Center).areas(area);
+    area
+}
@@ -0,0 +1,341 @@
+use std::cmp::Ordering;
+use std::fmt;
+use std::str::FromStr;
+use chrono::{Datelike, NaiveDate};
+use serde::{Deserialize, Serialize};
+use crate::error::TodoError;
+use crate::task::{Task, TaskStatus};
+use crate::time_tracking;
+
+// 排序方式，Manual 即手动调整的顺序
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+pub enum SortKey {
+    #[default]
+    Manual,
+    Created,
+    Status,
+    Description,
+    Completed,
+    Due,
+    Priority,
+}
+
+// 分组方式
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+pub enum GroupBy {
+    #[default]
+    None,
+    Status,
+    Tag,
+    Due,
+}
+
+// 列表视图设置，GUI 会持久化保存
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+pub struct ViewOptions {
+    pub sort: SortKey,
+    pub descending: bool,
+    pub group: GroupBy,
+}
+
+// 一个分组及其中排好序的任务，不分组时标题为空
+#[derive(Debug)]
+pub struct TaskGroup<'a> {
+    pub title: String,
+    pub tasks: Vec<&'a Task>,
+}
+
+impl SortKey {
+    pub const ALL: [SortKey; 7] = [
+        SortKey::Manual,
+        SortKey::Created,
+        SortKey::Status,
+        SortKey::Description,
+        SortKey::Completed,
+        SortKey::Due,
+        SortKey::Priority,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            SortKey::Manual => "手动顺序",
+            SortKey::Created => "创建时间",
+            SortKey::Status => "状态",
+            SortKey::Description => "描述",
+            SortKey::Completed => "完成时间",
+            SortKey::Due => "截止日期",
+            SortKey::Priority => "优先级",
+        }
+    }
+
+    // 降序只反转键值本身的比较，缺少该值的任务无论升降序都排在最后
+    fn compare(&self, a: &Task, b: &Task, descending: bool) -> Ordering {
+        let ordering = match self {
+            SortKey::Manual => a.position().cmp(&b.position()),
+            SortKey::Created => a.created_at().cmp(b.created_at()),
+            SortKey::Status => status_rank(a.status()).cmp(&status_rank(b.status())),
+            SortKey::Description => a.description().to_lowercase().cmp(&b.description().to_lowercase()),
+            // 未完成的任务排在最后
+            SortKey::Completed => return none_last(a.completed_at(), b.completed_at(), descending),
+            // 没有截止日期或优先级的任务排在最后
+            SortKey::Due => return none_last(a.due(), b.due(), descending),
+            SortKey::Priority => return none_last(a.priority(), b.priority(), descending),
+        };
+        if descending { ordering.reverse() } else { ordering }
+    }
+}
+
+impl GroupBy {
+    pub const ALL: [GroupBy; 4] = [GroupBy::None, GroupBy::Status, GroupBy::Tag, GroupBy::Due];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            GroupBy::None => "不分组",
+            GroupBy::Status => "按状态",
+            GroupBy::Tag => "按标签",
+            GroupBy::Due => "按截止日期",
+        }
+    }
+}
+
+impl ViewOptions {
+    // 排序后分组；按标签分组时，有多个标签的任务会出现在每个标签组中
+    pub fn apply<'a>(&self, tasks: impl IntoIterator<Item = &'a Task>) -> Vec<TaskGroup<'a>> {
+        self.apply_on(tasks, time_tracking::today())
+    }
+
+    // 按截止日期分组时以 today 为准
+    pub fn apply_on<'a>(&self, tasks: impl IntoIterator<Item = &'a Task>, today: NaiveDate) -> Vec<TaskGroup<'a>> {
+        let mut tasks: Vec<&Task> = tasks.into_iter().collect();
+        // 相同键值时保持手动顺序
+        tasks.sort_by(|a, b| self.sort.compare(a, b, self.descending).then_with(|| a.position().cmp(&b.position())));
+
+        match self.group {
+            GroupBy::None => vec![TaskGroup { title: String::new(), tasks }],
+            GroupBy::Status => [TaskStatus::Pending, TaskStatus::Suspended, TaskStatus::Completed]
+                .into_iter()
+                .map(|status| TaskGroup {
+                    title: status_title(&status).to_string(),
+                    tasks: tasks.iter().copied().filter(|t| *t.status() == status).collect(),
+                })
+                .filter(|group| !group.tasks.is_empty())
+                .collect(),
+            GroupBy::Tag => {
+                let mut tags: Vec<&str> = tasks.iter().flat_map(|t| t.tags()).map(String::as_str).collect();
+                tags.sort();
+                tags.dedup();
+                let mut groups: Vec<TaskGroup> = tags.into_iter()
+                    .map(|tag| TaskGroup {
+                        title: format!("🏷 {}", tag),
+                        tasks: tasks.iter().copied().filter(|t| t.has_tag(tag)).collect(),
+                    })
+                    .collect();
+                let untagged: Vec<&Task> = tasks.iter().copied().filter(|t| t.tags().is_empty()).collect();
+                if !untagged.is_empty() {
+                    groups.push(TaskGroup { title: "无标签".to_string(), tasks: untagged });
+                }
+                groups
+            }
+            GroupBy::Due => DueBucket::ALL
+                .into_iter()
+                .map(|bucket| TaskGroup {
+                    title: bucket.title().to_string(),
+                    tasks: tasks.iter().copied().filter(|t| DueBucket::of(t, today) == bucket).collect(),
+                })
+                .filter(|group| !group.tasks.is_empty())
+                .collect(),
+        }
+    }
+}
+
+// 按截止日期分组的区间；本周到周日为止，截止日期已过但已完成的任务归入"更早"
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum DueBucket {
+    Overdue,
+    Today,
+    ThisWeek,
+    Later,
+    Earlier,
+    NoDue,
+}
+
+impl DueBucket {
+    const ALL: [DueBucket; 6] = [
+        DueBucket::Overdue,
+        DueBucket::Today,
+        DueBucket::ThisWeek,
+        DueBucket::Later,
+        DueBucket::Earlier,
+        DueBucket::NoDue,
+    ];
+
+    fn of(task: &Task, today: NaiveDate) -> DueBucket {
+        let Some(due) = task.due() else {
+            return DueBucket::NoDue;
+        };
+        let week_end = today + chrono::Days::new(u64::from(6 - today.weekday().num_days_from_monday()));
+        if due < today {
+            if task.is_completed() { DueBucket::Earlier } else { DueBucket::Overdue }
+        } else if due == today {
+            DueBucket::Today
+        } else if due <= week_end {
+            DueBucket::ThisWeek
+        } else {
+            DueBucket::Later
+        }
+    }
+
+    fn title(&self) -> &'static str {
+        match self {
+            DueBucket::Overdue => "⚠ 已过期",
+            DueBucket::Today => "今天",
+            DueBucket::ThisWeek => "本周",
+            DueBucket::Later => "以后",
+            DueBucket::Earlier => "更早",
+            DueBucket::NoDue => "无截止日期",
+        }
+    }
+}
+
+fn none_last<T: Ord>(a: Option<T>, b: Option<T>, descending: bool) -> Ordering {
+    match (a, b) {
+        (Some(x), Some(y)) if descending => y.cmp(&x),
+        (Some(x), Some(y)) => x.cmp(&y),
+        (Some(_), None) => Ordering::Less,
+        (None, Some(_)) => Ordering::Greater,
+        (None, None) => Ordering::Equal,
+    }
+}
+
+fn status_rank(status: &TaskStatus) -> u8 {
+    match status {
+        TaskStatus::Pending => 0,
+        TaskStatus::Suspended => 1,
+        TaskStatus::Completed => 2,
+    }
+}
+
+fn status_title(status: &TaskStatus) -> &'static str {
+    match status {
+        TaskStatus::Pending => "待处理",
+        TaskStatus::Suspended => "已挂起",
+        TaskStatus::Completed => "已完成",
+    }
+}
+
+impl fmt::Display for SortKey {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.label())
+    }
+}
+
+impl fmt::Display for GroupBy {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.label())
+    }
+}
+
+impl FromStr for SortKey {
+    type Err = TodoError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "manual" | "position" => Ok(SortKey::Manual),
+            "created" => Ok(SortKey::Created),
+            "status" => Ok(SortKey::Status),
+            "description" | "name" => Ok(SortKey::Description),
+            "completed" | "done" => Ok(SortKey::Completed),
+            "due" => Ok(SortKey::Due),
+            "priority" => Ok(SortKey::Priority),
+            other => Err(TodoError::InvalidInput(format!("未知的排序方式: {}", other))),
+        }
+    }
+}
+
+impl FromStr for GroupBy {
+    type Err = TodoError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "none" => Ok(GroupBy::None),
+            "status" => Ok(GroupBy::Status),
+            "tag" | "tags" => Ok(GroupBy::Tag),
+            "due" => Ok(GroupBy::Due),
+            other => Err(TodoError::InvalidInput(format!("未知的分组方式: {}", other))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::task::Priority;
+
+    fn date(day: u32) -> NaiveDate {
+        // 2025-07-02 是周三
+        NaiveDate::from_ymd_opt(2025, 7, day).unwrap()
+    }
+
+    fn task(id: usize, due: Option<NaiveDate>, priority: Option<Priority>) -> Task {
+        let mut task = Task::new(id, format!("任务{}", id));
+        task.set_due(due);
+        task.set_priority(priority);
+        task
+    }
+
+    fn ids(group: &TaskGroup) -> Vec<usize> {
+        group.tasks.iter().map(|t| t.id()).collect()
+    }
+
+    #[test]
+    fn sorts_by_due_with_missing_dates_last() {
+        let tasks = [task(1, None, None), task(2, Some(date(5)), None), task(3, Some(date(3)), None)];
+        let view = ViewOptions { sort: SortKey::Due, ..Default::default() };
+        assert_eq!(ids(&view.apply_on(&tasks, date(2))[0]), [3, 2, 1]);
+    }
+
+    #[test]
+    fn descending_keeps_missing_dates_last_and_manual_order_for_ties() {
+        let mut tasks = [task(4, Some(date(3)), None), task(1, None, None), task(3, Some(date(5)), None), task(2, Some(date(3)), None)];
+        for task in &mut tasks {
+            task.set_position(task.id());
+        }
+        let view = ViewOptions { sort: SortKey::Due, descending: true, ..Default::default() };
+        assert_eq!(ids(&view.apply_on(&tasks, date(2))[0]), [3, 2, 4, 1]);
+    }
+
+    #[test]
+    fn sorts_by_priority_high_first() {
+        let tasks = [task(1, None, Some(Priority::Low)), task(2, None, None), task(3, None, Some(Priority::High))];
+        let view = ViewOptions { sort: SortKey::Priority, ..Default::default() };
+        assert_eq!(ids(&view.apply_on(&tasks, date(2))[0]), [3, 1, 2]);
+    }
+
+    #[test]
+    fn groups_by_due_relative_to_today() {
+        let mut done = task(6, Some(date(1)), None);
+        done.complete();
+        let tasks = [
+            task(1, Some(date(1)), None),
+            task(2, Some(date(2)), None),
+            task(3, Some(date(6)), None),
+            task(4, Some(date(7)), None),
+            task(5, None, None),
+            done,
+        ];
+        let view = ViewOptions { sort: SortKey::Due, group: GroupBy::Due, ..Default::default() };
+        let groups = view.apply_on(&tasks, date(2));
+        let titles: Vec<&str> = groups.iter().map(|g| g.title.as_str()).collect();
+        assert_eq!(titles, ["⚠ 已过期", "今天", "本周", "以后", "更早", "无截止日期"]);
+        let grouped: Vec<Vec<usize>> = groups.iter().map(ids).collect();
+        assert_eq!(grouped, [vec![1], vec![2], vec![3], vec![4], vec![6], vec![5]]);
+    }
+
+    #[test]
+    fn parses_sort_and_group_names() {
+        assert_eq!("due".parse::<SortKey>().unwrap(), SortKey::Due);
+        assert_eq!("Priority".parse::<SortKey>().unwrap(), SortKey::Priority);
+        assert_eq!("due".parse::<GroupBy>().unwrap(), GroupBy::Due);
+        assert!("deadline".parse::<SortKey>().is_err());
+    }
+}
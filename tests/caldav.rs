@@ -0,0 +1,102 @@
+use std::thread;
+use tiny_http::Server;
+use todolist_cli::caldav::{self, CalDavClient, CalDavConfig, ConflictPolicy, SyncState};
+use todolist_cli::ical;
+use todolist_cli::task::{TaskStatus, TaskUpdate};
+use todolist_cli::task_list::TaskList;
+
+#[allow(dead_code)]
+#[path = "../examples/caldav_server.rs"]
+mod caldav_server;
+
+// CalDAV 同步集成测试: 每个测试在后台线程启动一个内存中的替身服务器
+fn start_server(etags: bool) -> String {
+    let server = Server::http("127.0.0.1:0").unwrap();
+    let address = server.server_addr().to_ip().unwrap();
+    thread::spawn(move || caldav_server::serve(server, None, etags));
+    format!("http://{}/tasks/", address)
+}
+
+struct Device {
+    tasks: TaskList,
+    state: SyncState,
+    client: CalDavClient,
+}
+
+impl Device {
+    fn new(url: &str) -> Self {
+        let config = CalDavConfig { url: url.to_string(), ..Default::default() };
+        Device {
+            tasks: TaskList::new(),
+            state: SyncState { url: url.to_string(), entries: Vec::new() },
+            client: CalDavClient::new(&config).unwrap(),
+        }
+    }
+
+    fn sync(&mut self) -> caldav::SyncReport {
+        caldav::sync(&mut self.tasks, &self.client, &mut self.state, ConflictPolicy::Skip).unwrap()
+    }
+}
+
+#[test]
+fn matches_existing_items_by_uid_without_sync_state() {
+    let url = start_server(true);
+    let mut laptop = Device::new(&url);
+    laptop.tasks.add_task("写周报".to_string());
+    laptop.sync();
+
+    // 另一台设备有相同的任务 (例如通过文件同步)，但没有 caldav_state.json
+    let mut desktop = Device::new(&url);
+    desktop.tasks = laptop.tasks.clone();
+    let report = desktop.sync();
+    assert_eq!(report.matched, 1);
+    assert!(report.conflicts.is_empty(), "{:?}", report.conflicts);
+    assert_eq!(desktop.tasks.get_tasks().len(), 1);
+
+    // 删除同步记录后重新同步也不会重复
+    laptop.state.entries.clear();
+    let report = laptop.sync();
+    assert_eq!((report.matched, report.created_local, report.created_remote), (1, 0, 0));
+    assert!(report.conflicts.is_empty(), "{:?}", report.conflicts);
+    assert_eq!(laptop.tasks.get_tasks().len(), 1);
+}
+
+#[test]
+fn detects_remote_changes_without_etags() {
+    let url = start_server(false);
+    let mut laptop = Device::new(&url);
+    let id = laptop.tasks.add_task("写周报".to_string());
+    assert_eq!(laptop.sync().created_remote, 1);
+
+    let mut desktop = Device::new(&url);
+    assert_eq!(desktop.sync().created_local, 1);
+    let update = TaskUpdate { description: Some("写月报".to_string()), ..Default::default() };
+    desktop.tasks.update_task(1, update).unwrap();
+    assert_eq!(desktop.sync().pushed, 1);
+
+    let report = laptop.sync();
+    assert_eq!(report.pulled, 1);
+    assert_eq!(laptop.tasks.get_task(id).unwrap().description(), "写月报");
+    // 没有新的修改时不再拉取
+    assert_eq!(laptop.sync().pulled, 0);
+}
+
+#[test]
+fn pulling_keeps_the_remote_completion_time() {
+    let url = start_server(true);
+    let mut laptop = Device::new(&url);
+    let id = laptop.tasks.add_task("写周报".to_string());
+    laptop.sync();
+
+    // 其他客户端在较早的时间完成了任务
+    let entry = &laptop.state.entries[0];
+    let body = ical::to_resource(laptop.tasks.get_task(id).unwrap(), &entry.uid)
+        .replace("STATUS:NEEDS-ACTION", "STATUS:COMPLETED\r\nCOMPLETED:20240102T030405Z");
+    let resource = format!("{}{}", url.trim_end_matches("/tasks/"), entry.href);
+    ureq::put(&resource).send_string(&body).unwrap();
+
+    assert_eq!(laptop.sync().pulled, 1);
+    let task = laptop.tasks.get_task(id).unwrap();
+    assert_eq!(task.status(), &TaskStatus::Completed);
+    assert_eq!(task.completed_at(), Some("2024-01-02 03:04:05"));
+}
@@ -0,0 +1,362 @@
+use std::fs;
+use std::path::PathBuf;
+use std::process::{Child, Command, Output};
+use std::time::Duration;
+
+// 命令行集成测试: 每个测试在独立的临时目录中运行 todolist-cli，互不影响
+struct Workspace {
+    dir: PathBuf,
+}
+
+impl Workspace {
+    fn new(name: &str) -> Self {
+        let dir = std::env::temp_dir().join(format!("todolist-cli-test-{}-{}", name, std::process::id()));
+        fs::remove_dir_all(&dir).ok();
+        fs::create_dir_all(&dir).unwrap();
+        Workspace { dir }
+    }
+
+    fn run(&self, args: &[&str]) -> Output {
+        Command::new(env!("CARGO_BIN_EXE_todolist-cli"))
+            .args(args)
+            .current_dir(&self.dir)
+            .output()
+            .unwrap()
+    }
+
+    // 执行成功并返回标准输出
+    fn ok(&self, args: &[&str]) -> String {
+        let output = self.run(args);
+        assert!(output.status.success(), "{:?} 失败: {}", args, String::from_utf8_lossy(&output.stderr));
+        String::from_utf8(output.stdout).unwrap()
+    }
+
+    // 通过标准输入传入内容，等待进程结束
+    fn run_with_input(&self, args: &[&str], input: &str) -> Output {
+        use std::io::Write;
+        let mut child = Command::new(env!("CARGO_BIN_EXE_todolist-cli"))
+            .args(args)
+            .current_dir(&self.dir)
+            .stdin(std::process::Stdio::piped())
+            .stdout(std::process::Stdio::piped())
+            .stderr(std::process::Stdio::piped())
+            .spawn()
+            .unwrap();
+        child.stdin.take().unwrap().write_all(input.as_bytes()).unwrap();
+        child.wait_with_output().unwrap()
+    }
+
+    fn write(&self, name: &str, content: &str) {
+        fs::write(self.dir.join(name), content).unwrap();
+    }
+}
+
+// 在工作目录中启动 serve，退出时结束进程
+struct ApiServer {
+    child: Child,
+    url: String,
+}
+
+impl ApiServer {
+    fn start(workspace: &Workspace, token: &str) -> Self {
+        let port = std::net::TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap().port();
+        let child = Command::new(env!("CARGO_BIN_EXE_todolist-cli"))
+            .args(["serve", "--port", &port.to_string(), "--token", token])
+            .current_dir(&workspace.dir)
+            .stdout(std::process::Stdio::null())
+            .spawn()
+            .unwrap();
+        let url = format!("http://127.0.0.1:{}", port);
+        for _ in 0..50 {
+            if ureq::get(&format!("{}/openapi.json", url)).call().is_ok() {
+                break;
+            }
+            std::thread::sleep(Duration::from_millis(100));
+        }
+        ApiServer { child, url }
+    }
+
+    fn status(&self, method: &str, path: &str, token: Option<&str>, body: &str) -> u16 {
+        let mut request = ureq::request(method, &format!("{}{}", self.url, path));
+        if let Some(token) = token {
+            request = request.set("Authorization", &format!("Bearer {}", token));
+        }
+        match request.send_string(body) {
+            Ok(response) => response.status(),
+            Err(ureq::Error::Status(status, _)) => status,
+            Err(err) => panic!("{} {} 失败: {}", method, path, err),
+        }
+    }
+}
+
+impl Drop for ApiServer {
+    fn drop(&mut self) {
+        self.child.kill().ok();
+        self.child.wait().ok();
+    }
+}
+
+impl Drop for Workspace {
+    fn drop(&mut self) {
+        fs::remove_dir_all(&self.dir).ok();
+    }
+}
+
+#[test]
+fn export_keeps_its_own_format_flag() {
+    let workspace = Workspace::new("export-format");
+    workspace.ok(&["add", "写报告", "#work"]);
+    let csv = workspace.ok(&["export", "--format", "csv"]);
+    assert!(csv.starts_with("id,uuid,description"), "{}", csv);
+    assert!(csv.contains("写报告"));
+}
+
+#[test]
+fn import_keeps_its_own_format_flag() {
+    let workspace = Workspace::new("import-format");
+    workspace.write("a.txt", "(A) buy milk +home\nx 2024-01-02 done item\n");
+    workspace.ok(&["import", "a.txt", "--format", "todo.txt"]);
+    let list = workspace.ok(&["list", "--format", "jsonl"]);
+    assert_eq!(list.lines().count(), 2, "{}", list);
+    assert!(list.contains("buy milk"));
+}
+
+#[test]
+fn report_keeps_its_own_format_flag() {
+    let workspace = Workspace::new("report-format");
+    workspace.ok(&["add", "写报告"]);
+    let markdown = workspace.ok(&["report", "--today"]);
+    let text = workspace.ok(&["report", "--today", "--format", "text"]);
+    assert!(markdown.starts_with('#'), "{}", markdown);
+    assert!(!text.starts_with('#'), "{}", text);
+    assert_ne!(markdown, text);
+}
+
+#[test]
+fn output_format_before_or_after_command() {
+    let workspace = Workspace::new("output-format");
+    workspace.ok(&["add", "a"]);
+    assert!(workspace.ok(&["--format", "json", "list"]).trim_start().starts_with('['));
+    assert!(workspace.ok(&["list", "--format=jsonl"]).starts_with('{'));
+    let output = workspace.run(&["--format", "json", "time"]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("\"code\":\"invalid_input\""));
+}
+
+#[test]
+fn sync_path_argument_uses_git() {
+    let workspace = Workspace::new("sync-git");
+    let remote = workspace.dir.join("remote.git");
+    let status = Command::new("git").args(["init", "--quiet", "--bare"]).arg(&remote).status().unwrap();
+    assert!(status.success());
+    let data = workspace.dir.join("data");
+    fs::create_dir(&data).unwrap();
+    let data_workspace = Workspace { dir: data };
+    data_workspace.ok(&["git-init"]);
+    data_workspace.ok(&["add", "写报告"]);
+    data_workspace.ok(&["sync", remote.to_str().unwrap()]);
+    // 推送到裸仓库，而不是把 tasks.json 当作副本写进去
+    assert!(!remote.join("tasks.json").exists());
+    let log = Command::new("git").args(["--git-dir", remote.to_str().unwrap(), "log", "--oneline"]).output().unwrap();
+    assert!(String::from_utf8_lossy(&log.stdout).contains("写报告"));
+}
+
+#[test]
+fn git_storage_ignores_an_enclosing_repository() {
+    let workspace = Workspace::new("git-enclosing");
+    let status = Command::new("git").args(["init", "--quiet"]).current_dir(&workspace.dir).status().unwrap();
+    assert!(status.success());
+    Command::new("git").args(["config", "todolist.autocommit", "true"]).current_dir(&workspace.dir).status().unwrap();
+    let data = workspace.dir.join("data");
+    fs::create_dir(&data).unwrap();
+    let data_workspace = Workspace { dir: data };
+
+    // 外层仓库开启了自动提交，也不会把数据目录中的修改提交进去
+    data_workspace.ok(&["add", "写报告"]);
+    let log = Command::new("git").args(["log", "--oneline"]).current_dir(&workspace.dir).output().unwrap();
+    assert!(log.stdout.is_empty(), "{}", String::from_utf8_lossy(&log.stdout));
+    let output = data_workspace.run(&["git-init"]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("数据目录位于仓库"), "{}", String::from_utf8_lossy(&output.stderr));
+}
+
+#[test]
+fn sync_replica_merges_both_ways() {
+    let workspace = Workspace::new("sync-replica");
+    workspace.ok(&["add", "本地任务"]);
+    let shared = workspace.dir.join("shared");
+    fs::create_dir(&shared).unwrap();
+    let other_device = Workspace { dir: shared };
+    other_device.ok(&["add", "另一台设备的任务"]);
+
+    workspace.ok(&["sync", "--replica", "shared"]);
+    for side in [&workspace, &other_device] {
+        let list = side.ok(&["list"]);
+        assert!(list.contains("本地任务") && list.contains("另一台设备的任务"), "{}", list);
+    }
+    assert!(!workspace.run(&["sync", "--replica"]).status.success());
+}
+
+#[test]
+fn tui_without_a_terminal_reports_an_error() {
+    let workspace = Workspace::new("tui-no-tty");
+    // 旧格式的文件不会在报错之前被升级或备份
+    let legacy = r#"{"name":"Task List","tasks":[{"id":1,"description":"旧任务","status":"Pending","created_at":"2025-01-01T09:00:00","completed_at":null,"tags":[],"position":0}],"next_id":2}"#;
+    workspace.write("tasks.json", legacy);
+    let output = workspace.run(&["tui"]);
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("交互式终端") && !stderr.contains("panicked"), "{}", stderr);
+    assert_eq!(fs::read_to_string(workspace.dir.join("tasks.json")).unwrap(), legacy);
+    assert!(!workspace.dir.join("tasks.json.bak").exists());
+}
+
+#[test]
+fn repl_keeps_an_unreadable_task_file() {
+    let workspace = Workspace::new("repl-unreadable");
+    workspace.write("tasks.json", "{ 不是 JSON");
+    let output = Command::new(env!("CARGO_BIN_EXE_todolist-cli"))
+        .current_dir(&workspace.dir)
+        .stdin(std::process::Stdio::null())
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("读取 tasks.json 失败"));
+    assert_eq!(fs::read_to_string(workspace.dir.join("tasks.json")).unwrap(), "{ 不是 JSON");
+}
+
+#[test]
+fn suspend_stops_the_timer() {
+    let workspace = Workspace::new("suspend-timer");
+    workspace.ok(&["add", "写报告"]);
+    workspace.ok(&["start", "1"]);
+    let suspended = workspace.ok(&["suspend", "1", "--format", "jsonl"]);
+    assert!(suspended.contains("\"status\":\"suspended\""), "{}", suspended);
+    assert!(suspended.contains("\"timer_running\":false"), "{}", suspended);
+}
+
+#[test]
+fn bulk_commands_reject_bare_words() {
+    let workspace = Workspace::new("bulk-select");
+    workspace.ok(&["add", "fix login"]);
+    workspace.ok(&["add", "fix logout", "#work"]);
+    let output = workspace.run(&["delete", "fix"]);
+    assert!(!output.status.success());
+    assert!(!workspace.run(&["done", "1", "typo"]).status.success());
+    assert_eq!(workspace.ok(&["list", "--format", "jsonl"]).lines().count(), 2);
+
+    workspace.ok(&["done", "text:login"]);
+    workspace.ok(&["delete", "tag:work"]);
+    let list = workspace.ok(&["list", "--format", "jsonl"]);
+    assert_eq!(list.lines().count(), 1);
+    assert!(list.contains("\"status\":\"completed\""), "{}", list);
+}
+
+#[test]
+fn due_dates_and_priorities_sort_and_group() {
+    let workspace = Workspace::new("due-priority");
+    workspace.ok(&["add", "没有期限"]);
+    workspace.ok(&["add", "明天交"]);
+    workspace.ok(&["add", "已经过期"]);
+    workspace.ok(&["edit", "2", "--due", "tomorrow", "--priority", "low"]);
+    workspace.ok(&["edit", "3", "--due", "2000-01-01", "--priority", "high"]);
+    assert!(!workspace.run(&["edit", "1", "--due", "someday"]).status.success());
+
+    let by_due = workspace.ok(&["list", "--sort", "due", "--format", "tsv"]);
+    let ids: Vec<&str> = by_due.lines().skip(1).map(|line| line.split('\t').next().unwrap()).collect();
+    assert_eq!(ids, ["3", "2", "1"]);
+    assert!(by_due.lines().next().unwrap().contains("\tdue\tpriority"), "{}", by_due);
+
+    let grouped = workspace.ok(&["list", "--group", "due"]);
+    assert!(grouped.contains("已过期") && grouped.contains("无截止日期"), "{}", grouped);
+
+    workspace.ok(&["edit", "3", "--priority", "-", "--notes", "问过负责人"]);
+    let task = workspace.ok(&["list", "--format", "jsonl", "text:过期"]);
+    assert!(task.contains("\"due\":\"2000-01-01\"") && task.contains("\"priority\":null") && task.contains("\"notes\":\"问过负责人\""), "{}", task);
+}
+
+#[test]
+fn recurring_tasks_round_trip_through_ics() {
+    let workspace = Workspace::new("recurring-ics");
+    workspace.ok(&["add", "交周报", "#work"]);
+    workspace.ok(&["edit", "1", "--due", "2025-07-04", "--repeat", "weekly"]);
+    workspace.ok(&["suspend", "1"]);
+    workspace.ok(&["export", "--format", "ics", "-o", "tasks.ics"]);
+    let ics = std::fs::read_to_string(workspace.dir.join("tasks.ics")).unwrap();
+    assert!(ics.contains("RRULE:FREQ=WEEKLY") && ics.contains("X-TODOLIST-STATUS:SUSPENDED"), "{}", ics);
+
+    let other = Workspace::new("recurring-ics-import");
+    fs::copy(workspace.dir.join("tasks.ics"), other.dir.join("tasks.ics")).unwrap();
+    other.ok(&["import", "tasks.ics"]);
+    let task = other.ok(&["list", "--format", "jsonl"]);
+    assert!(task.contains("\"status\":\"suspended\"") && task.contains("\"due\":\"2025-07-04\"") && task.contains("\"repeat\":\"1w\""), "{}", task);
+
+}
+
+#[test]
+fn api_checks_tokens_body_size_and_replaces_on_put() {
+    let workspace = Workspace::new("api-auth");
+    workspace.ok(&["add", "写报告", "#work"]);
+    workspace.ok(&["edit", "1", "--priority", "high", "--due", "2025-07-04"]);
+    let server = ApiServer::start(&workspace, "secret");
+
+    assert_eq!(server.status("GET", "/tasks", Some("secret"), ""), 200);
+    assert_eq!(server.status("GET", "/tasks", Some("secreT"), ""), 401);
+    // 地址中的令牌只用于 /events
+    assert_eq!(server.status("GET", "/tasks?token=secret", None, ""), 401);
+
+    let huge = format!("{{\"description\":\"{}\"}}", "x".repeat(2 * 1024 * 1024));
+    assert_eq!(server.status("POST", "/tasks", Some("secret"), &huge), 413);
+
+    // PUT 整体替换，PATCH 只修改给出的字段
+    assert_eq!(server.status("PATCH", "/tasks/1", Some("secret"), r#"{"description":"写周报"}"#), 200);
+    let task = workspace.ok(&["list", "--format", "jsonl"]);
+    assert!(task.contains("\"priority\":\"high\"") && task.contains("写周报"), "{}", task);
+    assert_eq!(server.status("PUT", "/tasks/1", Some("secret"), r#"{"description":"写月报"}"#), 200);
+    let task = workspace.ok(&["list", "--format", "jsonl"]);
+    assert!(task.contains("\"priority\":null") && task.contains("\"due\":null") && task.contains("\"tags\":[]"), "{}", task);
+    assert_eq!(server.status("PUT", "/tasks/1", Some("secret"), r#"{"tags":["work"]}"#), 400);
+}
+
+#[test]
+fn event_streams_are_limited() {
+    use std::io::{Read, Write};
+    let workspace = Workspace::new("api-events");
+    let server = ApiServer::start(&workspace, "secret");
+    let address = server.url.trim_start_matches("http://").to_string();
+    let open_stream = || {
+        let mut stream = std::net::TcpStream::connect(&address).unwrap();
+        stream.set_read_timeout(Some(Duration::from_secs(5))).unwrap();
+        write!(stream, "GET /events?token=secret HTTP/1.1\r\nHost: {}\r\n\r\n", address).unwrap();
+        let mut head = [0; 12];
+        stream.read_exact(&mut head).unwrap();
+        (stream, String::from_utf8_lossy(&head).into_owned())
+    };
+    let streams: Vec<_> = (0..16).map(|_| open_stream()).collect();
+    assert!(streams.iter().all(|(_, head)| head.ends_with("200")), "{:?}", streams.iter().map(|(_, h)| h).collect::<Vec<_>>());
+    assert!(open_stream().1.ends_with("503"));
+}
+
+#[test]
+fn rpc_update_then_get_round_trips() {
+    let workspace = Workspace::new("rpc-update");
+    workspace.ok(&["add", "写报告", "#work"]);
+    workspace.ok(&["edit", "1", "--priority", "high"]);
+    let requests = [
+        r#"{"jsonrpc":"2.0","id":1,"method":"tasks.update","params":{"id":1,"description":"写周报","due":"2030-01-01","priority":null}}"#,
+        r#"{"jsonrpc":"2.0","id":2,"method":"tasks.get","params":{"id":1}}"#,
+    ];
+    let output = workspace.run_with_input(&["rpc"], &(requests.join("\n") + "\n"));
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    // 修改后还会推送 tasks.changed 通知，只看带 id 的响应
+    let get = stdout.lines()
+        .find(|line| line.contains("\"id\":2"))
+        .unwrap_or_else(|| panic!("{}", stdout));
+    let response: serde_json::Value = serde_json::from_str(get).unwrap();
+    let task = &response["result"];
+    assert_eq!(task["description"], "写周报", "{}", get);
+    assert_eq!(task["due"], "2030-01-01");
+    assert!(task["priority"].is_null());
+    assert_eq!(task["tags"], serde_json::json!(["work"]));
+}